@@ -1,4 +1,3 @@
-pub mod chain_info;
 pub mod common_storage;
 pub mod rewards_wrapper;
 pub mod unique_payments;
@@ -1,6 +1,7 @@
+use chain_info::CurrentChainInfo;
 use common_structs::PaymentsVec;
 
-use crate::common::{chain_info::CurrentChainInfo, rewards_wrapper::MergedRewardsWrapper};
+use crate::common::rewards_wrapper::MergedRewardsWrapper;
 
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
@@ -29,6 +30,7 @@ pub enum WithdrawType {
     MetastakingTokens,
     RewardTokens,
     AllTokens,
+    RewardDust,
 }
 
 #[derive(TypeAbi, TopEncode)]
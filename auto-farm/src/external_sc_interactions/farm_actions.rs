@@ -17,6 +17,7 @@ pub trait FarmActionsModule:
     + crate::whitelists::farms_whitelist::FarmsWhitelistModule
     + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
     + crate::user_tokens::user_farm_tokens::UserFarmTokensModule
+    + crate::thresholds::ThresholdsModule
     + crate::user_tokens::user_rewards::UserRewardsModule
     + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
     + crate::fees::FeesModule
@@ -56,6 +57,11 @@ pub trait FarmActionsModule:
                 continue;
             }
 
+            if !self.has_enough_farm_rewards_to_claim(farm_id, &farm_addr, &farm_token) {
+                new_user_farm_tokens.push(farm_token);
+                continue;
+            }
+
             let claim_result = self.call_farm_claim(farm_addr, user.clone(), farm_token);
             new_user_farm_tokens.push(claim_result.new_farm_token);
 
@@ -65,6 +71,26 @@ pub trait FarmActionsModule:
         user_tokens_mapper.set(&new_user_farm_tokens);
     }
 
+    fn has_enough_farm_rewards_to_claim(
+        &self,
+        farm_id: AddressId,
+        farm_addr: &ManagedAddress,
+        farm_token: &EsdtTokenPayment,
+    ) -> bool {
+        let min_rewards = self.min_farm_rewards_to_claim(farm_id).get();
+        if min_rewards == 0 {
+            return true;
+        }
+
+        let raw_attributes: ManagedBuffer = self
+            .blockchain()
+            .get_token_attributes(&farm_token.token_identifier, farm_token.token_nonce);
+        let estimated_rewards =
+            self.estimate_base_farm_rewards(farm_addr, raw_attributes, &farm_token.amount);
+
+        estimated_rewards >= min_rewards
+    }
+
     /// user_farm_ids contains the associated farm_id for each token in user_farm_tokens
     fn compound_staking_rewards_with_existing_farm_position(
         &self,
@@ -1,6 +1,7 @@
 multiversx_sc::imports!();
 
 use common_structs::PaymentsVec;
+use farm_staking_proxy::dual_yield_token::DualYieldTokenAttributes;
 pub use farm_staking_proxy::proxy_actions::claim::ProxyTrait as _;
 use farm_staking_proxy::result_types::ClaimDualYieldResult;
 
@@ -13,6 +14,7 @@ pub trait MetastakingActionsModule:
     + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
     + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
     + crate::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule
+    + crate::thresholds::ThresholdsModule
     + crate::user_tokens::user_rewards::UserRewardsModule
     + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
     + crate::fees::FeesModule
@@ -48,6 +50,11 @@ pub trait MetastakingActionsModule:
             }
 
             let ms_addr = unsafe { opt_ms_addr.unwrap_unchecked() };
+            if !self.has_enough_metastaking_rewards_to_claim(ms_id, &ms_addr, &dual_yield_token) {
+                new_user_dual_yield_tokens.push(dual_yield_token);
+                continue;
+            }
+
             let claim_result = self.call_metastaking_claim(ms_addr, user.clone(), dual_yield_token);
             new_user_dual_yield_tokens.push(claim_result.new_dual_yield_tokens);
 
@@ -58,6 +65,44 @@ pub trait MetastakingActionsModule:
         user_tokens_mapper.set(&new_user_dual_yield_tokens);
     }
 
+    /// Only the LP farm position backing the dual yield tokens is taken into account
+    fn has_enough_metastaking_rewards_to_claim(
+        &self,
+        ms_id: AddressId,
+        ms_addr: &ManagedAddress,
+        dual_yield_token: &EsdtTokenPayment,
+    ) -> bool {
+        let min_rewards = self.min_metastaking_rewards_to_claim(ms_id).get();
+        if min_rewards == 0 {
+            return true;
+        }
+
+        let attributes: DualYieldTokenAttributes<Self::Api> = self
+            .blockchain()
+            .get_token_attributes(&dual_yield_token.token_identifier, dual_yield_token.token_nonce);
+        if attributes.virtual_pos_token_amount == 0 {
+            return false;
+        }
+
+        // the LP farm tokens are held by the metastaking contract
+        let lp_farm_token_id = self.get_lp_farm_token_id_mapper(ms_addr.clone()).get();
+        let lp_farm_token_data = self.blockchain().get_esdt_token_data(
+            ms_addr,
+            &lp_farm_token_id,
+            attributes.lp_farm_token_nonce,
+        );
+        let lp_farm_token_amount = &attributes.lp_farm_token_amount * &dual_yield_token.amount
+            / &attributes.virtual_pos_token_amount;
+        let lp_farm_addr = self.get_lp_farm_address_mapper(ms_addr.clone()).get();
+        let estimated_rewards = self.estimate_base_farm_rewards(
+            &lp_farm_addr,
+            lp_farm_token_data.attributes,
+            &lp_farm_token_amount,
+        );
+
+        estimated_rewards >= min_rewards
+    }
+
     fn call_metastaking_claim(
         &self,
         ms_address: ManagedAddress,
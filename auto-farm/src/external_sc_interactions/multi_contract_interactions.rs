@@ -1,5 +1,7 @@
 multiversx_sc::imports!();
 
+use common_structs::PaymentsVec;
+
 use crate::common::{rewards_wrapper::RewardsWrapper, unique_payments::UniquePayments};
 
 use super::metabonding_actions::SingleMetabondingClaimArg;
@@ -25,6 +27,7 @@ pub trait MultiContractInteractionsModule:
     + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
     + crate::user_tokens::user_rewards::UserRewardsModule
     + crate::user_tokens::withdraw_tokens::WithdrawTokensModule
+    + crate::thresholds::ThresholdsModule
     + crate::fees::FeesModule
     + crate::events::EventsModule
     + lkmex_transfer::energy_transfer::EnergyTransferModule
@@ -79,6 +82,8 @@ pub trait MultiContractInteractionsModule:
         let user_farm_tokens_mapper = self.user_farm_tokens(user_id);
 
         let mut user_wrapped_rewards = user_rewards_mapper.get();
+        self.merge_user_reward_dust(user_id, &mut user_wrapped_rewards);
+
         let mut user_rewards = user_wrapped_rewards.other_tokens.into_payments();
         let mut user_farm_tokens = user_farm_tokens_mapper.get();
         let user_farm_ids = self.get_farm_ids_for_farm_tokens(&user_farm_tokens);
@@ -102,10 +107,40 @@ pub trait MultiContractInteractionsModule:
             len -= 1;
         }
 
-        user_wrapped_rewards.other_tokens = UniquePayments::new_from_unique_payments(user_rewards);
+        let remaining_rewards = self.sweep_reward_dust(user_id, user_rewards);
+        user_wrapped_rewards.other_tokens =
+            UniquePayments::new_from_unique_payments(remaining_rewards);
         user_rewards_mapper.set(&user_wrapped_rewards);
         user_farm_tokens_mapper.set(&user_farm_tokens);
 
         self.emit_proxy_claim_event(user, &user_wrapped_rewards, &user_farm_tokens);
     }
+
+    /// Moves the rewards below their dust threshold to the user's dust bucket.
+    /// The bucket is added back to the rewards at the start of the next compounding.
+    /// Returns the remaining rewards.
+    fn sweep_reward_dust(
+        &self,
+        user_id: AddressId,
+        rewards: PaymentsVec<Self::Api>,
+    ) -> PaymentsVec<Self::Api> {
+        let mut remaining_rewards = PaymentsVec::new();
+        let mut reward_dust = PaymentsVec::new();
+        for payment in &rewards {
+            if self.is_below_dust_threshold(&payment) {
+                reward_dust.push(payment);
+            } else {
+                remaining_rewards.push(payment);
+            }
+        }
+
+        if !reward_dust.is_empty() {
+            self.add_user_reward_dust(
+                user_id,
+                UniquePayments::new_from_unique_payments(reward_dust),
+            );
+        }
+
+        remaining_rewards
+    }
 }
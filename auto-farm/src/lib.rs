@@ -8,6 +8,7 @@ pub mod external_sc_interactions;
 pub mod external_storage_read;
 pub mod fees;
 pub mod registration;
+pub mod thresholds;
 pub mod user_tokens;
 pub mod whitelists;
 
@@ -27,11 +28,14 @@ pub trait AutoFarm:
     + external_sc_interactions::locked_token_merging::LockedTokenMergingModule
     + whitelists::metastaking_whitelist::MetastakingWhitelistModule
     + user_tokens::user_metastaking_tokens::UserMetastakingTokensModule
+    + whitelists::position_creators_whitelist::PositionCreatorsWhitelistModule
+    + user_tokens::deposit_for_user::DepositForUserModule
     + external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
     + external_sc_interactions::metastaking_actions::MetastakingActionsModule
     + external_sc_interactions::multi_contract_interactions::MultiContractInteractionsModule
     + user_tokens::user_rewards::UserRewardsModule
     + user_tokens::withdraw_tokens::WithdrawTokensModule
+    + thresholds::ThresholdsModule
     + fees::FeesModule
     + events::EventsModule
     + lkmex_transfer::energy_transfer::EnergyTransferModule
@@ -40,11 +40,13 @@ pub trait RegistrationModule:
         let farm_tokens = self.withdraw_all_tokens(&caller, &self.user_farm_tokens(user_id));
         let ms_tokens = self.withdraw_all_tokens(&caller, &self.user_metastaking_tokens(user_id));
         let claimed_rewards = self.user_claim_rewards(caller.clone(), user_id);
+        let claimed_dust = self.user_claim_reward_dust(&caller, user_id);
         let _ = ids_mapper.remove_by_id(user_id);
 
         let mut results = farm_tokens;
         results.append_vec(ms_tokens);
         results.append_vec(claimed_rewards);
+        results.append_vec(claimed_dust);
 
         self.emit_token_withdrawal_event(&caller, WithdrawType::AllTokens, &results);
 
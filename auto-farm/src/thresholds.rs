@@ -0,0 +1,156 @@
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait ThresholdsModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
+    + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
+    + utils::UtilsModule
+{
+    /// Minimum amount of farm tokens a single deposit payment must have.
+    /// Set to 0 to disable the check.
+    #[only_owner]
+    #[endpoint(setMinFarmDeposit)]
+    fn set_min_farm_deposit(&self, farm_address: ManagedAddress, min_amount: BigUint) {
+        let farm_id = self.farm_ids().get_id_non_zero(&farm_address);
+        self.min_farm_deposit(farm_id).set(min_amount);
+    }
+
+    /// Minimum amount of dual yield tokens a single deposit payment must have.
+    /// Set to 0 to disable the check.
+    #[only_owner]
+    #[endpoint(setMinMetastakingDeposit)]
+    fn set_min_metastaking_deposit(
+        &self,
+        metastaking_address: ManagedAddress,
+        min_amount: BigUint,
+    ) {
+        let ms_id = self
+            .metastaking_ids()
+            .get_id_non_zero(&metastaking_address);
+        self.min_metastaking_deposit(ms_id).set(min_amount);
+    }
+
+    /// Farm positions with estimated pending rewards below this amount are not claimed.
+    /// The estimation only accounts for base farm rewards.
+    #[only_owner]
+    #[endpoint(setMinFarmRewardsToClaim)]
+    fn set_min_farm_rewards_to_claim(&self, farm_address: ManagedAddress, min_amount: BigUint) {
+        let farm_id = self.farm_ids().get_id_non_zero(&farm_address);
+        self.min_farm_rewards_to_claim(farm_id).set(min_amount);
+    }
+
+    /// Metastaking positions with estimated pending LP farm rewards below this amount
+    /// are not claimed. The estimation only accounts for base LP farm rewards.
+    #[only_owner]
+    #[endpoint(setMinMetastakingRewardsToClaim)]
+    fn set_min_metastaking_rewards_to_claim(
+        &self,
+        metastaking_address: ManagedAddress,
+        min_amount: BigUint,
+    ) {
+        let ms_id = self
+            .metastaking_ids()
+            .get_id_non_zero(&metastaking_address);
+        self.min_metastaking_rewards_to_claim(ms_id).set(min_amount);
+    }
+
+    /// Reward tokens (other than locked tokens) with an amount below this threshold
+    /// are moved to the user's dust bucket after each claim.
+    ///
+    /// The bucket is merged back into the user's rewards before the next compounding,
+    /// so the dust is compounded once it adds up to the threshold.
+    /// The bucket therefore holds at most one entry per reward token.
+    #[only_owner]
+    #[endpoint(setRewardDustThreshold)]
+    fn set_reward_dust_threshold(&self, token_id: TokenIdentifier, threshold: BigUint) {
+        require!(token_id.is_valid_esdt_identifier(), "Invalid token ID");
+
+        self.reward_dust_threshold(&token_id).set(threshold);
+    }
+
+    fn require_min_farm_deposit(&self, farm_id: AddressId, amount: &BigUint) {
+        let min_amount = self.min_farm_deposit(farm_id).get();
+        require!(amount >= &min_amount, "Deposit amount too low");
+    }
+
+    fn require_min_metastaking_deposit(&self, ms_id: AddressId, amount: &BigUint) {
+        let min_amount = self.min_metastaking_deposit(ms_id).get();
+        require!(amount >= &min_amount, "Deposit amount too low");
+    }
+
+    fn is_below_dust_threshold(&self, payment: &EsdtTokenPayment) -> bool {
+        let threshold = self.reward_dust_threshold(&payment.token_identifier).get();
+        payment.amount < threshold
+    }
+
+    /// Estimates the base rewards of a position from the farm's current reward per share.
+    /// Boosted rewards are not taken into account, so the estimation is a lower bound.
+    fn estimate_base_farm_rewards(
+        &self,
+        farm_addr: &ManagedAddress,
+        raw_farm_token_attributes: ManagedBuffer,
+        farm_token_amount: &BigUint,
+    ) -> BigUint {
+        // reward_per_share is the first field for both farm and farm-staking token attributes
+        let mut attributes_input = ManagedBufferNestedDecodeInput::new(raw_farm_token_attributes);
+        let position_rps = BigUint::dep_decode(&mut attributes_input)
+            .unwrap_or_else(|_| sc_panic!("Invalid farm token attributes"));
+
+        let farm_rps = self.get_reward_per_share_mapper(farm_addr.clone()).get();
+        if farm_rps <= position_rps {
+            return BigUint::zero();
+        }
+
+        let division_safety_constant = self
+            .get_division_safety_constant_mapper(farm_addr.clone())
+            .get();
+
+        farm_token_amount * &(farm_rps - position_rps) / division_safety_constant
+    }
+
+    #[view(getMinFarmDeposit)]
+    fn get_min_farm_deposit_view(&self, farm_address: ManagedAddress) -> BigUint {
+        let farm_id = self.farm_ids().get_id(&farm_address);
+        self.min_farm_deposit(farm_id).get()
+    }
+
+    #[view(getMinMetastakingDeposit)]
+    fn get_min_metastaking_deposit_view(&self, metastaking_address: ManagedAddress) -> BigUint {
+        let ms_id = self.metastaking_ids().get_id(&metastaking_address);
+        self.min_metastaking_deposit(ms_id).get()
+    }
+
+    #[view(getMinFarmRewardsToClaim)]
+    fn get_min_farm_rewards_to_claim_view(&self, farm_address: ManagedAddress) -> BigUint {
+        let farm_id = self.farm_ids().get_id(&farm_address);
+        self.min_farm_rewards_to_claim(farm_id).get()
+    }
+
+    #[view(getMinMetastakingRewardsToClaim)]
+    fn get_min_metastaking_rewards_to_claim_view(
+        &self,
+        metastaking_address: ManagedAddress,
+    ) -> BigUint {
+        let ms_id = self.metastaking_ids().get_id(&metastaking_address);
+        self.min_metastaking_rewards_to_claim(ms_id).get()
+    }
+
+    #[storage_mapper("minFarmDeposit")]
+    fn min_farm_deposit(&self, farm_id: AddressId) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("minMSDeposit")]
+    fn min_metastaking_deposit(&self, ms_id: AddressId) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("minFarmRewardsToClaim")]
+    fn min_farm_rewards_to_claim(&self, farm_id: AddressId) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("minMSRewardsToClaim")]
+    fn min_metastaking_rewards_to_claim(&self, ms_id: AddressId) -> SingleValueMapper<BigUint>;
+
+    #[view(getRewardDustThreshold)]
+    #[storage_mapper("rewardDustThreshold")]
+    fn reward_dust_threshold(&self, token_id: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+}
@@ -0,0 +1,49 @@
+multiversx_sc::imports!();
+
+use common_structs::PaymentsVec;
+
+#[multiversx_sc::module]
+pub trait DepositForUserModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::common::common_storage::CommonStorageModule
+    + crate::whitelists::farms_whitelist::FarmsWhitelistModule
+    + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
+    + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
+    + crate::whitelists::position_creators_whitelist::PositionCreatorsWhitelistModule
+    + crate::thresholds::ThresholdsModule
+    + super::user_farm_tokens::UserFarmTokensModule
+    + super::user_metastaking_tokens::UserMetastakingTokensModule
+    + super::withdraw_tokens::WithdrawTokensModule
+    + crate::events::EventsModule
+    + utils::UtilsModule
+{
+    /// Deposits farm and metastaking tokens in the name of the given user.
+    /// Can only be called by trusted position creators.
+    #[payable("*")]
+    #[endpoint(depositForUser)]
+    fn deposit_for_user(&self, user: ManagedAddress) {
+        let caller = self.blockchain().get_caller();
+        self.require_position_creator(&caller);
+        require!(!user.is_zero(), "Invalid user address");
+
+        let payments = self.get_non_empty_payments();
+        let mut farm_tokens = PaymentsVec::new();
+        let mut metastaking_tokens = PaymentsVec::new();
+        for payment in &payments {
+            let farm_id = self.farm_for_farm_token(&payment.token_identifier).get();
+            if farm_id != NULL_ID {
+                farm_tokens.push(payment);
+            } else {
+                metastaking_tokens.push(payment);
+            }
+        }
+
+        if !farm_tokens.is_empty() {
+            self.add_user_farm_tokens(&user, &farm_tokens);
+        }
+        if !metastaking_tokens.is_empty() {
+            self.add_user_metastaking_tokens(&user, &metastaking_tokens);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+pub mod deposit_for_user;
 pub mod user_farm_tokens;
 pub mod user_metastaking_tokens;
 pub mod user_rewards;
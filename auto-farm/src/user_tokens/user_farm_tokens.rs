@@ -9,6 +9,7 @@ pub trait UserFarmTokensModule:
     + crate::common::common_storage::CommonStorageModule
     + crate::whitelists::farms_whitelist::FarmsWhitelistModule
     + crate::external_storage_read::farm_storage_read::FarmStorageReadModule
+    + crate::thresholds::ThresholdsModule
     + super::withdraw_tokens::WithdrawTokensModule
     + crate::events::EventsModule
     + utils::UtilsModule
@@ -18,18 +19,23 @@ pub trait UserFarmTokensModule:
     fn deposit_farm_tokens(&self) {
         let payments = self.get_non_empty_payments();
         let caller = self.blockchain().get_caller();
-        let user_id = self.user_ids().get_id_or_insert(&caller);
+        self.add_user_farm_tokens(&caller, &payments);
+    }
+
+    fn add_user_farm_tokens(&self, user: &ManagedAddress, payments: &PaymentsVec<Self::Api>) {
+        let user_id = self.user_ids().get_id_or_insert(user);
 
         self.user_farm_tokens(user_id).update(|tokens| {
-            for payment in &payments {
+            for payment in payments {
                 let farm_id = self.farm_for_farm_token(&payment.token_identifier).get();
                 require!(farm_id != NULL_ID, "Invalid token");
+                self.require_min_farm_deposit(farm_id, &payment.amount);
 
                 tokens.push(payment);
             }
         });
 
-        self.emit_token_deposit_event(&caller, DepositType::FarmTokens, &payments);
+        self.emit_token_deposit_event(user, DepositType::FarmTokens, payments);
     }
 
     #[endpoint(withdrawAllFarmTokens)]
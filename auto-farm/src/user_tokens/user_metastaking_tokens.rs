@@ -9,6 +9,7 @@ pub trait UserMetastakingTokensModule:
     + crate::common::common_storage::CommonStorageModule
     + crate::whitelists::metastaking_whitelist::MetastakingWhitelistModule
     + crate::external_storage_read::metastaking_storage_read::MetastakingStorageReadModule
+    + crate::thresholds::ThresholdsModule
     + super::withdraw_tokens::WithdrawTokensModule
     + crate::events::EventsModule
     + utils::UtilsModule
@@ -18,20 +19,29 @@ pub trait UserMetastakingTokensModule:
     fn deposit_metastaking_tokens(&self) {
         let payments = self.get_non_empty_payments();
         let caller = self.blockchain().get_caller();
-        let user_id = self.user_ids().get_id_or_insert(&caller);
+        self.add_user_metastaking_tokens(&caller, &payments);
+    }
+
+    fn add_user_metastaking_tokens(
+        &self,
+        user: &ManagedAddress,
+        payments: &PaymentsVec<Self::Api>,
+    ) {
+        let user_id = self.user_ids().get_id_or_insert(user);
 
         self.user_metastaking_tokens(user_id).update(|tokens| {
-            for payment in &payments {
+            for payment in payments {
                 let ms_id = self
                     .metastaking_for_dual_yield_token(&payment.token_identifier)
                     .get();
                 require!(ms_id != NULL_ID, "Invalid token");
+                self.require_min_metastaking_deposit(ms_id, &payment.amount);
 
                 tokens.push(payment);
             }
         });
 
-        self.emit_token_deposit_event(&caller, DepositType::MetastakingTokens, &payments);
+        self.emit_token_deposit_event(user, DepositType::MetastakingTokens, payments);
     }
 
     #[endpoint(withdrawAllMetastakingTokens)]
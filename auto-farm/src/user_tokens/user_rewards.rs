@@ -2,7 +2,10 @@ multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
 use crate::{
-    common::rewards_wrapper::{MergedRewardsWrapper, RewardsWrapper},
+    common::{
+        rewards_wrapper::{MergedRewardsWrapper, RewardsWrapper},
+        unique_payments::UniquePayments,
+    },
     events::WithdrawType,
 };
 use common_structs::PaymentsVec;
@@ -38,6 +41,29 @@ pub trait UserRewardsModule:
         self.claim_common(user, rewards_mapper)
     }
 
+    #[endpoint(userClaimRewardDust)]
+    fn user_claim_reward_dust_endpoint(&self) -> PaymentsVec<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        let user_id = self.user_ids().get_id_non_zero(&caller);
+        let claimed_tokens = self.user_claim_reward_dust(&caller, user_id);
+        self.emit_token_withdrawal_event(&caller, WithdrawType::RewardDust, &claimed_tokens);
+
+        claimed_tokens
+    }
+
+    fn user_claim_reward_dust(
+        &self,
+        user: &ManagedAddress,
+        user_id: AddressId,
+    ) -> PaymentsVec<Self::Api> {
+        let dust_tokens = self.take_user_reward_dust(user_id).into_payments();
+        if !dust_tokens.is_empty() {
+            self.send().direct_multi(user, &dust_tokens);
+        }
+
+        dust_tokens
+    }
+
     #[view(getUserRewards)]
     fn get_user_rewards_view(&self, user: ManagedAddress) -> MergedRewardsWrapper<Self::Api> {
         let user_id = self.user_ids().get_id(&user);
@@ -79,9 +105,51 @@ pub trait UserRewardsModule:
         });
     }
 
+    #[view(getUserRewardDust)]
+    fn get_user_reward_dust_view(&self, user: ManagedAddress) -> PaymentsVec<Self::Api> {
+        let user_id = self.user_ids().get_id(&user);
+        let dust_mapper = self.user_reward_dust(user_id);
+        if user_id != NULL_ID && !dust_mapper.is_empty() {
+            dust_mapper.get().into_payments()
+        } else {
+            PaymentsVec::new()
+        }
+    }
+
+    fn add_user_reward_dust(&self, user_id: AddressId, dust: UniquePayments<Self::Api>) {
+        let dust_mapper = self.user_reward_dust(user_id);
+        if dust_mapper.is_empty() {
+            dust_mapper.set(dust);
+            return;
+        }
+
+        dust_mapper.update(|existing_dust| existing_dust.merge_with(dust));
+    }
+
+    fn merge_user_reward_dust(
+        &self,
+        user_id: AddressId,
+        user_rewards: &mut MergedRewardsWrapper<Self::Api>,
+    ) {
+        let dust = self.take_user_reward_dust(user_id);
+        user_rewards.other_tokens.merge_with(dust);
+    }
+
+    fn take_user_reward_dust(&self, user_id: AddressId) -> UniquePayments<Self::Api> {
+        let dust_mapper = self.user_reward_dust(user_id);
+        if dust_mapper.is_empty() {
+            return UniquePayments::new();
+        }
+
+        dust_mapper.take()
+    }
+
     #[storage_mapper("userRewards")]
     fn user_rewards(
         &self,
         user_id: AddressId,
     ) -> SingleValueMapper<MergedRewardsWrapper<Self::Api>>;
+
+    #[storage_mapper("userRewardDust")]
+    fn user_reward_dust(&self, user_id: AddressId) -> SingleValueMapper<UniquePayments<Self::Api>>;
 }
@@ -1,2 +1,3 @@
 pub mod farms_whitelist;
 pub mod metastaking_whitelist;
+pub mod position_creators_whitelist;
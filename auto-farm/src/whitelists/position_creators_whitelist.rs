@@ -0,0 +1,36 @@
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait PositionCreatorsWhitelistModule: utils::UtilsModule {
+    /// Contracts that can deposit positions on behalf of users, e.g. auto-pos-creator.
+    #[only_owner]
+    #[endpoint(addPositionCreators)]
+    fn add_position_creators(&self, position_creators: MultiValueEncoded<ManagedAddress>) {
+        let mut mapper = self.position_creators();
+        for sc_address in position_creators {
+            self.require_sc_address(&sc_address);
+
+            let _ = mapper.insert(sc_address);
+        }
+    }
+
+    #[only_owner]
+    #[endpoint(removePositionCreators)]
+    fn remove_position_creators(&self, position_creators: MultiValueEncoded<ManagedAddress>) {
+        let mut mapper = self.position_creators();
+        for sc_address in position_creators {
+            let _ = mapper.swap_remove(&sc_address);
+        }
+    }
+
+    fn require_position_creator(&self, address: &ManagedAddress) {
+        require!(
+            self.position_creators().contains(address),
+            "Caller is not a trusted position creator"
+        );
+    }
+
+    #[view(getPositionCreators)]
+    #[storage_mapper("positionCreators")]
+    fn position_creators(&self) -> UnorderedSetMapper<ManagedAddress>;
+}
@@ -6,7 +6,9 @@ use common_structs::FarmTokenAttributes;
 use energy_factory::energy::EnergyModule;
 use energy_factory::locked_token_transfer::LockedTokenTransferModule;
 use energy_query::Energy;
+use farm_staking_proxy::{dual_yield_token::DualYieldTokenModule, FarmStakingProxy};
 use multiversx_sc::codec::Empty;
+use multiversx_sc::storage::mappers::StorageTokenWrapper;
 use multiversx_sc::types::{BigInt, EsdtTokenPayment, ManagedVec, MultiValueEncoded};
 use multiversx_sc_scenario::testing_framework::TxTokenTransfer;
 use multiversx_sc_scenario::{
@@ -17,17 +19,26 @@ use simple_lock::locked_token::LockedTokenAttributes;
 
 use auto_farm::external_sc_interactions::farm_actions::FarmActionsModule;
 use auto_farm::fees::FeesModule;
+use auto_farm::thresholds::ThresholdsModule;
+use auto_farm::user_tokens::deposit_for_user::DepositForUserModule;
 use auto_farm::user_tokens::user_farm_tokens::UserFarmTokensModule;
+use auto_farm::user_tokens::user_metastaking_tokens::UserMetastakingTokensModule;
 use auto_farm::user_tokens::user_rewards::UserRewardsModule;
 use auto_farm::whitelists::farms_whitelist::FarmsWhitelistModule;
+use auto_farm::whitelists::metastaking_whitelist::MetastakingWhitelistModule;
+use auto_farm::whitelists::position_creators_whitelist::PositionCreatorsWhitelistModule;
 use auto_farm::AutoFarm;
 
 use tests_common::farm_with_locked_rewards_setup::{
-    FarmSetup, FARM_TOKEN_ID, LOCKED_REWARD_TOKEN_ID,
+    FarmSetup, FARMING_TOKEN_ID, FARM_TOKEN_ID, LOCKED_REWARD_TOKEN_ID,
 };
 
 const FEE_PERCENTAGE: u64 = 1_000; // 10%
 
+static DUAL_YIELD_TOKEN_ID: &[u8] = b"DYTOK-123456";
+static STAKING_TOKEN_ID: &[u8] = b"STAKE-123456";
+static STAKING_FARM_TOKEN_ID: &[u8] = b"STKFARM-123456";
+
 const FIRST_FARM_INDEX: usize = 0;
 const SECOND_FARM_INDEX: usize = 1;
 
@@ -499,3 +510,546 @@ fn withdraw_specific_farm_tokens_test() {
         None,
     );
 }
+
+#[test]
+fn deposit_below_min_farm_deposit_test() {
+    DebugApi::dummy();
+    let mut farm_setup = FarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+    );
+
+    farm_setup.b_mock.borrow_mut().set_block_epoch(2);
+
+    // setup auto-farm SC
+    let rust_zero = rust_biguint!(0);
+    let proxy_address = farm_setup
+        .b_mock
+        .borrow_mut()
+        .create_user_account(&rust_zero);
+    let auto_farm_wrapper = farm_setup.b_mock.borrow_mut().create_sc_account(
+        &rust_zero,
+        Some(&farm_setup.owner),
+        auto_farm::contract_obj,
+        "auto farm",
+    );
+    let energy_factory_addr = farm_setup.energy_factory_wrapper.address_ref().clone();
+    let first_farm_addr = farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    let min_deposit = 10_000;
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_address!(&proxy_address),
+                FEE_PERCENTAGE,
+                managed_address!(&energy_factory_addr),
+                managed_address!(&energy_factory_addr), // unused here
+                managed_address!(&energy_factory_addr), // unused here
+            );
+
+            let mut args = MultiValueEncoded::new();
+            args.push(managed_address!(&first_farm_addr));
+            sc.add_farms(args);
+
+            sc.set_min_farm_deposit(
+                managed_address!(&first_farm_addr),
+                managed_biguint!(min_deposit),
+            );
+        })
+        .assert_ok();
+
+    let first_user = farm_setup.first_user.clone();
+    farm_setup.set_user_energy(&first_user, 1_000, 2, 1);
+    farm_setup.enter_farm(FIRST_FARM_INDEX, &first_user, min_deposit);
+
+    // deposit below minimum
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(min_deposit - 1),
+            |sc| {
+                sc.deposit_farm_tokens();
+            },
+        )
+        .assert_user_error("Deposit amount too low");
+
+    // deposit exactly the minimum
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(min_deposit),
+            |sc| {
+                sc.deposit_farm_tokens();
+
+                assert_eq!(
+                    sc.get_min_farm_deposit_view(managed_address!(&first_farm_addr)),
+                    managed_biguint!(min_deposit)
+                );
+            },
+        )
+        .assert_ok();
+}
+
+#[test]
+fn deposit_below_min_metastaking_deposit_test() {
+    DebugApi::dummy();
+    let farm_setup = FarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+    );
+
+    // setup auto-farm SC
+    let rust_zero = rust_biguint!(0);
+    let proxy_address = farm_setup
+        .b_mock
+        .borrow_mut()
+        .create_user_account(&rust_zero);
+    let auto_farm_wrapper = farm_setup.b_mock.borrow_mut().create_sc_account(
+        &rust_zero,
+        Some(&farm_setup.owner),
+        auto_farm::contract_obj,
+        "auto farm",
+    );
+    let energy_factory_addr = farm_setup.energy_factory_wrapper.address_ref().clone();
+    let first_farm_addr = farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+    let second_farm_addr = farm_setup.farm_wrappers[SECOND_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    // only the token IDs of the metastaking SC are read by auto-farm
+    let ms_wrapper = farm_setup.b_mock.borrow_mut().create_sc_account(
+        &rust_zero,
+        Some(&farm_setup.owner),
+        farm_staking_proxy::contract_obj,
+        "metastaking",
+    );
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &ms_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_address!(&energy_factory_addr),
+                managed_address!(&first_farm_addr),
+                managed_address!(&second_farm_addr), // unused here
+                managed_address!(&energy_factory_addr), // unused here
+                managed_token_id!(STAKING_TOKEN_ID),
+                managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                managed_token_id!(STAKING_FARM_TOKEN_ID),
+                managed_token_id!(FARMING_TOKEN_ID[FIRST_FARM_INDEX]),
+            );
+
+            sc.dual_yield_token()
+                .set_token_id(managed_token_id!(DUAL_YIELD_TOKEN_ID));
+        })
+        .assert_ok();
+
+    let ms_addr = ms_wrapper.address_ref().clone();
+    let min_deposit = 10_000;
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_address!(&proxy_address),
+                FEE_PERCENTAGE,
+                managed_address!(&energy_factory_addr),
+                managed_address!(&energy_factory_addr), // unused here
+                managed_address!(&energy_factory_addr), // unused here
+            );
+
+            let mut args = MultiValueEncoded::new();
+            args.push(managed_address!(&ms_addr));
+            sc.add_metastaking_scs(args);
+
+            sc.set_min_metastaking_deposit(
+                managed_address!(&ms_addr),
+                managed_biguint!(min_deposit),
+            );
+        })
+        .assert_ok();
+
+    let first_user = farm_setup.first_user.clone();
+    farm_setup.b_mock.borrow_mut().set_nft_balance(
+        &first_user,
+        DUAL_YIELD_TOKEN_ID,
+        1,
+        &rust_biguint!(min_deposit),
+        &Empty,
+    );
+
+    // deposit below minimum
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &auto_farm_wrapper,
+            DUAL_YIELD_TOKEN_ID,
+            1,
+            &rust_biguint!(min_deposit - 1),
+            |sc| {
+                sc.deposit_metastaking_tokens();
+            },
+        )
+        .assert_user_error("Deposit amount too low");
+
+    // deposit exactly the minimum
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &auto_farm_wrapper,
+            DUAL_YIELD_TOKEN_ID,
+            1,
+            &rust_biguint!(min_deposit),
+            |sc| {
+                sc.deposit_metastaking_tokens();
+
+                assert_eq!(
+                    sc.get_min_metastaking_deposit_view(managed_address!(&ms_addr)),
+                    managed_biguint!(min_deposit)
+                );
+            },
+        )
+        .assert_ok();
+}
+
+#[test]
+fn skip_claim_below_min_farm_rewards_test() {
+    DebugApi::dummy();
+    let mut farm_setup = FarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+    );
+
+    farm_setup.b_mock.borrow_mut().set_block_epoch(2);
+
+    // setup auto-farm SC
+    let rust_zero = rust_biguint!(0);
+    let proxy_address = farm_setup
+        .b_mock
+        .borrow_mut()
+        .create_user_account(&rust_zero);
+    let auto_farm_wrapper = farm_setup.b_mock.borrow_mut().create_sc_account(
+        &rust_zero,
+        Some(&farm_setup.owner),
+        auto_farm::contract_obj,
+        "auto farm",
+    );
+    let energy_factory_addr = farm_setup.energy_factory_wrapper.address_ref().clone();
+    let mut farms = Vec::new();
+    for farm_wrapper in &farm_setup.farm_wrappers {
+        farms.push(farm_wrapper.address_ref().clone());
+    }
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_address!(&proxy_address),
+                FEE_PERCENTAGE,
+                managed_address!(&energy_factory_addr),
+                managed_address!(&energy_factory_addr), // unused here
+                managed_address!(&energy_factory_addr), // unused here
+            );
+
+            let mut args = MultiValueEncoded::new();
+            for farm in &farms {
+                args.push(managed_address!(farm));
+            }
+            sc.add_farms(args);
+        })
+        .assert_ok();
+
+    // whitelist auto-farm SC in farms
+    for farm_wrapper in &farm_setup.farm_wrappers {
+        farm_setup
+            .b_mock
+            .borrow_mut()
+            .execute_tx(&farm_setup.owner, farm_wrapper, &rust_zero, |sc| {
+                sc.add_sc_address_to_whitelist(managed_address!(auto_farm_wrapper.address_ref()));
+            })
+            .assert_ok();
+    }
+
+    // whitelist auto-farm SC in energy factory
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(
+            &farm_setup.owner,
+            &farm_setup.energy_factory_wrapper,
+            &rust_zero,
+            |sc| {
+                sc.add_to_token_transfer_whitelist(
+                    ManagedVec::from_single_item(managed_address!(auto_farm_wrapper.address_ref()))
+                        .into(),
+                );
+            },
+        )
+        .assert_ok();
+
+    let third_user = farm_setup.third_user.clone();
+
+    // enter both farms
+    let first_farm_token_amount = 100_000_000;
+    let second_farm_token_amount = 50_000_000;
+    let first_user = farm_setup.first_user.clone();
+    farm_setup.set_user_energy(&first_user, 1_000, 2, 1);
+    farm_setup.enter_farm(FIRST_FARM_INDEX, &first_user, first_farm_token_amount);
+    farm_setup.enter_farm(SECOND_FARM_INDEX, &first_user, second_farm_token_amount);
+
+    // advance blocks and cummulate rewards
+    farm_setup.b_mock.borrow_mut().set_block_nonce(10);
+    farm_setup.b_mock.borrow_mut().set_block_epoch(6);
+    farm_setup.set_user_energy(&first_user, 1_000, 6, 1);
+    farm_setup.set_user_energy(&third_user, 1, 6, 1);
+
+    farm_setup.enter_farm(FIRST_FARM_INDEX, &third_user, 1);
+    farm_setup.exit_farm(FIRST_FARM_INDEX, &third_user, 2, 1);
+    farm_setup.enter_farm(SECOND_FARM_INDEX, &third_user, 1);
+    farm_setup.exit_farm(SECOND_FARM_INDEX, &third_user, 2, 1);
+
+    farm_setup.b_mock.borrow_mut().set_block_epoch(10);
+    farm_setup.set_user_energy(&first_user, 1_000, 10, 1);
+
+    // user deposit farm tokens
+    let farm_tokens = [
+        TxTokenTransfer {
+            token_identifier: FARM_TOKEN_ID[FIRST_FARM_INDEX].to_vec(),
+            nonce: 1,
+            value: rust_biguint!(first_farm_token_amount),
+        },
+        TxTokenTransfer {
+            token_identifier: FARM_TOKEN_ID[SECOND_FARM_INDEX].to_vec(),
+            nonce: 1,
+            value: rust_biguint!(second_farm_token_amount),
+        },
+    ];
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(&first_user, &auto_farm_wrapper, &farm_tokens, |sc| {
+            sc.deposit_farm_tokens();
+        })
+        .assert_ok();
+
+    // the first farm position has less pending rewards than the threshold
+    let first_farm_addr = farms[FIRST_FARM_INDEX].clone();
+    let min_rewards = 1_000_000u64;
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_min_farm_rewards_to_claim(
+                managed_address!(&first_farm_addr),
+                managed_biguint!(min_rewards),
+            );
+
+            assert_eq!(
+                sc.get_min_farm_rewards_to_claim_view(managed_address!(&first_farm_addr)),
+                managed_biguint!(min_rewards)
+            );
+        })
+        .assert_ok();
+
+    // only the second farm position is claimed
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&proxy_address, &auto_farm_wrapper, &rust_zero, |sc| {
+            let mut rew_wrapper = RewardsWrapper::new(managed_token_id!(LOCKED_REWARD_TOKEN_ID));
+            sc.claim_all_farm_rewards(&managed_address!(&first_user), 1, &mut rew_wrapper);
+
+            let user_farm_tokens = sc.get_user_farm_tokens_view(managed_address!(&first_user));
+            let mut expected_user_farm_tokens = ManagedVec::new();
+            expected_user_farm_tokens.push(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                1,
+                managed_biguint!(first_farm_token_amount),
+            ));
+            expected_user_farm_tokens.push(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[SECOND_FARM_INDEX]),
+                3,
+                managed_biguint!(second_farm_token_amount),
+            ));
+            assert_eq!(user_farm_tokens, expected_user_farm_tokens);
+        })
+        .assert_ok();
+
+    // once the threshold is lowered, the first farm position is claimed as well
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.set_min_farm_rewards_to_claim(
+                managed_address!(&first_farm_addr),
+                managed_biguint!(1),
+            );
+        })
+        .assert_ok();
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&proxy_address, &auto_farm_wrapper, &rust_zero, |sc| {
+            let mut rew_wrapper = RewardsWrapper::new(managed_token_id!(LOCKED_REWARD_TOKEN_ID));
+            sc.claim_all_farm_rewards(&managed_address!(&first_user), 1, &mut rew_wrapper);
+
+            let user_farm_tokens = sc.get_user_farm_tokens_view(managed_address!(&first_user));
+            assert_eq!(user_farm_tokens.get(0).token_nonce, 3);
+            assert_eq!(
+                user_farm_tokens.get(0).amount,
+                managed_biguint!(first_farm_token_amount)
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn deposit_for_user_test() {
+    DebugApi::dummy();
+    let farm_setup = FarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+    );
+
+    // setup auto-farm SC
+    let rust_zero = rust_biguint!(0);
+    let proxy_address = farm_setup
+        .b_mock
+        .borrow_mut()
+        .create_user_account(&rust_zero);
+    let auto_farm_wrapper = farm_setup.b_mock.borrow_mut().create_sc_account(
+        &rust_zero,
+        Some(&farm_setup.owner),
+        auto_farm::contract_obj,
+        "auto farm",
+    );
+    let position_creator_wrapper = farm_setup.b_mock.borrow_mut().create_sc_account(
+        &rust_zero,
+        Some(&farm_setup.owner),
+        auto_farm::contract_obj,
+        "position creator",
+    );
+    let energy_factory_addr = farm_setup.energy_factory_wrapper.address_ref().clone();
+    let position_creator_addr = position_creator_wrapper.address_ref().clone();
+    let first_farm_addr = farm_setup.farm_wrappers[FIRST_FARM_INDEX]
+        .address_ref()
+        .clone();
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_address!(&proxy_address),
+                FEE_PERCENTAGE,
+                managed_address!(&energy_factory_addr),
+                managed_address!(&energy_factory_addr), // unused here
+                managed_address!(&energy_factory_addr), // unused here
+            );
+
+            let mut args = MultiValueEncoded::new();
+            args.push(managed_address!(&first_farm_addr));
+            sc.add_farms(args);
+        })
+        .assert_ok();
+
+    let first_user = farm_setup.first_user.clone();
+    let farm_token_amount = 100_000;
+    farm_setup.b_mock.borrow_mut().set_nft_balance(
+        &first_user,
+        FARM_TOKEN_ID[FIRST_FARM_INDEX],
+        1,
+        &rust_biguint!(farm_token_amount),
+        &Empty,
+    );
+    farm_setup.b_mock.borrow_mut().set_nft_balance(
+        &position_creator_addr,
+        FARM_TOKEN_ID[FIRST_FARM_INDEX],
+        1,
+        &rust_biguint!(farm_token_amount),
+        &Empty,
+    );
+
+    // untrusted callers can't deposit for other users
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user,
+            &auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                sc.deposit_for_user(managed_address!(&first_user));
+            },
+        )
+        .assert_user_error("Caller is not a trusted position creator");
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_tx(&farm_setup.owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            let mut args = MultiValueEncoded::new();
+            args.push(managed_address!(&position_creator_addr));
+            sc.add_position_creators(args);
+        })
+        .assert_ok();
+
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &position_creator_addr,
+            &auto_farm_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                sc.deposit_for_user(managed_address!(&first_user));
+            },
+        )
+        .assert_ok();
+
+    // position is registered under the user, not the position creator
+    farm_setup
+        .b_mock
+        .borrow_mut()
+        .execute_query(&auto_farm_wrapper, |sc| {
+            let mut expected_user_farm_tokens = ManagedVec::new();
+            expected_user_farm_tokens.push(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                1,
+                managed_biguint!(farm_token_amount),
+            ));
+            assert_eq!(
+                sc.get_user_farm_tokens_view(managed_address!(&first_user)),
+                expected_user_farm_tokens
+            );
+            assert!(sc
+                .get_user_farm_tokens_view(managed_address!(&position_creator_addr))
+                .is_empty());
+        })
+        .assert_ok();
+}
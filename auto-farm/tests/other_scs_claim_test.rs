@@ -8,10 +8,12 @@ pub mod metabonding_setup;
 use crate::fees_collector_setup::LOCKED_TOKEN_ID;
 use auto_farm::{
     common::{common_storage::MAX_PERCENTAGE, rewards_wrapper::RewardsWrapper},
-    external_sc_interactions::metabonding_actions::{
-        MetabondingActionsModule, SingleMetabondingClaimArg,
+    external_sc_interactions::{
+        metabonding_actions::{MetabondingActionsModule, SingleMetabondingClaimArg},
+        multi_contract_interactions::MultiContractInteractionsModule,
     },
     fees::FeesModule,
+    thresholds::ThresholdsModule,
     user_tokens::user_rewards::UserRewardsModule,
     AutoFarm,
 };
@@ -178,6 +180,170 @@ fn metabonding_claim_through_auto_farm_test() {
         .assert_ok();
 }
 
+#[test]
+fn reward_dust_sweep_test() {
+    let farm_setup = FarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+    );
+
+    let b_mock = farm_setup.b_mock;
+    let rust_zero = rust_biguint!(0);
+
+    let mb_setup = setup_metabonding(&mut b_mock.borrow_mut(), metabonding::contract_obj);
+
+    let owner = b_mock.borrow_mut().create_user_account(&rust_zero);
+    let proxy_address = b_mock.borrow_mut().create_user_account(&rust_zero);
+    let auto_farm_wrapper = b_mock.borrow_mut().create_sc_account(
+        &rust_zero,
+        Some(&owner),
+        auto_farm::contract_obj,
+        "auto farm",
+    );
+
+    // the user's second project tokens rewards are below the threshold
+    let dust_threshold = 50_000_000u64;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_address!(&proxy_address),
+                FEE_PERCENTAGE,
+                managed_address!(mb_setup.address_ref()), // unused here
+                managed_address!(mb_setup.address_ref()),
+                managed_address!(mb_setup.address_ref()), // unused here
+            );
+
+            sc.set_reward_dust_threshold(
+                managed_token_id!(SECOND_PROJ_TOKEN),
+                managed_biguint!(dust_threshold),
+            );
+        })
+        .assert_ok();
+
+    // whitelist auto-farm SC in metabonding
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &mb_setup, &rust_zero, |sc| {
+            sc.sc_whitelist_addresses()
+                .add(&managed_address!(auto_farm_wrapper.address_ref()))
+        })
+        .assert_ok();
+
+    let sig_first_user_week_1 = hex_literal::hex!("d47c0d67b2d25de8b4a3f43d91a2b5ccb522afac47321ae80bf89c90a4445b26adefa693ab685fa20891f736d74eb2dedc11c4b1a8d6e642fa28df270d6ebe08");
+    let sig_first_user_week_2 = hex_literal::hex!("b4aadf08eea4cc7c636922511943edbab2ff6ef2558528e0e7b03c7448367989fe860ac091be4d942304f04c86b1eaa0501f36e02819a3c628b4c53f3d3ac801");
+
+    let first_user_addr = farm_setup.first_user;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user_addr, &auto_farm_wrapper, &rust_zero, |sc| {
+            sc.register();
+        })
+        .assert_ok();
+
+    // taken from metabonding test, minus the 10% fee
+    let first_proj_user_rewards = 112_500_000u64;
+    let second_proj_user_rewards = 45_000_000u64;
+    b_mock
+        .borrow_mut()
+        .execute_tx(&proxy_address, &auto_farm_wrapper, &rust_zero, |sc| {
+            let mut claim_args = ManagedVec::new();
+            claim_args.push(SingleMetabondingClaimArg {
+                week: 1,
+                user_delegation_amount: managed_biguint!(25_000),
+                user_lkmex_amount: managed_biguint!(0),
+                signature: (&sig_first_user_week_1).into(),
+            });
+            claim_args.push(SingleMetabondingClaimArg {
+                week: 2,
+                user_delegation_amount: managed_biguint!(25_000),
+                user_lkmex_amount: managed_biguint!(0),
+                signature: (&sig_first_user_week_2).into(),
+            });
+
+            let mut rew_wrapper = RewardsWrapper::new(managed_token_id!(LOCKED_TOKEN_ID));
+            sc.claim_metabonding_rewards(
+                &managed_address!(&first_user_addr),
+                claim_args,
+                &mut rew_wrapper,
+            );
+            sc.add_user_rewards(managed_address!(&first_user_addr), 1, rew_wrapper);
+
+            let user_rewards = sc.user_rewards(1).get().other_tokens.into_payments();
+            let remaining_rewards = sc.sweep_reward_dust(1, user_rewards);
+
+            let mut expected_remaining_rewards = ManagedVec::new();
+            expected_remaining_rewards.push(EsdtTokenPayment::new(
+                managed_token_id!(FIRST_PROJ_TOKEN),
+                0,
+                managed_biguint!(first_proj_user_rewards),
+            ));
+            assert_eq!(remaining_rewards, expected_remaining_rewards);
+
+            let mut expected_dust = ManagedVec::new();
+            expected_dust.push(EsdtTokenPayment::new(
+                managed_token_id!(SECOND_PROJ_TOKEN),
+                0,
+                managed_biguint!(second_proj_user_rewards),
+            ));
+            assert_eq!(
+                sc.get_user_reward_dust_view(managed_address!(&first_user_addr)),
+                expected_dust
+            );
+        })
+        .assert_ok();
+
+    // the dust is added back to the rewards before the next compounding
+    b_mock
+        .borrow_mut()
+        .execute_tx(&proxy_address, &auto_farm_wrapper, &rust_zero, |sc| {
+            let mut user_wrapped_rewards = sc.user_rewards(1).get();
+            user_wrapped_rewards.other_tokens =
+                UniquePayments::new_from_unique_payments(ManagedVec::new());
+            sc.merge_user_reward_dust(1, &mut user_wrapped_rewards);
+            assert!(sc
+                .get_user_reward_dust_view(managed_address!(&first_user_addr))
+                .is_empty());
+
+            let user_rewards = user_wrapped_rewards.other_tokens.into_payments();
+            let mut expected_rewards = ManagedVec::new();
+            expected_rewards.push(EsdtTokenPayment::new(
+                managed_token_id!(SECOND_PROJ_TOKEN),
+                0,
+                managed_biguint!(second_proj_user_rewards),
+            ));
+            assert_eq!(user_rewards, expected_rewards);
+
+            // still below the threshold, so it goes back to the bucket as a single entry
+            let remaining_rewards = sc.sweep_reward_dust(1, user_rewards);
+            assert!(remaining_rewards.is_empty());
+            assert_eq!(
+                sc.get_user_reward_dust_view(managed_address!(&first_user_addr)),
+                expected_rewards
+            );
+        })
+        .assert_ok();
+
+    // user claims the dust
+    b_mock
+        .borrow_mut()
+        .execute_tx(&first_user_addr, &auto_farm_wrapper, &rust_zero, |sc| {
+            let claimed_dust = sc.user_claim_reward_dust_endpoint();
+            assert_eq!(claimed_dust.len(), 1);
+
+            assert!(sc
+                .get_user_reward_dust_view(managed_address!(&first_user_addr))
+                .is_empty());
+        })
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &first_user_addr,
+        SECOND_PROJ_TOKEN,
+        &rust_biguint!(second_proj_user_rewards),
+    );
+}
+
 #[test]
 fn fees_collector_setup_test() {
     let farm_setup = FarmSetup::new(
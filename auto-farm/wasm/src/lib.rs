@@ -6,9 +6,9 @@
 
 // Init:                                 1
 // Upgrade:                              1
-// Endpoints:                           29
+// Endpoints:                           45
 // Async Callback (empty):               1
-// Total number of exported functions:  32
+// Total number of exported functions:  48
 
 #![no_std]
 
@@ -31,9 +31,12 @@ multiversx_sc_wasm_adapter::endpoints! {
         withdrawAllMetastakingTokens => withdraw_all_metastaking_tokens_endpoint
         withdrawSpecificMetastakingTokens => withdraw_specific_metastaking_tokens_endpoint
         getUserMetastakingTokens => get_user_metastaking_tokens_view
+        depositForUser => deposit_for_user
         claimAllRewardsAndCompound => claim_all_rewards_and_compound
         userClaimRewards => user_claim_rewards_endpoint
+        userClaimRewardDust => user_claim_reward_dust_endpoint
         getUserRewards => get_user_rewards_view
+        getUserRewardDust => get_user_reward_dust_view
         claimFees => claim_fees
         getFeePercentage => fee_percentage
         getAccumulatedFees => accumulated_fees
@@ -49,6 +52,19 @@ multiversx_sc_wasm_adapter::endpoints! {
         getMetastakingForDualYieldToken => get_metastaking_for_dual_yield_token_view
         getMetastakingForLpFarmToken => get_metastaking_for_lp_farm_token
         getMetastakingConfig => get_metastaking_config
+        addPositionCreators => add_position_creators
+        removePositionCreators => remove_position_creators
+        getPositionCreators => position_creators
+        setMinFarmDeposit => set_min_farm_deposit
+        setMinMetastakingDeposit => set_min_metastaking_deposit
+        setMinFarmRewardsToClaim => set_min_farm_rewards_to_claim
+        setMinMetastakingRewardsToClaim => set_min_metastaking_rewards_to_claim
+        setRewardDustThreshold => set_reward_dust_threshold
+        getMinFarmDeposit => get_min_farm_deposit_view
+        getMinMetastakingDeposit => get_min_metastaking_deposit_view
+        getMinFarmRewardsToClaim => get_min_farm_rewards_to_claim_view
+        getMinMetastakingRewardsToClaim => get_min_metastaking_rewards_to_claim_view
+        getRewardDustThreshold => reward_dust_threshold
     )
 }
 
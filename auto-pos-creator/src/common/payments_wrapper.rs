@@ -31,6 +31,15 @@ impl<M: SendApi> PaymentsWrapper<M> {
         self.payments.push(payment);
     }
 
+    #[inline]
+    pub fn get_payments(&self) -> &PaymentsVec<M> {
+        &self.payments
+    }
+
+    pub fn append(&mut self, other: Self) {
+        self.payments.append_vec(other.payments);
+    }
+
     pub fn send_and_return(self, to: &ManagedAddress<M>) -> PaymentsVec<M> {
         if self.payments.is_empty() {
             return self.payments;
@@ -0,0 +1,154 @@
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait ContractsWhitelistModule: utils::UtilsModule {
+    /// Pairs that can be used directly through the LP endpoints.
+    /// The pairs of whitelisted farms don't have to be added.
+    #[only_owner]
+    #[endpoint(addPairs)]
+    fn add_pairs(&self, pairs: MultiValueEncoded<ManagedAddress>) {
+        self.add_to_whitelist(self.whitelisted_pairs(), pairs);
+    }
+
+    #[only_owner]
+    #[endpoint(removePairs)]
+    fn remove_pairs(&self, pairs: MultiValueEncoded<ManagedAddress>) {
+        self.remove_from_whitelist(self.whitelisted_pairs(), pairs);
+    }
+
+    #[only_owner]
+    #[endpoint(addFarms)]
+    fn add_farms(&self, farms: MultiValueEncoded<ManagedAddress>) {
+        self.add_to_whitelist(self.whitelisted_farms(), farms);
+    }
+
+    #[only_owner]
+    #[endpoint(removeFarms)]
+    fn remove_farms(&self, farms: MultiValueEncoded<ManagedAddress>) {
+        self.remove_from_whitelist(self.whitelisted_farms(), farms);
+    }
+
+    #[only_owner]
+    #[endpoint(addMetastakingScs)]
+    fn add_metastaking_scs(&self, scs: MultiValueEncoded<ManagedAddress>) {
+        self.add_to_whitelist(self.whitelisted_metastaking_scs(), scs);
+    }
+
+    #[only_owner]
+    #[endpoint(removeMetastakingScs)]
+    fn remove_metastaking_scs(&self, scs: MultiValueEncoded<ManagedAddress>) {
+        self.remove_from_whitelist(self.whitelisted_metastaking_scs(), scs);
+    }
+
+    #[only_owner]
+    #[endpoint(addFarmStakingScs)]
+    fn add_farm_staking_scs(&self, scs: MultiValueEncoded<ManagedAddress>) {
+        self.add_to_whitelist(self.whitelisted_farm_staking_scs(), scs);
+    }
+
+    #[only_owner]
+    #[endpoint(removeFarmStakingScs)]
+    fn remove_farm_staking_scs(&self, scs: MultiValueEncoded<ManagedAddress>) {
+        self.remove_from_whitelist(self.whitelisted_farm_staking_scs(), scs);
+    }
+
+    /// If set, any pair, farm, metastaking or farm staking contract can be used
+    #[only_owner]
+    #[endpoint(setPermissionless)]
+    fn set_permissionless(&self, permissionless: bool) {
+        self.permissionless().set(permissionless);
+    }
+
+    fn add_to_whitelist(
+        &self,
+        mut mapper: UnorderedSetMapper<ManagedAddress>,
+        sc_addresses: MultiValueEncoded<ManagedAddress>,
+    ) {
+        for sc_address in sc_addresses {
+            self.require_sc_address(&sc_address);
+
+            let _ = mapper.insert(sc_address);
+        }
+    }
+
+    fn remove_from_whitelist(
+        &self,
+        mut mapper: UnorderedSetMapper<ManagedAddress>,
+        sc_addresses: MultiValueEncoded<ManagedAddress>,
+    ) {
+        for sc_address in sc_addresses {
+            let _ = mapper.swap_remove(&sc_address);
+        }
+    }
+
+    fn require_whitelisted_pair(&self, pair_address: &ManagedAddress) {
+        require!(
+            self.is_whitelisted(self.whitelisted_pairs(), pair_address),
+            "Pair not whitelisted"
+        );
+    }
+
+    fn require_whitelisted_farm(&self, farm_address: &ManagedAddress) {
+        require!(
+            self.is_whitelisted(self.whitelisted_farms(), farm_address),
+            "Farm not whitelisted"
+        );
+    }
+
+    fn require_whitelisted_metastaking(&self, metastaking_address: &ManagedAddress) {
+        require!(
+            self.is_whitelisted(self.whitelisted_metastaking_scs(), metastaking_address),
+            "Metastaking contract not whitelisted"
+        );
+    }
+
+    fn require_whitelisted_farm_staking(&self, farm_staking_address: &ManagedAddress) {
+        require!(
+            self.is_whitelisted(self.whitelisted_farm_staking_scs(), farm_staking_address),
+            "Farm staking contract not whitelisted"
+        );
+    }
+
+    /// Farm, metastaking or farm staking contract
+    fn require_whitelisted_position_sc(&self, sc_address: &ManagedAddress) {
+        let is_whitelisted = self.is_whitelisted(self.whitelisted_farms(), sc_address)
+            || self.is_whitelisted(self.whitelisted_metastaking_scs(), sc_address)
+            || self.is_whitelisted(self.whitelisted_farm_staking_scs(), sc_address);
+        require!(is_whitelisted, "Position contract not whitelisted");
+    }
+
+    fn has_whitelisted_contracts(&self) -> bool {
+        !self.whitelisted_pairs().is_empty()
+            || !self.whitelisted_farms().is_empty()
+            || !self.whitelisted_metastaking_scs().is_empty()
+            || !self.whitelisted_farm_staking_scs().is_empty()
+    }
+
+    fn is_whitelisted(
+        &self,
+        mapper: UnorderedSetMapper<ManagedAddress>,
+        sc_address: &ManagedAddress,
+    ) -> bool {
+        self.permissionless().get() || mapper.contains(sc_address)
+    }
+
+    #[view(isPermissionless)]
+    #[storage_mapper("permissionless")]
+    fn permissionless(&self) -> SingleValueMapper<bool>;
+
+    #[view(getWhitelistedPairs)]
+    #[storage_mapper("whitelistedPairs")]
+    fn whitelisted_pairs(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(getWhitelistedFarms)]
+    #[storage_mapper("whitelistedFarms")]
+    fn whitelisted_farms(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(getWhitelistedMetastakingScs)]
+    #[storage_mapper("whitelistedMetastakingScs")]
+    fn whitelisted_metastaking_scs(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(getWhitelistedFarmStakingScs)]
+    #[storage_mapper("whitelistedFarmStakingScs")]
+    fn whitelisted_farm_staking_scs(&self) -> UnorderedSetMapper<ManagedAddress>;
+}
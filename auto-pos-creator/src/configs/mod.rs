@@ -1 +1,3 @@
+pub mod contracts_whitelist;
 pub mod pairs_config;
+pub mod price_deviation_config;
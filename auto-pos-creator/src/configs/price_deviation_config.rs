@@ -0,0 +1,117 @@
+multiversx_sc::imports!();
+
+use pair::safe_price_view::ProxyTrait as _;
+
+pub const MAX_PRICE_DEVIATION_BPS: u64 = 10_000;
+
+#[derive(Clone, Copy)]
+pub enum SwapLeg {
+    OptimalZap,
+    ExcessBalancing,
+    DiscoveredRoute,
+}
+
+impl SwapLeg {
+    pub fn price_deviation_err_msg(&self) -> &'static str {
+        match self {
+            SwapLeg::OptimalZap => "Price deviation too high on optimal zap swap",
+            SwapLeg::ExcessBalancing => "Price deviation too high on excess balancing swap",
+            SwapLeg::DiscoveredRoute => "Price deviation too high on discovered route swap",
+        }
+    }
+}
+
+#[multiversx_sc::module]
+pub trait PriceDeviationConfigModule: utils::UtilsModule {
+    /// Maximum deviation of internal swaps from the reference price, in basis points.
+    /// Set to 0 to disable the check for the given pair.
+    #[only_owner]
+    #[endpoint(setMaxPriceDeviation)]
+    fn set_max_price_deviation(&self, pair_address: ManagedAddress, max_deviation_bps: u64) {
+        self.require_sc_address(&pair_address);
+        self.require_valid_price_deviation(max_deviation_bps);
+
+        self.max_price_deviation(&pair_address).set(max_deviation_bps);
+    }
+
+    /// The oracle must expose the same getSafePriceByDefaultOffset view as the pair.
+    /// By default, the pair itself is used as reference.
+    #[only_owner]
+    #[endpoint(setPriceOracle)]
+    fn set_price_oracle(&self, pair_address: ManagedAddress, oracle_address: ManagedAddress) {
+        self.require_sc_address(&pair_address);
+        self.require_sc_address(&oracle_address);
+
+        self.price_oracle(&pair_address).set(oracle_address);
+    }
+
+    #[only_owner]
+    #[endpoint(removePriceOracle)]
+    fn remove_price_oracle(&self, pair_address: ManagedAddress) {
+        self.price_oracle(&pair_address).clear();
+    }
+
+    /// The user's value, if given, replaces the pair's configured value. 0 disables the check.
+    fn get_max_price_deviation(
+        &self,
+        pair_address: &ManagedAddress,
+        opt_user_max_deviation_bps: Option<u64>,
+    ) -> u64 {
+        match opt_user_max_deviation_bps {
+            Some(user_max_deviation_bps) => {
+                self.require_valid_price_deviation(user_max_deviation_bps);
+                user_max_deviation_bps
+            }
+            None => self.max_price_deviation(pair_address).get(),
+        }
+    }
+
+    /// Returns 0 if the check is disabled
+    fn get_min_amount_out_by_price_deviation(
+        &self,
+        pair_address: &ManagedAddress,
+        input_tokens: &EsdtTokenPayment,
+        max_deviation_bps: u64,
+    ) -> BigUint {
+        if max_deviation_bps == 0 {
+            return BigUint::zero();
+        }
+
+        let reference_output = self.get_reference_output(pair_address, input_tokens.clone());
+        let min_amount_factor = MAX_PRICE_DEVIATION_BPS - max_deviation_bps;
+
+        reference_output.amount * min_amount_factor / MAX_PRICE_DEVIATION_BPS
+    }
+
+    fn get_reference_output(
+        &self,
+        pair_address: &ManagedAddress,
+        input_tokens: EsdtTokenPayment,
+    ) -> EsdtTokenPayment {
+        let oracle_mapper = self.price_oracle(pair_address);
+        let oracle_address = if oracle_mapper.is_empty() {
+            pair_address.clone()
+        } else {
+            oracle_mapper.get()
+        };
+
+        self.price_oracle_proxy(oracle_address)
+            .get_safe_price_by_default_offset(pair_address.clone(), input_tokens)
+            .execute_on_dest_context()
+    }
+
+    fn require_valid_price_deviation(&self, max_deviation_bps: u64) {
+        require!(max_deviation_bps < MAX_PRICE_DEVIATION_BPS, "Invalid max price deviation");
+    }
+
+    #[view(getMaxPriceDeviation)]
+    #[storage_mapper("maxPriceDeviation")]
+    fn max_price_deviation(&self, pair_address: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    #[view(getPriceOracle)]
+    #[storage_mapper("priceOracle")]
+    fn price_oracle(&self, pair_address: &ManagedAddress) -> SingleValueMapper<ManagedAddress>;
+
+    #[proxy]
+    fn price_oracle_proxy(&self, sc_address: ManagedAddress) -> pair::Proxy<Self::Api>;
+}
@@ -0,0 +1,212 @@
+use chain_info::CurrentChainInfo;
+use common_structs::PaymentsVec;
+
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+#[derive(TypeAbi, TopEncode)]
+pub enum PositionEventType {
+    Lp,
+    Farm,
+    Metastaking,
+    FarmStaking,
+}
+
+#[derive(TypeAbi, TopEncode)]
+pub struct CreatePosEvent<'a, M: ManagedTypeApi> {
+    pub input_payments: &'a ManagedVec<M, EgldOrEsdtTokenPayment<M>>,
+    pub new_position: &'a EsdtTokenPayment<M>,
+    pub leftovers: &'a PaymentsVec<M>,
+    pub chain_info: CurrentChainInfo,
+}
+
+#[derive(TypeAbi, TopEncode)]
+pub struct ExitPosEvent<'a, M: ManagedTypeApi> {
+    pub position: &'a EsdtTokenPayment<M>,
+    pub output_payments: &'a ManagedVec<M, EgldOrEsdtTokenPayment<M>>,
+    pub chain_info: CurrentChainInfo,
+}
+
+/// unbonded_tokens: the farming tokens released by the farm staking contracts
+/// output_payments: the tokens sent to the user, after the optional swap
+#[derive(TypeAbi, TopEncode)]
+pub struct ClaimUnbondedEvent<'a, M: ManagedTypeApi> {
+    pub unbonded_tokens: &'a PaymentsVec<M>,
+    pub output_payments: &'a ManagedVec<M, EgldOrEsdtTokenPayment<M>>,
+    pub chain_info: CurrentChainInfo,
+}
+
+/// route: the addresses of the pairs, in swap order
+#[derive(TypeAbi, TopEncode)]
+pub struct SwapEvent<'a, M: ManagedTypeApi> {
+    pub route: &'a ManagedVec<M, ManagedAddress<M>>,
+    pub input_tokens: &'a EsdtTokenPayment<M>,
+    pub output_tokens: &'a EsdtTokenPayment<M>,
+    pub chain_info: CurrentChainInfo,
+}
+
+/// Every swap done during an operation is logged through a swap event,
+/// emitted before the operation's createPos or exitPos event.
+/// A migration logs the exitPos event of the old position, then the createPos event of the new one.
+#[multiversx_sc::module]
+pub trait EventsModule {
+    /// input_payments: the payments received by the endpoint, or the share of the payment
+    /// used for the position when an endpoint creates several of them
+    fn emit_create_pos_event(
+        &self,
+        caller: &ManagedAddress,
+        position_type: PositionEventType,
+        sc_address: &ManagedAddress,
+        input_payments: &ManagedVec<EgldOrEsdtTokenPayment>,
+        new_position: &EsdtTokenPayment,
+        leftovers: &PaymentsVec<Self::Api>,
+    ) {
+        self.create_pos_event(
+            caller,
+            position_type,
+            sc_address,
+            CreatePosEvent {
+                input_payments,
+                new_position,
+                leftovers,
+                chain_info: CurrentChainInfo::new::<Self::Api>(),
+            },
+        );
+    }
+
+    fn emit_exit_pos_event(
+        &self,
+        caller: &ManagedAddress,
+        position_type: PositionEventType,
+        sc_address: &ManagedAddress,
+        position: &EsdtTokenPayment,
+        output_payments: &ManagedVec<EgldOrEsdtTokenPayment>,
+    ) {
+        self.exit_pos_event(
+            caller,
+            position_type,
+            sc_address,
+            ExitPosEvent {
+                position,
+                output_payments,
+                chain_info: CurrentChainInfo::new::<Self::Api>(),
+            },
+        );
+    }
+
+    fn emit_claim_unbonded_event(
+        &self,
+        caller: &ManagedAddress,
+        unbonded_tokens: &PaymentsVec<Self::Api>,
+        output_payments: &ManagedVec<EgldOrEsdtTokenPayment>,
+    ) {
+        self.claim_unbonded_event(
+            caller,
+            ClaimUnbondedEvent {
+                unbonded_tokens,
+                output_payments,
+                chain_info: CurrentChainInfo::new::<Self::Api>(),
+            },
+        );
+    }
+
+    fn emit_swap_event(
+        &self,
+        route: &ManagedVec<ManagedAddress>,
+        input_tokens: &EsdtTokenPayment,
+        output_tokens: &EsdtTokenPayment,
+    ) {
+        let caller = self.blockchain().get_caller();
+        self.swap_event(
+            &caller,
+            SwapEvent {
+                route,
+                input_tokens,
+                output_tokens,
+                chain_info: CurrentChainInfo::new::<Self::Api>(),
+            },
+        );
+    }
+
+    /// Logs the swap done by swap_optimal_input_if_needed, if any.
+    /// initial_payment is the first payment, before the swap.
+    fn emit_optimal_swap_event(
+        &self,
+        pair_address: &ManagedAddress,
+        initial_payment: &EsdtTokenPayment,
+        first_payment: &EsdtTokenPayment,
+        second_payment: &EsdtTokenPayment,
+    ) {
+        // LP tokens are not swapped
+        if second_payment.amount == 0 {
+            return;
+        }
+
+        let (remaining_payment, output_tokens) =
+            if first_payment.token_identifier == initial_payment.token_identifier {
+                (first_payment, second_payment)
+            } else {
+                (second_payment, first_payment)
+            };
+        let input_tokens = EsdtTokenPayment::new(
+            initial_payment.token_identifier.clone(),
+            0,
+            &initial_payment.amount - &remaining_payment.amount,
+        );
+        let route = ManagedVec::from_single_item(pair_address.clone());
+        self.emit_swap_event(&route, &input_tokens, output_tokens);
+    }
+
+    fn get_input_payments(&self) -> ManagedVec<EgldOrEsdtTokenPayment> {
+        match self.call_value().any_payment() {
+            EgldOrMultiEsdtPayment::Egld(egld_amount) => {
+                let egld_payment =
+                    EgldOrEsdtTokenPayment::new(EgldOrEsdtTokenIdentifier::egld(), 0, egld_amount);
+                ManagedVec::from_single_item(egld_payment)
+            }
+            EgldOrMultiEsdtPayment::MultiEsdt(esdt_payments) => {
+                self.to_egld_or_esdt_payments(&esdt_payments)
+            }
+        }
+    }
+
+    fn to_egld_or_esdt_payments(
+        &self,
+        payments: &PaymentsVec<Self::Api>,
+    ) -> ManagedVec<EgldOrEsdtTokenPayment> {
+        let mut egld_or_esdt_payments = ManagedVec::new();
+        for payment in payments {
+            egld_or_esdt_payments.push(EgldOrEsdtTokenPayment::from(payment));
+        }
+
+        egld_or_esdt_payments
+    }
+
+    #[event("createPos")]
+    fn create_pos_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] position_type: PositionEventType,
+        #[indexed] sc_address: &ManagedAddress,
+        event_data: CreatePosEvent<Self::Api>,
+    );
+
+    #[event("exitPos")]
+    fn exit_pos_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] position_type: PositionEventType,
+        #[indexed] sc_address: &ManagedAddress,
+        event_data: ExitPosEvent<Self::Api>,
+    );
+
+    #[event("claimUnbonded")]
+    fn claim_unbonded_event(
+        &self,
+        #[indexed] caller: &ManagedAddress,
+        event_data: ClaimUnbondedEvent<Self::Api>,
+    );
+
+    #[event("swap")]
+    fn swap_event(&self, #[indexed] caller: &ManagedAddress, event_data: SwapEvent<Self::Api>);
+}
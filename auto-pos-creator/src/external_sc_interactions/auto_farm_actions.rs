@@ -0,0 +1,66 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::PaymentsVec;
+
+use crate::common::payments_wrapper::PaymentsWrapper;
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Clone, Copy)]
+pub enum PositionDestination {
+    Caller,
+    AutoFarm,
+}
+
+#[multiversx_sc::module]
+pub trait AutoFarmActionsModule: utils::UtilsModule {
+    /// The auto-farm SC must have this contract in its position creators list
+    #[only_owner]
+    #[endpoint(setAutoFarmAddress)]
+    fn set_auto_farm_address(&self, auto_farm_address: ManagedAddress) {
+        self.require_sc_address(&auto_farm_address);
+
+        self.auto_farm_address().set(auto_farm_address);
+    }
+
+    fn call_auto_farm_deposit_for_user(
+        &self,
+        user: ManagedAddress,
+        tokens: PaymentsVec<Self::Api>,
+    ) {
+        let auto_farm_mapper = self.auto_farm_address();
+        require!(!auto_farm_mapper.is_empty(), "Auto-farm address not set");
+
+        let auto_farm_address = auto_farm_mapper.get();
+        self.auto_farm_proxy(auto_farm_address)
+            .deposit_for_user(user)
+            .with_multi_token_transfer(tokens)
+            .execute_on_dest_context::<()>();
+    }
+
+    /// Leftovers and rewards are always sent to the caller.
+    /// The new position is either sent to the caller or deposited in auto-farm on their behalf.
+    fn send_new_position(
+        &self,
+        caller: &ManagedAddress,
+        new_position: EsdtTokenPayment,
+        mut output_payments: PaymentsWrapper<Self::Api>,
+        destination: PositionDestination,
+    ) -> PaymentsVec<Self::Api> {
+        match destination {
+            PositionDestination::Caller => output_payments.push(new_position),
+            PositionDestination::AutoFarm => self.call_auto_farm_deposit_for_user(
+                caller.clone(),
+                PaymentsVec::from_single_item(new_position),
+            ),
+        }
+
+        output_payments.send_and_return(caller)
+    }
+
+    #[view(getAutoFarmAddress)]
+    #[storage_mapper("autoFarmAddress")]
+    fn auto_farm_address(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[proxy]
+    fn auto_farm_proxy(&self, sc_address: ManagedAddress) -> auto_farm::Proxy<Self::Api>;
+}
@@ -1,7 +1,7 @@
 multiversx_sc::imports!();
 
 #[multiversx_sc::module]
-pub trait EgldWrapperActionsModule {
+pub trait EgldWrapperActionsModule: read_external_storage::ReadExternalStorageModule {
     fn call_wrap_egld(&self, egld_amount: BigUint) -> EsdtTokenPayment {
         let wrapper_sc_address = self.egld_wrapper_address().get();
         let ((), back_transfers) = self
@@ -19,6 +19,27 @@ pub trait EgldWrapperActionsModule {
         returned_wrapped_egld.get(0)
     }
 
+    fn call_unwrap_egld(&self, wrapped_egld_payment: EsdtTokenPayment) -> BigUint {
+        let wrapper_sc_address = self.egld_wrapper_address().get();
+        let ((), back_transfers) = self
+            .egld_wrapper_proxy(wrapper_sc_address)
+            .unwrap_egld()
+            .with_esdt_transfer(wrapped_egld_payment)
+            .execute_on_dest_context_with_back_transfers();
+
+        back_transfers.total_egld_amount
+    }
+
+    /// EGLD is replaced by the wrapper's WEGLD token
+    fn get_wrapped_token_id(&self, token_id: &EgldOrEsdtTokenIdentifier) -> TokenIdentifier {
+        if token_id.is_egld() {
+            let wrapper_sc_address = self.egld_wrapper_address().get();
+            self.get_wrapped_egld_token_id_mapper(wrapper_sc_address).get()
+        } else {
+            token_id.clone().unwrap_esdt()
+        }
+    }
+
     #[storage_mapper("egldWrapperAddress")]
     fn egld_wrapper_address(&self) -> SingleValueMapper<ManagedAddress>;
 
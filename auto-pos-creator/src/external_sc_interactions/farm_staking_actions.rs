@@ -3,6 +3,7 @@ multiversx_sc::imports!();
 use common_structs::PaymentsVec;
 use farm::EnterFarmResultType;
 use farm_staking::stake_farm::ProxyTrait as OtherProxyTrait;
+use farm_staking::unbond_farm::ProxyTrait as OtherProxyTrait2;
 
 #[multiversx_sc::module]
 pub trait FarmStakingActionsModule: read_external_storage::ReadExternalStorageModule {
@@ -18,6 +19,17 @@ pub trait FarmStakingActionsModule: read_external_storage::ReadExternalStorageMo
             .execute_on_dest_context()
     }
 
+    fn call_farm_staking_unbond(
+        &self,
+        sc_address: ManagedAddress,
+        unbond_tokens: EsdtTokenPayment,
+    ) -> EsdtTokenPayment {
+        self.farm_staking_proxy(sc_address)
+            .unbond_farm()
+            .with_esdt_transfer(unbond_tokens)
+            .execute_on_dest_context()
+    }
+
     fn get_farm_staking_farming_token_id(&self, sc_address: ManagedAddress) -> TokenIdentifier {
         self.get_farming_token_id_mapper(sc_address).get()
     }
@@ -1,6 +1,8 @@
+pub mod auto_farm_actions;
 pub mod egld_wrapper_actions;
 pub mod farm_actions;
 pub mod farm_staking_actions;
 pub mod metastaking_actions;
 pub mod pair_actions;
+pub mod route_finder;
 pub mod router_actions;
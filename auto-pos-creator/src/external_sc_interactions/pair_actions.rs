@@ -7,6 +7,8 @@ use pair::pair_actions::{
     swap::ProxyTrait as _,
 };
 
+use crate::configs::price_deviation_config::SwapLeg;
+
 pub const SWAP_MIN_AMOUNT: u64 = 1;
 
 pub struct PairAddLiqArgs<M: ManagedTypeApi> {
@@ -34,6 +36,7 @@ pub type PairTokenPayments<M> = PairRemoveLiqResult<M>;
 pub trait PairActionsModule:
     read_external_storage::ReadExternalStorageModule
     + crate::configs::pairs_config::PairsConfigModule
+    + crate::configs::price_deviation_config::PriceDeviationConfigModule
     + utils::UtilsModule
 {
     fn call_pair_swap(
@@ -48,6 +51,60 @@ pub trait PairActionsModule:
             .execute_on_dest_context()
     }
 
+    /// The reference price is read before swapping
+    fn call_pair_swap_with_price_check(
+        &self,
+        pair_address: ManagedAddress,
+        input_tokens: EsdtTokenPayment,
+        requested_token_id: TokenIdentifier,
+        max_price_deviation_bps: u64,
+        swap_leg: SwapLeg,
+    ) -> EsdtTokenPayment {
+        let min_amount_out = self.get_min_amount_out_by_price_deviation(
+            &pair_address,
+            &input_tokens,
+            max_price_deviation_bps,
+        );
+        let output_tokens = self.call_pair_swap(pair_address, input_tokens, requested_token_id);
+        if output_tokens.amount < min_amount_out {
+            sc_panic!(swap_leg.price_deviation_err_msg());
+        }
+
+        output_tokens
+    }
+
+    fn get_optimal_swap_amount(
+        &self,
+        pair_address: ManagedAddress,
+        input_tokens: &EsdtTokenPayment,
+    ) -> BigUint {
+        let reserve_in = self
+            .get_pair_reserve_mapper(pair_address.clone(), &input_tokens.token_identifier)
+            .get();
+        let total_fee_percent = self.get_total_fee_percent_mapper(pair_address).get();
+
+        pair_math::get_optimal_swap_amount(&input_tokens.amount, &reserve_in, total_fee_percent)
+    }
+
+    /// Same formula as the pair's get_amount_out, using the current reserves
+    fn get_amount_out_estimate(
+        &self,
+        pair_address: ManagedAddress,
+        input_token_id: &TokenIdentifier,
+        amount_in: &BigUint,
+        output_token_id: &TokenIdentifier,
+    ) -> BigUint {
+        let reserve_in = self
+            .get_pair_reserve_mapper(pair_address.clone(), input_token_id)
+            .get();
+        let reserve_out = self
+            .get_pair_reserve_mapper(pair_address.clone(), output_token_id)
+            .get();
+        let total_fee_percent = self.get_total_fee_percent_mapper(pair_address).get();
+
+        pair_math::get_amount_out(amount_in, &reserve_in, &reserve_out, total_fee_percent)
+    }
+
     fn call_pair_add_liquidity(
         &self,
         args: PairAddLiqArgs<Self::Api>,
@@ -0,0 +1,339 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::configs::{pairs_config::PairConfig, price_deviation_config::SwapLeg};
+
+use super::{pair_actions::SWAP_MIN_AMOUNT, router_actions::SwapOperationType};
+
+pub static SWAP_TOKENS_FIXED_INPUT_FUNC_NAME: &[u8] = b"swapTokensFixedInput";
+
+#[derive(ManagedVecItem, Clone)]
+pub struct RouteHop<M: ManagedTypeApi> {
+    pub pair_address: ManagedAddress<M>,
+    pub output_token_id: TokenIdentifier<M>,
+}
+
+pub struct SwapRoute<M: ManagedTypeApi> {
+    pub hops: ManagedVec<M, RouteHop<M>>,
+    pub amount_out: BigUint<M>,
+}
+
+/// Routes are only discovered when requested.
+/// Each discovered hop is checked against the given max price deviation,
+/// or against the one configured for the hop's pair.
+#[derive(Clone, Copy)]
+pub enum RouteDiscovery {
+    Disabled,
+    Enabled {
+        opt_max_price_deviation_bps: Option<u64>,
+    },
+}
+
+#[multiversx_sc::module]
+pub trait RouteFinderModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::configs::pairs_config::PairsConfigModule
+    + super::pair_actions::PairActionsModule
+    + super::router_actions::RouterActionsModule
+    + crate::events::EventsModule
+    + utils::UtilsModule
+{
+    /// Intermediary tokens used for route discovery, e.g. WEGLD and USDC.
+    #[only_owner]
+    #[endpoint(addHubTokens)]
+    fn add_hub_tokens(&self, hub_tokens: MultiValueEncoded<TokenIdentifier>) {
+        let mut mapper = self.hub_tokens();
+        for token_id in hub_tokens {
+            self.require_valid_token_id(&token_id);
+            let _ = mapper.insert(token_id);
+        }
+    }
+
+    #[only_owner]
+    #[endpoint(removeHubTokens)]
+    fn remove_hub_tokens(&self, hub_tokens: MultiValueEncoded<TokenIdentifier>) {
+        let mut mapper = self.hub_tokens();
+        for token_id in hub_tokens {
+            let _ = mapper.swap_remove(&token_id);
+        }
+    }
+
+    /// Swaps the input tokens to one of the pair tokens.
+    /// If no swap operations are given, route discovery is enabled
+    /// and the tokens are not accepted by the pair,
+    /// the best route is discovered from the router's pairs.
+    fn process_payment_for_pair(
+        &self,
+        payment: EsdtTokenPayment,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+        pair_address: &ManagedAddress,
+        route_discovery: RouteDiscovery,
+    ) -> EsdtTokenPayment {
+        if !swap_operations.is_empty() {
+            return self.call_router_swap_with_event(payment, swap_operations);
+        }
+
+        let opt_max_price_deviation_bps = match route_discovery {
+            RouteDiscovery::Disabled => return payment,
+            RouteDiscovery::Enabled {
+                opt_max_price_deviation_bps,
+            } => opt_max_price_deviation_bps,
+        };
+
+        let pair_config = self.get_pair_config(pair_address);
+        let token_id = &payment.token_identifier;
+        if token_id == &pair_config.first_token_id
+            || token_id == &pair_config.second_token_id
+            || token_id == &pair_config.lp_token_id
+        {
+            return payment;
+        }
+
+        match self.find_best_route_to_pair(&payment, pair_address, &pair_config) {
+            Some(route) => {
+                self.call_router_swap_route(payment, &route, opt_max_price_deviation_bps)
+            }
+            None => payment,
+        }
+    }
+
+    /// Same as process_payment_for_pair, but the only wanted output is the given token
+    fn process_payment_for_token(
+        &self,
+        payment: EsdtTokenPayment,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+        wanted_token_id: &TokenIdentifier,
+        route_discovery: RouteDiscovery,
+    ) -> EsdtTokenPayment {
+        if !swap_operations.is_empty() {
+            return self.call_router_swap_with_event(payment, swap_operations);
+        }
+
+        let opt_max_price_deviation_bps = match route_discovery {
+            RouteDiscovery::Disabled => return payment,
+            RouteDiscovery::Enabled {
+                opt_max_price_deviation_bps,
+            } => opt_max_price_deviation_bps,
+        };
+
+        if &payment.token_identifier == wanted_token_id {
+            return payment;
+        }
+
+        match self.find_best_route(&payment, wanted_token_id) {
+            Some(route) => {
+                self.call_router_swap_route(payment, &route, opt_max_price_deviation_bps)
+            }
+            None => payment,
+        }
+    }
+
+    /// Compares the best routes to each of the pair tokens.
+    /// Outputs in the second token are valued in first tokens, using the pair's reserves.
+    fn find_best_route_to_pair(
+        &self,
+        input_tokens: &EsdtTokenPayment,
+        pair_address: &ManagedAddress,
+        pair_config: &PairConfig<Self::Api>,
+    ) -> Option<SwapRoute<Self::Api>> {
+        let opt_first_route = self.find_best_route(input_tokens, &pair_config.first_token_id);
+        let opt_second_route = self.find_best_route(input_tokens, &pair_config.second_token_id);
+
+        match (opt_first_route, opt_second_route) {
+            (Some(first_route), Some(second_route)) => {
+                let first_reserve = self
+                    .get_pair_reserve_mapper(pair_address.clone(), &pair_config.first_token_id)
+                    .get();
+                let second_reserve = self
+                    .get_pair_reserve_mapper(pair_address.clone(), &pair_config.second_token_id)
+                    .get();
+                if second_reserve == 0 {
+                    return Some(first_route);
+                }
+
+                let second_route_value = &second_route.amount_out * &first_reserve / second_reserve;
+                if first_route.amount_out >= second_route_value {
+                    Some(first_route)
+                } else {
+                    Some(second_route)
+                }
+            }
+            (opt_first_route, None) => opt_first_route,
+            (None, opt_second_route) => opt_second_route,
+        }
+    }
+
+    /// Candidates are the direct pair and the routes through each of the hub tokens
+    fn find_best_route(
+        &self,
+        input_tokens: &EsdtTokenPayment,
+        wanted_token_id: &TokenIdentifier,
+    ) -> Option<SwapRoute<Self::Api>> {
+        let input_token_id = &input_tokens.token_identifier;
+        let mut opt_best_route = None;
+
+        if let Some(pair_address) = self.get_router_pair(input_token_id, wanted_token_id) {
+            let mut hops = ManagedVec::new();
+            hops.push(RouteHop {
+                pair_address,
+                output_token_id: wanted_token_id.clone(),
+            });
+            self.update_best_route(&mut opt_best_route, input_tokens, hops);
+        }
+
+        for hub_token_id in self.hub_tokens().iter() {
+            if &hub_token_id == input_token_id || &hub_token_id == wanted_token_id {
+                continue;
+            }
+
+            let opt_first_pair = self.get_router_pair(input_token_id, &hub_token_id);
+            let opt_second_pair = self.get_router_pair(&hub_token_id, wanted_token_id);
+            if let (Some(first_pair), Some(second_pair)) = (opt_first_pair, opt_second_pair) {
+                let mut hops = ManagedVec::new();
+                hops.push(RouteHop {
+                    pair_address: first_pair,
+                    output_token_id: hub_token_id,
+                });
+                hops.push(RouteHop {
+                    pair_address: second_pair,
+                    output_token_id: wanted_token_id.clone(),
+                });
+                self.update_best_route(&mut opt_best_route, input_tokens, hops);
+            }
+        }
+
+        opt_best_route
+    }
+
+    fn update_best_route(
+        &self,
+        opt_best_route: &mut Option<SwapRoute<Self::Api>>,
+        input_tokens: &EsdtTokenPayment,
+        hops: ManagedVec<RouteHop<Self::Api>>,
+    ) {
+        let amount_out = self.estimate_route_amount_out(input_tokens, &hops);
+        if amount_out == 0 {
+            return;
+        }
+
+        if let Some(best_route) = opt_best_route {
+            if best_route.amount_out >= amount_out {
+                return;
+            }
+        }
+
+        *opt_best_route = Some(SwapRoute { hops, amount_out });
+    }
+
+    fn estimate_route_amount_out(
+        &self,
+        input_tokens: &EsdtTokenPayment,
+        hops: &ManagedVec<RouteHop<Self::Api>>,
+    ) -> BigUint {
+        let mut current_token_id = input_tokens.token_identifier.clone();
+        let mut current_amount = input_tokens.amount.clone();
+        for hop in hops {
+            current_amount = self.get_amount_out_estimate(
+                hop.pair_address,
+                &current_token_id,
+                &current_amount,
+                &hop.output_token_id,
+            );
+            current_token_id = hop.output_token_id;
+        }
+
+        current_amount
+    }
+
+    fn get_router_pair(
+        &self,
+        first_token_id: &TokenIdentifier,
+        second_token_id: &TokenIdentifier,
+    ) -> Option<ManagedAddress> {
+        let router_address = self.router_address().get();
+        let mapper = self.get_router_pair_address_mapper(
+            router_address.clone(),
+            first_token_id,
+            second_token_id,
+        );
+        if !mapper.is_empty() {
+            return Some(mapper.get());
+        }
+
+        let reversed_mapper =
+            self.get_router_pair_address_mapper(router_address, second_token_id, first_token_id);
+        if !reversed_mapper.is_empty() {
+            return Some(reversed_mapper.get());
+        }
+
+        None
+    }
+
+    /// Each hop's min amount out is derived from the reference price of the hop's pair.
+    /// The route is executed right after being estimated,
+    /// so the estimated amounts are the amounts each hop receives.
+    fn call_router_swap_route(
+        &self,
+        input_tokens: EsdtTokenPayment,
+        route: &SwapRoute<Self::Api>,
+        opt_max_price_deviation_bps: Option<u64>,
+    ) -> EsdtTokenPayment {
+        let mut swap_operations = MultiValueEncoded::new();
+        let mut hop_input_tokens = input_tokens.clone();
+        for hop in &route.hops {
+            let max_price_deviation_bps =
+                self.get_max_price_deviation(&hop.pair_address, opt_max_price_deviation_bps);
+            let mut min_amount_out = self.get_min_amount_out_by_price_deviation(
+                &hop.pair_address,
+                &hop_input_tokens,
+                max_price_deviation_bps,
+            );
+            let amount_out = self.get_amount_out_estimate(
+                hop.pair_address.clone(),
+                &hop_input_tokens.token_identifier,
+                &hop_input_tokens.amount,
+                &hop.output_token_id,
+            );
+            if amount_out < min_amount_out {
+                sc_panic!(SwapLeg::DiscoveredRoute.price_deviation_err_msg());
+            }
+            if min_amount_out == 0 {
+                min_amount_out = BigUint::from(SWAP_MIN_AMOUNT);
+            }
+
+            hop_input_tokens = EsdtTokenPayment::new(hop.output_token_id.clone(), 0, amount_out);
+            swap_operations.push(
+                (
+                    hop.pair_address,
+                    ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    hop.output_token_id,
+                    min_amount_out,
+                )
+                    .into(),
+            );
+        }
+
+        self.call_router_swap_with_event(input_tokens, swap_operations)
+    }
+
+    fn call_router_swap_with_event(
+        &self,
+        input_tokens: EsdtTokenPayment,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> EsdtTokenPayment {
+        let mut route = ManagedVec::new();
+        for swap_operation in swap_operations.clone() {
+            let (pair_address, _, _, _) = swap_operation.into_tuple();
+            route.push(pair_address);
+        }
+
+        let output_tokens = self.call_router_swap(input_tokens.clone(), swap_operations);
+        self.emit_swap_event(&route, &input_tokens, &output_tokens);
+
+        output_tokens
+    }
+
+    #[view(getHubTokens)]
+    #[storage_mapper("hubTokens")]
+    fn hub_tokens(&self) -> UnorderedSetMapper<TokenIdentifier>;
+}
@@ -1,10 +1,34 @@
 multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
 
 use router::{factory::ProxyTrait as _, multi_pair_swap::ProxyTrait as _};
 
 pub type SwapOperationType<M> =
     MultiValue4<ManagedAddress<M>, ManagedBuffer<M>, TokenIdentifier<M>, BigUint<M>>;
 
+/// Same fields as SwapOperationType, usable when multiple routes are given as arguments
+#[derive(
+    TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, Clone, PartialEq,
+)]
+pub struct SwapOperation<M: ManagedTypeApi> {
+    pub pair_address: ManagedAddress<M>,
+    pub function_name: ManagedBuffer<M>,
+    pub token_wanted: TokenIdentifier<M>,
+    pub min_amount_out: BigUint<M>,
+}
+
+impl<M: ManagedTypeApi> From<SwapOperation<M>> for SwapOperationType<M> {
+    fn from(swap_operation: SwapOperation<M>) -> Self {
+        (
+            swap_operation.pair_address,
+            swap_operation.function_name,
+            swap_operation.token_wanted,
+            swap_operation.min_amount_out,
+        )
+            .into()
+    }
+}
+
 #[multiversx_sc::module]
 pub trait RouterActionsModule {
     fn check_router_pair(
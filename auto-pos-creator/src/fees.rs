@@ -0,0 +1,155 @@
+multiversx_sc::imports!();
+
+use common_structs::PaymentsVec;
+use pair_math::MAX_BPS;
+
+pub const MAX_FEE_BPS: u64 = 1_000;
+
+#[multiversx_sc::module]
+pub trait FeesModule: utils::UtilsModule {
+    /// Fee taken from the input tokens of the create position endpoints, before any swap.
+    /// Migrations pay it on the tokens received from exiting the old position.
+    /// Set to 0 to disable the fee.
+    #[only_owner]
+    #[endpoint(setFeeBps)]
+    fn set_fee_bps(&self, fee_bps: u64) {
+        require!(fee_bps <= MAX_FEE_BPS, "Invalid fee");
+
+        self.fee_bps().set(fee_bps);
+    }
+
+    #[only_owner]
+    #[endpoint(setFeeCollector)]
+    fn set_fee_collector(&self, fee_collector: ManagedAddress) {
+        require!(!fee_collector.is_zero(), "Invalid fee collector address");
+
+        self.fee_collector().set(fee_collector);
+    }
+
+    #[only_owner]
+    #[endpoint(addFeeExemptAddresses)]
+    fn add_fee_exempt_addresses(&self, addresses: MultiValueEncoded<ManagedAddress>) {
+        let mut mapper = self.fee_exempt_addresses();
+        for address in addresses {
+            let _ = mapper.insert(address);
+        }
+    }
+
+    #[only_owner]
+    #[endpoint(removeFeeExemptAddresses)]
+    fn remove_fee_exempt_addresses(&self, addresses: MultiValueEncoded<ManagedAddress>) {
+        let mut mapper = self.fee_exempt_addresses();
+        for address in addresses {
+            let _ = mapper.swap_remove(&address);
+        }
+    }
+
+    #[endpoint(claimFees)]
+    fn claim_fees(&self) -> PaymentsVec<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        let fee_collector_mapper = self.fee_collector();
+        require!(
+            !fee_collector_mapper.is_empty() && fee_collector_mapper.get() == caller,
+            "Only the fee collector can claim fees"
+        );
+
+        let mut fee_tokens_mapper = self.fee_tokens();
+        let mut fees = PaymentsVec::new();
+        for token_id in fee_tokens_mapper.iter() {
+            let amount = self.accumulated_fees(&token_id).take();
+            fees.push(EsdtTokenPayment::new(token_id, 0, amount));
+        }
+        fee_tokens_mapper.clear();
+
+        if !fees.is_empty() {
+            self.send().direct_multi(&caller, &fees);
+        }
+
+        self.fees_claimed_event(&caller, &fees);
+
+        fees
+    }
+
+    fn deduct_protocol_fees(
+        &self,
+        user: &ManagedAddress,
+        payments: PaymentsVec<Self::Api>,
+    ) -> PaymentsVec<Self::Api> {
+        let mut remaining_payments = PaymentsVec::new();
+        for payment in &payments {
+            remaining_payments.push(self.deduct_protocol_fee(user, payment));
+        }
+
+        remaining_payments
+    }
+
+    /// Returns the remaining tokens
+    fn deduct_protocol_fee(
+        &self,
+        user: &ManagedAddress,
+        mut payment: EsdtTokenPayment,
+    ) -> EsdtTokenPayment {
+        let fee_bps = self.fee_bps().get();
+        if fee_bps == 0 || self.fee_exempt_addresses().contains(user) {
+            return payment;
+        }
+
+        let fee_amount = &payment.amount * fee_bps / MAX_BPS;
+        if fee_amount == 0 {
+            return payment;
+        }
+
+        payment.amount -= &fee_amount;
+        let _ = self.fee_tokens().insert(payment.token_identifier.clone());
+        self.accumulated_fees(&payment.token_identifier)
+            .update(|accumulated_fees| *accumulated_fees += &fee_amount);
+
+        self.protocol_fee_event(user, &payment.token_identifier, &fee_amount);
+
+        payment
+    }
+
+    #[view(getAccumulatedFees)]
+    fn get_accumulated_fees(&self) -> MultiValueEncoded<EsdtTokenPayment> {
+        let mut fees = MultiValueEncoded::new();
+        for token_id in self.fee_tokens().iter() {
+            let amount = self.accumulated_fees(&token_id).get();
+            fees.push(EsdtTokenPayment::new(token_id, 0, amount));
+        }
+
+        fees
+    }
+
+    #[event("protocolFee")]
+    fn protocol_fee_event(
+        &self,
+        #[indexed] user: &ManagedAddress,
+        #[indexed] token_id: &TokenIdentifier,
+        fee_amount: &BigUint,
+    );
+
+    #[event("feesClaimed")]
+    fn fees_claimed_event(
+        &self,
+        #[indexed] fee_collector: &ManagedAddress,
+        fees: &PaymentsVec<Self::Api>,
+    );
+
+    #[view(getFeeBps)]
+    #[storage_mapper("feeBps")]
+    fn fee_bps(&self) -> SingleValueMapper<u64>;
+
+    #[view(getFeeCollector)]
+    #[storage_mapper("feeCollector")]
+    fn fee_collector(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getFeeExemptAddresses)]
+    #[storage_mapper("feeExemptAddresses")]
+    fn fee_exempt_addresses(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[storage_mapper("feeTokens")]
+    fn fee_tokens(&self) -> UnorderedSetMapper<TokenIdentifier>;
+
+    #[storage_mapper("accumulatedFees")]
+    fn accumulated_fees(&self, token_id: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+}
@@ -4,24 +4,40 @@ multiversx_sc::imports!();
 
 pub mod common;
 pub mod configs;
+pub mod events;
 pub mod external_sc_interactions;
+pub mod fees;
 pub mod multi_contract_interactions;
+pub mod quotes;
 
 #[multiversx_sc::contract]
 pub trait AutoPosCreator:
     utils::UtilsModule
     + read_external_storage::ReadExternalStorageModule
     + configs::pairs_config::PairsConfigModule
+    + configs::price_deviation_config::PriceDeviationConfigModule
+    + configs::contracts_whitelist::ContractsWhitelistModule
+    + events::EventsModule
     + external_sc_interactions::pair_actions::PairActionsModule
     + external_sc_interactions::router_actions::RouterActionsModule
     + external_sc_interactions::farm_actions::FarmActionsModule
     + external_sc_interactions::farm_staking_actions::FarmStakingActionsModule
     + external_sc_interactions::metastaking_actions::MetastakingActionsModule
     + external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + external_sc_interactions::route_finder::RouteFinderModule
+    + external_sc_interactions::auto_farm_actions::AutoFarmActionsModule
+    + fees::FeesModule
     + multi_contract_interactions::create_pos::CreatePosModule
     + multi_contract_interactions::create_pos_endpoints::CreatePosEndpointsModule
+    + multi_contract_interactions::create_multi_token_pos::CreateMultiTokenPosModule
+    + multi_contract_interactions::merge_pos::MergePosModule
+    + multi_contract_interactions::batch_create_pos::BatchCreatePosModule
     + multi_contract_interactions::exit_pos::ExitPosModule
     + multi_contract_interactions::exit_pos_endpoints::ExitPosEndpointsModule
+    + multi_contract_interactions::exit_to_single_token::ExitToSingleTokenModule
+    + multi_contract_interactions::migrate_pos::MigratePosModule
+    + multi_contract_interactions::pending_unbonds::PendingUnbondsModule
+    + quotes::quote_views::QuoteViewsModule
 {
     #[init]
     fn init(&self, egld_wrapper_address: ManagedAddress, router_address: ManagedAddress) {
@@ -31,6 +47,12 @@ pub trait AutoPosCreator:
         self.router_address().set(router_address);
     }
 
+    /// Contracts deployed before the whitelists keep accepting any contract,
+    /// until the owner fills the whitelists and disables the permissionless mode
     #[upgrade]
-    fn upgrade(&self) {}
+    fn upgrade(&self) {
+        if !self.has_whitelisted_contracts() {
+            self.permissionless().set(true);
+        }
+    }
 }
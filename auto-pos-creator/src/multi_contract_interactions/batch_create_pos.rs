@@ -0,0 +1,263 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::PaymentsVec;
+use pair_math::MAX_BPS;
+
+use crate::{
+    common::payments_wrapper::PaymentsWrapper,
+    events::PositionEventType,
+    external_sc_interactions::{
+        route_finder::RouteDiscovery,
+        router_actions::{SwapOperation, SwapOperationType},
+    },
+};
+
+use super::{
+    create_pos::{CreateFarmPosArgs, CreateMetastakingPosArgs},
+    merge_pos::PositionType,
+};
+
+/// target_address: farm, metastaking or farm staking contract
+/// weight_bps: share of the input payment used for this position. All weights must add up to 100%.
+/// swap_route: an empty route means the payment is not swapped, unless discover_route is set
+/// discover_route: discovers the route on-chain when the swap route is empty.
+/// Each discovered hop is checked against its pair's configured max price deviation.
+/// first_token_min_amount_out, second_token_min_amount_out: add liquidity min amounts.
+/// For farm staking, first_token_min_amount_out is the min amount of farm tokens.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem)]
+pub struct BatchPosEntry<M: ManagedTypeApi> {
+    pub target_address: ManagedAddress<M>,
+    pub weight_bps: u64,
+    pub swap_route: ManagedVec<M, SwapOperation<M>>,
+    pub discover_route: bool,
+    pub first_token_min_amount_out: BigUint<M>,
+    pub second_token_min_amount_out: BigUint<M>,
+}
+
+#[multiversx_sc::module]
+pub trait BatchCreatePosModule:
+    utils::UtilsModule
+    + read_external_storage::ReadExternalStorageModule
+    + crate::configs::pairs_config::PairsConfigModule
+    + crate::configs::contracts_whitelist::ContractsWhitelistModule
+    + crate::events::EventsModule
+    + crate::external_sc_interactions::pair_actions::PairActionsModule
+    + crate::external_sc_interactions::router_actions::RouterActionsModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::farm_staking_actions::FarmStakingActionsModule
+    + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + crate::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + crate::external_sc_interactions::route_finder::RouteFinderModule
+    + super::create_pos::CreatePosModule
+    + crate::fees::FeesModule
+    + super::merge_pos::MergePosModule
+{
+    /// Splits a single EGLD or fungible ESDT payment between the given entries,
+    /// creating one position for each of them.
+    /// The last entry also receives the rounding dust of the split.
+    /// Returns the leftovers and rewards, followed by all the new positions.
+    #[payable("*")]
+    #[endpoint(createPositionsFromSingleToken)]
+    fn create_positions_from_single_token(
+        &self,
+        entries: MultiValueEncoded<BatchPosEntry<Self::Api>>,
+    ) -> PaymentsVec<Self::Api> {
+        require!(!entries.is_empty(), "No entries given");
+
+        let entries = entries.to_vec();
+        let mut total_weight_bps = 0u64;
+        for entry in &entries {
+            require!(entry.weight_bps > 0, "Invalid weight");
+            total_weight_bps += entry.weight_bps;
+        }
+        require!(total_weight_bps == MAX_BPS, "Weights must add up to 100%");
+
+        let caller = self.blockchain().get_caller();
+        let payment = self.call_value().egld_or_single_esdt();
+        let esdt_payment = self.get_esdt_payment(payment);
+        let esdt_payment = self.deduct_protocol_fee(&caller, esdt_payment);
+
+        let mut output_payments = PaymentsWrapper::new();
+        let mut new_positions = PaymentsWrapper::new();
+        let mut remaining_amount = esdt_payment.amount.clone();
+        let last_index = entries.len() - 1;
+        for (i, entry) in entries.iter().enumerate() {
+            let amount = if i == last_index {
+                remaining_amount.clone()
+            } else {
+                &esdt_payment.amount * entry.weight_bps / MAX_BPS
+            };
+            remaining_amount -= &amount;
+            require!(amount > 0, "Payment too small for the entry weight");
+
+            let entry_payment =
+                EsdtTokenPayment::new(esdt_payment.token_identifier.clone(), 0, amount);
+            let (new_position, entry_output_payments) =
+                self.create_batch_entry_position(&caller, entry_payment, entry);
+            output_payments.append(entry_output_payments);
+            new_positions.push(new_position);
+        }
+
+        output_payments.append(new_positions);
+
+        output_payments.send_and_return(&caller)
+    }
+
+    fn create_batch_entry_position(
+        &self,
+        caller: &ManagedAddress,
+        payment: EsdtTokenPayment,
+        entry: BatchPosEntry<Self::Api>,
+    ) -> (EsdtTokenPayment, PaymentsWrapper<Self::Api>) {
+        let input_payments =
+            ManagedVec::from_single_item(EgldOrEsdtTokenPayment::from(payment.clone()));
+        let mut swap_operations = MultiValueEncoded::new();
+        for swap_operation in &entry.swap_route {
+            swap_operations.push(SwapOperationType::from(swap_operation));
+        }
+        let route_discovery = if entry.discover_route {
+            RouteDiscovery::Enabled {
+                opt_max_price_deviation_bps: None,
+            }
+        } else {
+            RouteDiscovery::Disabled
+        };
+
+        let target_address = entry.target_address;
+        let (position_type, (new_position, output_payments)) =
+            match self.get_position_type(&target_address) {
+                PositionType::Farm => {
+                    self.require_whitelisted_farm(&target_address);
+
+                    let pair_address = self
+                        .get_farm_pair_contract_address_mapper(target_address.clone())
+                        .get();
+                    let (first_token_payment, second_token_payment) = self.zap_to_pair_tokens(
+                        payment,
+                        swap_operations,
+                        &pair_address,
+                        route_discovery,
+                    );
+                    let args = CreateFarmPosArgs {
+                        caller: caller.clone(),
+                        first_token_payment,
+                        second_token_payment,
+                        additional_payments: PaymentsVec::new(),
+                        add_liq_first_token_min_amount_out: entry.first_token_min_amount_out,
+                        add_liq_second_token_min_amount_out: entry.second_token_min_amount_out,
+                        pair_address,
+                        farm_address: target_address.clone(),
+                    };
+
+                    (PositionEventType::Farm, self.create_farm_pos(args))
+                }
+                PositionType::Metastaking => {
+                    self.require_whitelisted_metastaking(&target_address);
+
+                    let farm_address = self
+                        .get_lp_farm_address_mapper(target_address.clone())
+                        .get();
+                    let pair_address = self
+                        .get_farm_pair_contract_address_mapper(farm_address.clone())
+                        .get();
+                    let (first_token_payment, second_token_payment) = self.zap_to_pair_tokens(
+                        payment,
+                        swap_operations,
+                        &pair_address,
+                        route_discovery,
+                    );
+                    let args = CreateMetastakingPosArgs {
+                        caller: caller.clone(),
+                        first_token_payment,
+                        second_token_payment,
+                        additional_payments: PaymentsVec::new(),
+                        add_liq_first_token_min_amount_out: entry.first_token_min_amount_out,
+                        add_liq_second_token_min_amount_out: entry.second_token_min_amount_out,
+                        pair_address,
+                        farm_address,
+                        metastaking_address: target_address.clone(),
+                    };
+
+                    (PositionEventType::Metastaking, self.create_metastaking_pos(args))
+                }
+                PositionType::FarmStaking => {
+                    self.require_whitelisted_farm_staking(&target_address);
+
+                    let farming_token_id =
+                        self.get_farm_staking_farming_token_id(target_address.clone());
+                    let token_payment = self.process_payment_for_token(
+                        payment,
+                        swap_operations,
+                        &farming_token_id,
+                        route_discovery,
+                    );
+                    require!(
+                        token_payment.token_identifier == farming_token_id,
+                        "Invalid swap output token identifier"
+                    );
+
+                    let (new_farm_token, boosted_rewards_payment) = self
+                        .call_farm_staking_stake(
+                            target_address.clone(),
+                            caller.clone(),
+                            PaymentsVec::from_single_item(token_payment),
+                        )
+                        .into_tuple();
+                    require!(
+                        new_farm_token.amount >= entry.first_token_min_amount_out,
+                        "Slippage exceeded"
+                    );
+
+                    let mut output_payments = PaymentsWrapper::new();
+                    output_payments.push(boosted_rewards_payment);
+
+                    (PositionEventType::FarmStaking, (new_farm_token, output_payments))
+                }
+            };
+
+        self.emit_create_pos_event(
+            caller,
+            position_type,
+            &target_address,
+            &input_payments,
+            &new_position,
+            output_payments.get_payments(),
+        );
+
+        (new_position, output_payments)
+    }
+
+    /// Uses the pair's configured max price deviation
+    fn zap_to_pair_tokens(
+        &self,
+        payment: EsdtTokenPayment,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+        pair_address: &ManagedAddress,
+        route_discovery: RouteDiscovery,
+    ) -> (EsdtTokenPayment, EsdtTokenPayment) {
+        self.require_sc_address(pair_address);
+
+        let mut first_token_payment = self.process_payment_for_pair(
+            payment,
+            swap_operations,
+            pair_address,
+            route_discovery,
+        );
+        let max_price_deviation_bps = self.get_max_price_deviation(pair_address, None);
+        let initial_payment = first_token_payment.clone();
+        let second_token_payment = self.swap_optimal_input_if_needed(
+            &mut first_token_payment,
+            pair_address.clone(),
+            max_price_deviation_bps,
+        );
+        self.emit_optimal_swap_event(
+            pair_address,
+            &initial_payment,
+            &first_token_payment,
+            &second_token_payment,
+        );
+
+        (first_token_payment, second_token_payment)
+    }
+}
@@ -0,0 +1,380 @@
+multiversx_sc::imports!();
+
+use common_structs::PaymentsVec;
+
+use crate::{
+    configs::{pairs_config::PairConfig, price_deviation_config::SwapLeg},
+    events::PositionEventType,
+    external_sc_interactions::{
+        pair_actions::PairTokenPayments,
+        route_finder::RouteDiscovery,
+        router_actions::{SwapOperation, SwapOperationType},
+    },
+};
+
+use super::create_pos::{CreateFarmPosArgs, CreateMetastakingPosArgs, CreatePosOptions};
+
+pub type SwapRouteArg<M> = ManagedVec<M, SwapOperation<M>>;
+
+/// The identifier of the EGLD part of a multi-transfer
+pub const EGLD_000000_TOKEN_ID: &[u8] = b"EGLD-000000";
+
+#[multiversx_sc::module]
+pub trait CreateMultiTokenPosModule:
+    utils::UtilsModule
+    + read_external_storage::ReadExternalStorageModule
+    + crate::configs::pairs_config::PairsConfigModule
+    + crate::configs::contracts_whitelist::ContractsWhitelistModule
+    + crate::events::EventsModule
+    + crate::external_sc_interactions::pair_actions::PairActionsModule
+    + crate::external_sc_interactions::router_actions::RouterActionsModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + crate::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + crate::external_sc_interactions::route_finder::RouteFinderModule
+    + crate::external_sc_interactions::auto_farm_actions::AutoFarmActionsModule
+    + super::create_pos::CreatePosModule
+    + crate::fees::FeesModule
+{
+    /// Accepts any number of fungible ESDT payments, or a single EGLD payment.
+    /// EGLD can't be part of a multi-transfer, it has to be sent alone to be wrapped.
+    /// swap_routes: one route for each payment, in payment order.
+    /// An empty route means the payment is not swapped,
+    /// unless route discovery is requested through the options.
+    /// If no routes are given at all, every route is empty.
+    #[payable("*")]
+    #[endpoint(createFarmPosFromMultipleTokens)]
+    fn create_farm_pos_from_multiple_tokens(
+        &self,
+        farm_address: ManagedAddress,
+        add_liq_first_token_min_amount_out: BigUint,
+        add_liq_second_token_min_amount_out: BigUint,
+        swap_routes: MultiValueEncoded<SwapRouteArg<Self::Api>>,
+    ) -> PaymentsVec<Self::Api> {
+        self.create_farm_pos_from_multiple_tokens_with_options(
+            farm_address,
+            add_liq_first_token_min_amount_out,
+            add_liq_second_token_min_amount_out,
+            CreatePosOptions::default(),
+            swap_routes,
+        )
+    }
+
+    #[payable("*")]
+    #[endpoint(createFarmPosFromMultipleTokensWithOptions)]
+    fn create_farm_pos_from_multiple_tokens_with_options(
+        &self,
+        farm_address: ManagedAddress,
+        add_liq_first_token_min_amount_out: BigUint,
+        add_liq_second_token_min_amount_out: BigUint,
+        options: CreatePosOptions,
+        swap_routes: MultiValueEncoded<SwapRouteArg<Self::Api>>,
+    ) -> PaymentsVec<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        self.require_whitelisted_farm(&farm_address);
+
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(farm_address.clone())
+            .get();
+        self.require_sc_address(&pair_address);
+
+        let max_price_deviation_bps =
+            self.get_max_price_deviation(&pair_address, options.opt_max_price_deviation_bps);
+        let pair_tokens = self.convert_payments_to_pair_tokens(
+            &caller,
+            &pair_address,
+            max_price_deviation_bps,
+            options.route_discovery(),
+            swap_routes,
+        );
+        let args = CreateFarmPosArgs {
+            caller: caller.clone(),
+            first_token_payment: pair_tokens.first_tokens,
+            second_token_payment: pair_tokens.second_tokens,
+            additional_payments: PaymentsVec::new(),
+            add_liq_first_token_min_amount_out,
+            add_liq_second_token_min_amount_out,
+            pair_address,
+            farm_address: farm_address.clone(),
+        };
+        let (new_farm_tokens, output_payments) = self.create_farm_pos(args);
+        self.emit_create_pos_event(
+            &caller,
+            PositionEventType::Farm,
+            &farm_address,
+            &self.get_input_payments(),
+            &new_farm_tokens,
+            output_payments.get_payments(),
+        );
+
+        self.send_new_position(
+            &caller,
+            new_farm_tokens,
+            output_payments,
+            options.destination,
+        )
+    }
+
+    /// Same arguments as createFarmPosFromMultipleTokens
+    #[payable("*")]
+    #[endpoint(createMetastakingPosFromMultipleTokens)]
+    fn create_metastaking_pos_from_multiple_tokens(
+        &self,
+        metastaking_address: ManagedAddress,
+        add_liq_first_token_min_amount_out: BigUint,
+        add_liq_second_token_min_amount_out: BigUint,
+        swap_routes: MultiValueEncoded<SwapRouteArg<Self::Api>>,
+    ) -> PaymentsVec<Self::Api> {
+        self.create_metastaking_pos_from_multiple_tokens_with_options(
+            metastaking_address,
+            add_liq_first_token_min_amount_out,
+            add_liq_second_token_min_amount_out,
+            CreatePosOptions::default(),
+            swap_routes,
+        )
+    }
+
+    #[payable("*")]
+    #[endpoint(createMetastakingPosFromMultipleTokensWithOptions)]
+    fn create_metastaking_pos_from_multiple_tokens_with_options(
+        &self,
+        metastaking_address: ManagedAddress,
+        add_liq_first_token_min_amount_out: BigUint,
+        add_liq_second_token_min_amount_out: BigUint,
+        options: CreatePosOptions,
+        swap_routes: MultiValueEncoded<SwapRouteArg<Self::Api>>,
+    ) -> PaymentsVec<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        self.require_whitelisted_metastaking(&metastaking_address);
+
+        let farm_address = self
+            .get_lp_farm_address_mapper(metastaking_address.clone())
+            .get();
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(farm_address.clone())
+            .get();
+        self.require_sc_address(&pair_address);
+
+        let max_price_deviation_bps =
+            self.get_max_price_deviation(&pair_address, options.opt_max_price_deviation_bps);
+        let pair_tokens = self.convert_payments_to_pair_tokens(
+            &caller,
+            &pair_address,
+            max_price_deviation_bps,
+            options.route_discovery(),
+            swap_routes,
+        );
+        let args = CreateMetastakingPosArgs {
+            caller: caller.clone(),
+            first_token_payment: pair_tokens.first_tokens,
+            second_token_payment: pair_tokens.second_tokens,
+            additional_payments: PaymentsVec::new(),
+            add_liq_first_token_min_amount_out,
+            add_liq_second_token_min_amount_out,
+            pair_address,
+            farm_address,
+            metastaking_address: metastaking_address.clone(),
+        };
+        let (new_metastaking_tokens, output_payments) = self.create_metastaking_pos(args);
+        self.emit_create_pos_event(
+            &caller,
+            PositionEventType::Metastaking,
+            &metastaking_address,
+            &self.get_input_payments(),
+            &new_metastaking_tokens,
+            output_payments.get_payments(),
+        );
+
+        self.send_new_position(
+            &caller,
+            new_metastaking_tokens,
+            output_payments,
+            options.destination,
+        )
+    }
+
+    /// Swaps every payment to one of the pair tokens,
+    /// then swaps the excess of one of the tokens so the results are in the pool ratio
+    fn convert_payments_to_pair_tokens(
+        &self,
+        caller: &ManagedAddress,
+        pair_address: &ManagedAddress,
+        max_price_deviation_bps: u64,
+        route_discovery: RouteDiscovery,
+        swap_routes: MultiValueEncoded<SwapRouteArg<Self::Api>>,
+    ) -> PairTokenPayments<Self::Api> {
+        let payments = self.get_fungible_esdt_payments();
+        let payments = self.deduct_protocol_fees(caller, payments);
+
+        self.swap_payments_to_pair_tokens(
+            pair_address,
+            payments,
+            max_price_deviation_bps,
+            route_discovery,
+            swap_routes,
+        )
+    }
+
+    /// Same as convert_payments_to_pair_tokens, but for the given payments
+    fn swap_payments_to_pair_tokens(
+        &self,
+        pair_address: &ManagedAddress,
+        payments: PaymentsVec<Self::Api>,
+        max_price_deviation_bps: u64,
+        route_discovery: RouteDiscovery,
+        swap_routes: MultiValueEncoded<SwapRouteArg<Self::Api>>,
+    ) -> PairTokenPayments<Self::Api> {
+        require!(
+            swap_routes.is_empty() || swap_routes.len() == payments.len(),
+            "Invalid number of swap routes"
+        );
+
+        let pair_config = self.get_pair_config(pair_address);
+        let mut first_tokens =
+            EsdtTokenPayment::new(pair_config.first_token_id.clone(), 0, BigUint::zero());
+        let mut second_tokens =
+            EsdtTokenPayment::new(pair_config.second_token_id.clone(), 0, BigUint::zero());
+        let mut swap_routes_iter = swap_routes.into_iter();
+        for payment in &payments {
+            let mut swap_operations = MultiValueEncoded::new();
+            if let Some(swap_route) = swap_routes_iter.next() {
+                for swap_operation in &swap_route {
+                    swap_operations.push(SwapOperationType::from(swap_operation));
+                }
+            }
+
+            let output_payment = self.process_payment_for_pair(
+                payment,
+                swap_operations,
+                pair_address,
+                route_discovery,
+            );
+            if output_payment.token_identifier == first_tokens.token_identifier {
+                first_tokens.amount += output_payment.amount;
+            } else if output_payment.token_identifier == second_tokens.token_identifier {
+                second_tokens.amount += output_payment.amount;
+            } else {
+                sc_panic!("The output token identifier is not part of the LP");
+            }
+        }
+
+        self.check_router_pair(
+            pair_address.clone(),
+            pair_config.first_token_id.clone(),
+            pair_config.second_token_id.clone(),
+        );
+
+        self.balance_pair_tokens(
+            pair_address,
+            &pair_config,
+            first_tokens,
+            second_tokens,
+            max_price_deviation_bps,
+        )
+    }
+
+    fn get_fungible_esdt_payments(&self) -> PaymentsVec<Self::Api> {
+        match self.call_value().any_payment() {
+            EgldOrMultiEsdtPayment::Egld(egld_amount) => {
+                require!(egld_amount > 0, "Invalid payments");
+                PaymentsVec::from_single_item(self.call_wrap_egld(egld_amount))
+            }
+            EgldOrMultiEsdtPayment::MultiEsdt(esdt_payments) => {
+                require!(!esdt_payments.is_empty(), "Invalid payments");
+                let egld_token_id = TokenIdentifier::from(EGLD_000000_TOKEN_ID);
+                for payment in &esdt_payments {
+                    require!(
+                        payment.token_identifier != egld_token_id,
+                        "EGLD must be sent alone"
+                    );
+                    require!(payment.token_nonce == 0, "Only fungible ESDT accepted");
+                }
+
+                esdt_payments
+            }
+        }
+    }
+
+    fn balance_pair_tokens(
+        &self,
+        pair_address: &ManagedAddress,
+        pair_config: &PairConfig<Self::Api>,
+        mut first_tokens: EsdtTokenPayment,
+        mut second_tokens: EsdtTokenPayment,
+        max_price_deviation_bps: u64,
+    ) -> PairTokenPayments<Self::Api> {
+        let first_reserve = self
+            .get_pair_reserve_mapper(pair_address.clone(), &pair_config.first_token_id)
+            .get();
+        let second_reserve = self
+            .get_pair_reserve_mapper(pair_address.clone(), &pair_config.second_token_id)
+            .get();
+        require!(first_reserve > 0 && second_reserve > 0, "Pair has no liquidity");
+
+        let first_value_in_second = &first_tokens.amount * &second_reserve;
+        let second_value_in_first = &second_tokens.amount * &first_reserve;
+        if first_value_in_second > second_value_in_first {
+            let matched_amount = &second_value_in_first / &second_reserve;
+            let excess_amount = &first_tokens.amount - &matched_amount;
+            let swap_output = self.swap_excess_pair_tokens(
+                pair_address,
+                &mut first_tokens,
+                excess_amount,
+                pair_config.second_token_id.clone(),
+                max_price_deviation_bps,
+            );
+            second_tokens.amount += swap_output.amount;
+        } else if second_value_in_first > first_value_in_second {
+            let matched_amount = &first_value_in_second / &first_reserve;
+            let excess_amount = &second_tokens.amount - &matched_amount;
+            let swap_output = self.swap_excess_pair_tokens(
+                pair_address,
+                &mut second_tokens,
+                excess_amount,
+                pair_config.first_token_id.clone(),
+                max_price_deviation_bps,
+            );
+            first_tokens.amount += swap_output.amount;
+        }
+
+        PairTokenPayments {
+            first_tokens,
+            second_tokens,
+        }
+    }
+
+    /// Swaps the optimal part of the excess amount to the other pair token.
+    /// The given payment's amount is reduced by the swapped amount.
+    fn swap_excess_pair_tokens(
+        &self,
+        pair_address: &ManagedAddress,
+        payment: &mut EsdtTokenPayment,
+        excess_amount: BigUint,
+        other_token_id: TokenIdentifier,
+        max_price_deviation_bps: u64,
+    ) -> EsdtTokenPayment {
+        let excess_payment =
+            EsdtTokenPayment::new(payment.token_identifier.clone(), 0, excess_amount);
+        let swap_amount = self.get_optimal_swap_amount(pair_address.clone(), &excess_payment);
+        if swap_amount == 0 {
+            return EsdtTokenPayment::new(other_token_id, 0, BigUint::zero());
+        }
+
+        payment.amount -= &swap_amount;
+        let swap_input_payment =
+            EsdtTokenPayment::new(payment.token_identifier.clone(), 0, swap_amount);
+
+        let output_tokens = self.call_pair_swap_with_price_check(
+            pair_address.clone(),
+            swap_input_payment.clone(),
+            other_token_id,
+            max_price_deviation_bps,
+            SwapLeg::ExcessBalancing,
+        );
+
+        let route = ManagedVec::from_single_item(pair_address.clone());
+        self.emit_swap_event(&route, &swap_input_payment, &output_tokens);
+
+        output_tokens
+    }
+}
@@ -5,14 +5,52 @@ use common_structs::PaymentsVec;
 
 use crate::{
     common::payments_wrapper::PaymentsWrapper,
+    configs::price_deviation_config::SwapLeg,
     external_sc_interactions::{
+        auto_farm_actions::PositionDestination,
         pair_actions::{PairAddLiqArgs, PairTokenPayments},
+        route_finder::RouteDiscovery,
         router_actions::SwapOperationType,
     },
 };
 
 pub type DoubleSwapResult<M> = PairTokenPayments<M>;
 
+/// Extra settings of the *WithOptions create endpoints.
+/// The endpoints without options use the default values.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy)]
+pub struct CreatePosOptions {
+    pub destination: PositionDestination,
+    /// Overrides the configured max price deviation of the target pair
+    /// and of the pairs in discovered routes.
+    /// Given swap operations are bounded by their own min amounts.
+    pub opt_max_price_deviation_bps: Option<u64>,
+    /// Discovers the swap routes on-chain when no swap operations are given
+    pub discover_routes: bool,
+}
+
+impl Default for CreatePosOptions {
+    fn default() -> Self {
+        CreatePosOptions {
+            destination: PositionDestination::Caller,
+            opt_max_price_deviation_bps: None,
+            discover_routes: false,
+        }
+    }
+}
+
+impl CreatePosOptions {
+    pub fn route_discovery(&self) -> RouteDiscovery {
+        if self.discover_routes {
+            RouteDiscovery::Enabled {
+                opt_max_price_deviation_bps: self.opt_max_price_deviation_bps,
+            }
+        } else {
+            RouteDiscovery::Disabled
+        }
+    }
+}
+
 pub struct CreateFarmPosArgs<M: ManagedTypeApi> {
     pub caller: ManagedAddress<M>,
     pub first_token_payment: EsdtTokenPayment<M>,
@@ -61,10 +99,11 @@ pub trait CreatePosModule:
         }
     }
 
-    fn swap_half_input_payment_if_needed(
+    fn swap_optimal_input_if_needed(
         &self,
         first_payment: &mut EsdtTokenPayment,
         pair_address: ManagedAddress,
+        max_price_deviation_bps: u64,
     ) -> EsdtTokenPayment {
         let pair_config = self.get_pair_config(&pair_address);
 
@@ -82,14 +121,17 @@ pub trait CreatePosModule:
             sc_panic!("The output token identifier is not part of the LP")
         };
 
-        let swap_input_payment = EsdtTokenPayment::new(
-            first_payment.token_identifier.clone(),
-            0,
-            &first_payment.amount / 2u64,
-        );
+        let swap_amount = self.get_optimal_swap_amount(pair_address.clone(), first_payment);
+        let swap_input_payment =
+            EsdtTokenPayment::new(first_payment.token_identifier.clone(), 0, swap_amount);
         first_payment.amount -= &swap_input_payment.amount;
-        let second_payment =
-            self.call_pair_swap(pair_address.clone(), swap_input_payment, other_token_id);
+        let second_payment = self.call_pair_swap_with_price_check(
+            pair_address.clone(),
+            swap_input_payment,
+            other_token_id,
+            max_price_deviation_bps,
+            SwapLeg::OptimalZap,
+        );
 
         self.check_router_pair(
             pair_address,
@@ -4,23 +4,33 @@ use common_structs::PaymentsVec;
 
 use crate::{
     common::payments_wrapper::PaymentsWrapper,
-    external_sc_interactions::{pair_actions::PairAddLiqArgs, router_actions::SwapOperationType},
+    events::PositionEventType,
+    external_sc_interactions::{
+        auto_farm_actions::PositionDestination, pair_actions::PairAddLiqArgs,
+        router_actions::SwapOperationType,
+    },
 };
 
-use super::create_pos::{CreateFarmPosArgs, CreateMetastakingPosArgs};
+use super::create_pos::{CreateFarmPosArgs, CreateMetastakingPosArgs, CreatePosOptions};
 
 #[multiversx_sc::module]
 pub trait CreatePosEndpointsModule:
     utils::UtilsModule
     + read_external_storage::ReadExternalStorageModule
     + crate::configs::pairs_config::PairsConfigModule
+    + crate::configs::contracts_whitelist::ContractsWhitelistModule
+    + crate::events::EventsModule
     + crate::external_sc_interactions::pair_actions::PairActionsModule
     + crate::external_sc_interactions::router_actions::RouterActionsModule
     + crate::external_sc_interactions::farm_actions::FarmActionsModule
     + crate::external_sc_interactions::farm_staking_actions::FarmStakingActionsModule
     + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
     + crate::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + crate::external_sc_interactions::route_finder::RouteFinderModule
+    + crate::external_sc_interactions::auto_farm_actions::AutoFarmActionsModule
     + super::create_pos::CreatePosModule
+    + crate::fees::FeesModule
+    + super::merge_pos::MergePosModule
 {
     #[payable("*")]
     #[endpoint(createLpPosFromSingleToken)]
@@ -31,23 +41,76 @@ pub trait CreatePosEndpointsModule:
         add_liq_second_token_min_amount_out: BigUint,
         swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
     ) -> PaymentsVec<Self::Api> {
+        self.create_lp_pos_from_single_token_with_options(
+            pair_address,
+            add_liq_first_token_min_amount_out,
+            add_liq_second_token_min_amount_out,
+            CreatePosOptions::default(),
+            swap_operations,
+        )
+    }
+
+    /// LP positions can only be sent to the caller
+    #[payable("*")]
+    #[endpoint(createLpPosFromSingleTokenWithOptions)]
+    fn create_lp_pos_from_single_token_with_options(
+        &self,
+        pair_address: ManagedAddress,
+        add_liq_first_token_min_amount_out: BigUint,
+        add_liq_second_token_min_amount_out: BigUint,
+        options: CreatePosOptions,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> PaymentsVec<Self::Api> {
+        require!(
+            options.destination == PositionDestination::Caller,
+            "LP positions can only be sent to the caller"
+        );
+
         let caller = self.blockchain().get_caller();
         let payment = self.call_value().egld_or_single_esdt();
 
         self.require_sc_address(&pair_address);
-
-        let mut first_token_payment = self.process_payment(payment, swap_operations);
-        let second_token_payment =
-            self.swap_half_input_payment_if_needed(&mut first_token_payment, pair_address.clone());
+        self.require_whitelisted_pair(&pair_address);
+
+        let esdt_payment = self.get_esdt_payment(payment);
+        let esdt_payment = self.deduct_protocol_fee(&caller, esdt_payment);
+        let mut first_token_payment = self.process_payment_for_pair(
+            esdt_payment,
+            swap_operations,
+            &pair_address,
+            options.route_discovery(),
+        );
+        let max_price_deviation_bps =
+            self.get_max_price_deviation(&pair_address, options.opt_max_price_deviation_bps);
+        let initial_payment = first_token_payment.clone();
+        let second_token_payment = self.swap_optimal_input_if_needed(
+            &mut first_token_payment,
+            pair_address.clone(),
+            max_price_deviation_bps,
+        );
+        self.emit_optimal_swap_event(
+            &pair_address,
+            &initial_payment,
+            &first_token_payment,
+            &second_token_payment,
+        );
 
         let args = PairAddLiqArgs {
-            pair_address,
+            pair_address: pair_address.clone(),
             first_tokens: first_token_payment,
             second_tokens: second_token_payment,
             first_token_min_amount_out: add_liq_first_token_min_amount_out,
             second_token_min_amount_out: add_liq_second_token_min_amount_out,
         };
         let (new_lp_tokens, mut output_payments) = self.create_lp_pos(args);
+        self.emit_create_pos_event(
+            &caller,
+            PositionEventType::Lp,
+            &pair_address,
+            &self.get_input_payments(),
+            &new_lp_tokens,
+            output_payments.get_payments(),
+        );
         output_payments.push(new_lp_tokens);
 
         output_payments.send_and_return(&caller)
@@ -63,17 +126,28 @@ pub trait CreatePosEndpointsModule:
     ) -> PaymentsVec<Self::Api> {
         let caller = self.blockchain().get_caller();
         self.require_sc_address(&pair_address);
+        self.require_whitelisted_pair(&pair_address);
 
         let [first_token_payment, second_token_payment] = self.call_value().multi_esdt();
+        let first_token_payment = self.deduct_protocol_fee(&caller, first_token_payment);
+        let second_token_payment = self.deduct_protocol_fee(&caller, second_token_payment);
 
         let args = PairAddLiqArgs {
-            pair_address,
+            pair_address: pair_address.clone(),
             first_tokens: first_token_payment,
             second_tokens: second_token_payment,
             first_token_min_amount_out: add_liq_first_token_min_amount_out,
             second_token_min_amount_out: add_liq_second_token_min_amount_out,
         };
         let (new_lp_tokens, mut output_payments) = self.create_lp_pos(args);
+        self.emit_create_pos_event(
+            &caller,
+            PositionEventType::Lp,
+            &pair_address,
+            &self.get_input_payments(),
+            &new_lp_tokens,
+            output_payments.get_payments(),
+        );
         output_payments.push(new_lp_tokens);
 
         output_payments.send_and_return(&caller)
@@ -87,18 +161,58 @@ pub trait CreatePosEndpointsModule:
         add_liq_first_token_min_amount_out: BigUint,
         add_liq_second_token_min_amount_out: BigUint,
         swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> PaymentsVec<Self::Api> {
+        self.create_farm_pos_from_single_token_with_options(
+            farm_address,
+            add_liq_first_token_min_amount_out,
+            add_liq_second_token_min_amount_out,
+            CreatePosOptions::default(),
+            swap_operations,
+        )
+    }
+
+    #[payable("*")]
+    #[endpoint(createFarmPosFromSingleTokenWithOptions)]
+    fn create_farm_pos_from_single_token_with_options(
+        &self,
+        farm_address: ManagedAddress,
+        add_liq_first_token_min_amount_out: BigUint,
+        add_liq_second_token_min_amount_out: BigUint,
+        options: CreatePosOptions,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
     ) -> PaymentsVec<Self::Api> {
         let caller = self.blockchain().get_caller();
         let (first_payment, additional_payments) = self.split_first_payment();
+        self.require_whitelisted_farm(&farm_address);
 
         let pair_address = self
             .get_farm_pair_contract_address_mapper(farm_address.clone())
             .get();
         self.require_sc_address(&pair_address);
-
-        let mut first_token_payment = self.process_payment(first_payment, swap_operations);
-        let second_token_payment =
-            self.swap_half_input_payment_if_needed(&mut first_token_payment, pair_address.clone());
+        self.require_merge_compatible_payments(&farm_address, &additional_payments);
+
+        let esdt_payment = self.get_esdt_payment(first_payment);
+        let esdt_payment = self.deduct_protocol_fee(&caller, esdt_payment);
+        let mut first_token_payment = self.process_payment_for_pair(
+            esdt_payment,
+            swap_operations,
+            &pair_address,
+            options.route_discovery(),
+        );
+        let max_price_deviation_bps =
+            self.get_max_price_deviation(&pair_address, options.opt_max_price_deviation_bps);
+        let initial_payment = first_token_payment.clone();
+        let second_token_payment = self.swap_optimal_input_if_needed(
+            &mut first_token_payment,
+            pair_address.clone(),
+            max_price_deviation_bps,
+        );
+        self.emit_optimal_swap_event(
+            &pair_address,
+            &initial_payment,
+            &first_token_payment,
+            &second_token_payment,
+        );
 
         let args = CreateFarmPosArgs {
             caller: caller.clone(),
@@ -108,12 +222,24 @@ pub trait CreatePosEndpointsModule:
             add_liq_first_token_min_amount_out,
             add_liq_second_token_min_amount_out,
             pair_address,
-            farm_address,
+            farm_address: farm_address.clone(),
         };
-        let (new_farm_tokens, mut output_payments) = self.create_farm_pos(args);
-        output_payments.push(new_farm_tokens);
+        let (new_farm_tokens, output_payments) = self.create_farm_pos(args);
+        self.emit_create_pos_event(
+            &caller,
+            PositionEventType::Farm,
+            &farm_address,
+            &self.get_input_payments(),
+            &new_farm_tokens,
+            output_payments.get_payments(),
+        );
 
-        output_payments.send_and_return(&caller)
+        self.send_new_position(
+            &caller,
+            new_farm_tokens,
+            output_payments,
+            options.destination,
+        )
     }
 
     #[payable("*")]
@@ -125,6 +251,7 @@ pub trait CreatePosEndpointsModule:
         add_liq_second_token_min_amount_out: BigUint,
     ) -> PaymentsVec<Self::Api> {
         let caller = self.blockchain().get_caller();
+        self.require_whitelisted_farm(&farm_address);
 
         let pair_address = self
             .get_farm_pair_contract_address_mapper(farm_address.clone())
@@ -133,6 +260,9 @@ pub trait CreatePosEndpointsModule:
 
         let (first_token_payment, second_token_payment, additional_payments) =
             self.split_first_two_payments();
+        self.require_merge_compatible_payments(&farm_address, &additional_payments);
+        let first_token_payment = self.deduct_protocol_fee(&caller, first_token_payment);
+        let second_token_payment = self.deduct_protocol_fee(&caller, second_token_payment);
 
         let args = CreateFarmPosArgs {
             caller: caller.clone(),
@@ -142,9 +272,17 @@ pub trait CreatePosEndpointsModule:
             add_liq_first_token_min_amount_out,
             add_liq_second_token_min_amount_out,
             pair_address,
-            farm_address,
+            farm_address: farm_address.clone(),
         };
         let (new_farm_tokens, mut output_payments) = self.create_farm_pos(args);
+        self.emit_create_pos_event(
+            &caller,
+            PositionEventType::Farm,
+            &farm_address,
+            &self.get_input_payments(),
+            &new_farm_tokens,
+            output_payments.get_payments(),
+        );
         output_payments.push(new_farm_tokens);
 
         output_payments.send_and_return(&caller)
@@ -158,9 +296,29 @@ pub trait CreatePosEndpointsModule:
         add_liq_first_token_min_amount_out: BigUint,
         add_liq_second_token_min_amount_out: BigUint,
         swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> PaymentsVec<Self::Api> {
+        self.create_metastaking_pos_from_single_token_with_options(
+            metastaking_address,
+            add_liq_first_token_min_amount_out,
+            add_liq_second_token_min_amount_out,
+            CreatePosOptions::default(),
+            swap_operations,
+        )
+    }
+
+    #[payable("*")]
+    #[endpoint(createMetastakingPosFromSingleTokenWithOptions)]
+    fn create_metastaking_pos_from_single_token_with_options(
+        &self,
+        metastaking_address: ManagedAddress,
+        add_liq_first_token_min_amount_out: BigUint,
+        add_liq_second_token_min_amount_out: BigUint,
+        options: CreatePosOptions,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
     ) -> PaymentsVec<Self::Api> {
         let caller = self.blockchain().get_caller();
         let (first_payment, additional_payments) = self.split_first_payment();
+        self.require_whitelisted_metastaking(&metastaking_address);
 
         let farm_address = self
             .get_lp_farm_address_mapper(metastaking_address.clone())
@@ -169,10 +327,30 @@ pub trait CreatePosEndpointsModule:
             .get_farm_pair_contract_address_mapper(farm_address.clone())
             .get();
         self.require_sc_address(&pair_address);
-
-        let mut first_token_payment = self.process_payment(first_payment, swap_operations);
-        let second_token_payment =
-            self.swap_half_input_payment_if_needed(&mut first_token_payment, pair_address.clone());
+        self.require_merge_compatible_payments(&metastaking_address, &additional_payments);
+
+        let esdt_payment = self.get_esdt_payment(first_payment);
+        let esdt_payment = self.deduct_protocol_fee(&caller, esdt_payment);
+        let mut first_token_payment = self.process_payment_for_pair(
+            esdt_payment,
+            swap_operations,
+            &pair_address,
+            options.route_discovery(),
+        );
+        let max_price_deviation_bps =
+            self.get_max_price_deviation(&pair_address, options.opt_max_price_deviation_bps);
+        let initial_payment = first_token_payment.clone();
+        let second_token_payment = self.swap_optimal_input_if_needed(
+            &mut first_token_payment,
+            pair_address.clone(),
+            max_price_deviation_bps,
+        );
+        self.emit_optimal_swap_event(
+            &pair_address,
+            &initial_payment,
+            &first_token_payment,
+            &second_token_payment,
+        );
 
         let args = CreateMetastakingPosArgs {
             caller: caller.clone(),
@@ -183,12 +361,24 @@ pub trait CreatePosEndpointsModule:
             add_liq_second_token_min_amount_out,
             pair_address,
             farm_address,
-            metastaking_address,
+            metastaking_address: metastaking_address.clone(),
         };
-        let (new_metastaking_tokens, mut output_payments) = self.create_metastaking_pos(args);
-        output_payments.push(new_metastaking_tokens);
+        let (new_metastaking_tokens, output_payments) = self.create_metastaking_pos(args);
+        self.emit_create_pos_event(
+            &caller,
+            PositionEventType::Metastaking,
+            &metastaking_address,
+            &self.get_input_payments(),
+            &new_metastaking_tokens,
+            output_payments.get_payments(),
+        );
 
-        output_payments.send_and_return(&caller)
+        self.send_new_position(
+            &caller,
+            new_metastaking_tokens,
+            output_payments,
+            options.destination,
+        )
     }
 
     #[payable("*")]
@@ -200,6 +390,7 @@ pub trait CreatePosEndpointsModule:
         add_liq_second_token_min_amount_out: BigUint,
     ) -> PaymentsVec<Self::Api> {
         let caller = self.blockchain().get_caller();
+        self.require_whitelisted_metastaking(&metastaking_address);
 
         let farm_address = self
             .get_lp_farm_address_mapper(metastaking_address.clone())
@@ -211,6 +402,9 @@ pub trait CreatePosEndpointsModule:
 
         let (first_token_payment, second_token_payment, additional_payments) =
             self.split_first_two_payments();
+        self.require_merge_compatible_payments(&metastaking_address, &additional_payments);
+        let first_token_payment = self.deduct_protocol_fee(&caller, first_token_payment);
+        let second_token_payment = self.deduct_protocol_fee(&caller, second_token_payment);
 
         let args = CreateMetastakingPosArgs {
             caller: caller.clone(),
@@ -221,9 +415,17 @@ pub trait CreatePosEndpointsModule:
             add_liq_second_token_min_amount_out,
             pair_address,
             farm_address,
-            metastaking_address,
+            metastaking_address: metastaking_address.clone(),
         };
         let (new_metastaking_tokens, mut output_payments) = self.create_metastaking_pos(args);
+        self.emit_create_pos_event(
+            &caller,
+            PositionEventType::Metastaking,
+            &metastaking_address,
+            &self.get_input_payments(),
+            &new_metastaking_tokens,
+            output_payments.get_payments(),
+        );
         output_payments.push(new_metastaking_tokens);
 
         output_payments.send_and_return(&caller)
@@ -237,12 +439,44 @@ pub trait CreatePosEndpointsModule:
         min_amount_out: BigUint,
         swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
     ) -> PaymentsVec<Self::Api> {
+        self.create_farm_staking_pos_from_single_token_with_options(
+            farm_staking_address,
+            min_amount_out,
+            CreatePosOptions::default(),
+            swap_operations,
+        )
+    }
+
+    /// Farm staking positions can only be sent to the caller
+    #[payable("*")]
+    #[endpoint(createFarmStakingPosFromSingleTokenWithOptions)]
+    fn create_farm_staking_pos_from_single_token_with_options(
+        &self,
+        farm_staking_address: ManagedAddress,
+        min_amount_out: BigUint,
+        options: CreatePosOptions,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> PaymentsVec<Self::Api> {
+        require!(
+            options.destination == PositionDestination::Caller,
+            "Farm staking positions can only be sent to the caller"
+        );
+
         let caller = self.blockchain().get_caller();
+        self.require_whitelisted_farm_staking(&farm_staking_address);
 
         let (first_payment, additional_payments) = self.split_first_payment();
+        self.require_merge_compatible_payments(&farm_staking_address, &additional_payments);
 
-        let token_payment = self.process_payment(first_payment, swap_operations);
         let farming_token_id = self.get_farm_staking_farming_token_id(farm_staking_address.clone());
+        let esdt_payment = self.get_esdt_payment(first_payment);
+        let esdt_payment = self.deduct_protocol_fee(&caller, esdt_payment);
+        let token_payment = self.process_payment_for_token(
+            esdt_payment,
+            swap_operations,
+            &farming_token_id,
+            options.route_discovery(),
+        );
 
         require!(
             token_payment.token_identifier == farming_token_id,
@@ -253,13 +487,21 @@ pub trait CreatePosEndpointsModule:
         token_payments.append_vec(additional_payments);
 
         let (new_farm_token, boosted_rewards_payment) = self
-            .call_farm_staking_stake(farm_staking_address, caller.clone(), token_payments)
+            .call_farm_staking_stake(farm_staking_address.clone(), caller.clone(), token_payments)
             .into_tuple();
 
         require!(new_farm_token.amount >= min_amount_out, "Slippage exceeded");
 
         let mut output_payments = PaymentsWrapper::new();
         output_payments.push(boosted_rewards_payment);
+        self.emit_create_pos_event(
+            &caller,
+            PositionEventType::FarmStaking,
+            &farm_staking_address,
+            &self.get_input_payments(),
+            &new_farm_token,
+            output_payments.get_payments(),
+        );
         output_payments.push(new_farm_token);
 
         output_payments.send_and_return(&caller)
@@ -34,16 +34,18 @@ pub trait ExitPosModule:
     + crate::external_sc_interactions::farm_actions::FarmActionsModule
     + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
 {
+    /// Returns the farm staking unbond tokens
     fn unstake_metastaking(
         &self,
         output_payments: &mut PaymentsWrapper<Self::Api>,
         args: MetastakingExitArgs<Self::Api>,
-    ) {
+    ) -> EsdtTokenPayment {
         let unstake_result = self.call_metastaking_unstake(args);
         output_payments.push(unstake_result.other_token_payment);
         output_payments.push(unstake_result.lp_farm_rewards);
         output_payments.push(unstake_result.staking_rewards);
-        output_payments.push(unstake_result.unbond_staking_farm_token);
+
+        unstake_result.unbond_staking_farm_token
     }
 
     fn exit_farm(
@@ -2,7 +2,7 @@ multiversx_sc::imports!();
 
 use common_structs::PaymentsVec;
 
-use crate::common::payments_wrapper::PaymentsWrapper;
+use crate::{common::payments_wrapper::PaymentsWrapper, events::PositionEventType};
 
 use super::exit_pos::{FarmExitArgs, MetastakingExitArgs, RemoveLiqArgs};
 
@@ -11,11 +11,21 @@ pub trait ExitPosEndpointsModule:
     utils::UtilsModule
     + read_external_storage::ReadExternalStorageModule
     + crate::configs::pairs_config::PairsConfigModule
+    + crate::configs::contracts_whitelist::ContractsWhitelistModule
+    + crate::events::EventsModule
     + crate::external_sc_interactions::pair_actions::PairActionsModule
+    + crate::external_sc_interactions::router_actions::RouterActionsModule
     + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::farm_staking_actions::FarmStakingActionsModule
     + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + crate::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + crate::external_sc_interactions::route_finder::RouteFinderModule
     + super::exit_pos::ExitPosModule
+    + super::exit_to_single_token::ExitToSingleTokenModule
+    + super::pending_unbonds::PendingUnbondsModule
 {
+    /// opt_hold_unbond_tokens: if true, the contract keeps the farm staking unbond tokens,
+    /// which the user can later unbond through claimUnbondedTokens
     #[payable("*")]
     #[endpoint(exitMetastakingPos)]
     fn exit_metastaking_pos_endpoint(
@@ -23,22 +33,43 @@ pub trait ExitPosEndpointsModule:
         metastaking_address: ManagedAddress,
         first_token_min_amount_out: BigUint,
         second_token_min_amont_out: BigUint,
+        opt_hold_unbond_tokens: OptionalValue<bool>,
     ) -> PaymentsVec<Self::Api> {
+        self.require_whitelisted_metastaking(&metastaking_address);
+
         let caller = self.blockchain().get_caller();
-        let payment = self.call_value().single_esdt();
+        let position = self.call_value().single_esdt();
         let mut output_payments = PaymentsWrapper::new();
 
         let args = MetastakingExitArgs {
-            ms_address: metastaking_address,
+            ms_address: metastaking_address.clone(),
             user: caller.clone(),
-            ms_tokens: payment,
+            ms_tokens: position.clone(),
             first_token_min_amount_out,
             second_token_min_amont_out,
         };
 
-        self.unstake_metastaking(&mut output_payments, args);
+        let unbond_tokens = self.unstake_metastaking(&mut output_payments, args);
+        let hold_unbond_tokens = opt_hold_unbond_tokens.into_option().unwrap_or_default();
+        if hold_unbond_tokens {
+            let farm_staking_address = self
+                .get_staking_farm_address_mapper(metastaking_address.clone())
+                .get();
+            self.add_pending_unbond(&caller, farm_staking_address, unbond_tokens);
+        } else {
+            output_payments.push(unbond_tokens);
+        }
+
+        let output_payments = output_payments.send_and_return(&caller);
+        self.emit_exit_pos_event(
+            &caller,
+            PositionEventType::Metastaking,
+            &metastaking_address,
+            &position,
+            &self.to_egld_or_esdt_payments(&output_payments),
+        );
 
-        output_payments.send_and_return(&caller)
+        output_payments
     }
 
     #[payable("*")]
@@ -49,21 +80,32 @@ pub trait ExitPosEndpointsModule:
         first_token_min_amount_out: BigUint,
         second_token_min_amont_out: BigUint,
     ) -> PaymentsVec<Self::Api> {
+        self.require_whitelisted_farm(&farm_address);
+
         let caller = self.blockchain().get_caller();
-        let payment = self.call_value().single_esdt();
+        let position = self.call_value().single_esdt();
         let mut output_payments = PaymentsWrapper::new();
 
         let args = FarmExitArgs {
-            farm_address,
+            farm_address: farm_address.clone(),
             user: caller.clone(),
-            farm_tokens: payment,
+            farm_tokens: position.clone(),
             first_token_min_amount_out,
             second_token_min_amont_out,
         };
 
         self.exit_farm(&mut output_payments, args);
 
-        output_payments.send_and_return(&caller)
+        let output_payments = output_payments.send_and_return(&caller);
+        self.emit_exit_pos_event(
+            &caller,
+            PositionEventType::Farm,
+            &farm_address,
+            &position,
+            &self.to_egld_or_esdt_payments(&output_payments),
+        );
+
+        output_payments
     }
 
     #[payable("*")]
@@ -74,19 +116,30 @@ pub trait ExitPosEndpointsModule:
         first_token_min_amount_out: BigUint,
         second_token_min_amont_out: BigUint,
     ) -> PaymentsVec<Self::Api> {
+        self.require_whitelisted_pair(&pair_address);
+
         let caller = self.blockchain().get_caller();
-        let payment = self.call_value().single_esdt();
+        let position = self.call_value().single_esdt();
         let mut output_payments = PaymentsWrapper::new();
 
         let args = RemoveLiqArgs {
-            pair_address,
-            lp_tokens: payment,
+            pair_address: pair_address.clone(),
+            lp_tokens: position.clone(),
             first_token_min_amount_out,
             second_token_min_amont_out,
         };
 
         self.remove_pair_liq(&mut output_payments, args);
 
-        output_payments.send_and_return(&caller)
+        let output_payments = output_payments.send_and_return(&caller);
+        self.emit_exit_pos_event(
+            &caller,
+            PositionEventType::Lp,
+            &pair_address,
+            &position,
+            &self.to_egld_or_esdt_payments(&output_payments),
+        );
+
+        output_payments
     }
 }
@@ -0,0 +1,227 @@
+multiversx_sc::imports!();
+
+use common_structs::PaymentsVec;
+
+use crate::{
+    common::payments_wrapper::PaymentsWrapper, events::PositionEventType,
+    external_sc_interactions::route_finder::RouteDiscovery,
+};
+
+use super::exit_pos::MetastakingExitArgs;
+
+pub const REMOVE_LIQ_MIN_AMOUNT: u64 = 1;
+
+pub type ExitToSingleTokenResultType<M> = MultiValue2<EgldOrEsdtTokenPayment<M>, PaymentsVec<M>>;
+
+#[multiversx_sc::module]
+pub trait ExitToSingleTokenModule:
+    utils::UtilsModule
+    + read_external_storage::ReadExternalStorageModule
+    + crate::configs::pairs_config::PairsConfigModule
+    + crate::configs::contracts_whitelist::ContractsWhitelistModule
+    + crate::events::EventsModule
+    + crate::external_sc_interactions::pair_actions::PairActionsModule
+    + crate::external_sc_interactions::router_actions::RouterActionsModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + crate::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + crate::external_sc_interactions::route_finder::RouteFinderModule
+{
+    /// Removes liquidity and swaps both pair tokens to the output token.
+    /// The output token can also be EGLD, in which case the swapped WEGLD is unwrapped.
+    /// Returns the output tokens and the other payments, e.g. rewards.
+    #[payable("*")]
+    #[endpoint(exitLpPosToSingleToken)]
+    fn exit_lp_pos_to_single_token(
+        &self,
+        pair_address: ManagedAddress,
+        output_token_id: EgldOrEsdtTokenIdentifier,
+        min_amount_out: BigUint,
+    ) -> ExitToSingleTokenResultType<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        let position = self.call_value().single_esdt();
+        self.require_sc_address(&pair_address);
+        self.require_whitelisted_pair(&pair_address);
+
+        let pair_tokens =
+            self.remove_liquidity_to_pair_tokens(pair_address.clone(), position.clone());
+        let output_tokens =
+            self.swap_to_single_token(pair_tokens, &output_token_id, &min_amount_out);
+
+        self.send_exit_to_single_token_result(
+            &caller,
+            (PositionEventType::Lp, &pair_address, &position),
+            output_tokens,
+            PaymentsWrapper::new(),
+        )
+    }
+
+    #[payable("*")]
+    #[endpoint(exitFarmPosToSingleToken)]
+    fn exit_farm_pos_to_single_token(
+        &self,
+        farm_address: ManagedAddress,
+        output_token_id: EgldOrEsdtTokenIdentifier,
+        min_amount_out: BigUint,
+    ) -> ExitToSingleTokenResultType<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        let position = self.call_value().single_esdt();
+        self.require_whitelisted_farm(&farm_address);
+
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(farm_address.clone())
+            .get();
+        self.require_sc_address(&pair_address);
+
+        let mut other_payments = PaymentsWrapper::new();
+        let exit_farm_result =
+            self.call_exit_farm(farm_address.clone(), caller.clone(), position.clone());
+        other_payments.push(exit_farm_result.rewards);
+
+        let pair_tokens =
+            self.remove_liquidity_to_pair_tokens(pair_address, exit_farm_result.farming_tokens);
+        let output_tokens =
+            self.swap_to_single_token(pair_tokens, &output_token_id, &min_amount_out);
+
+        self.send_exit_to_single_token_result(
+            &caller,
+            (PositionEventType::Farm, &farm_address, &position),
+            output_tokens,
+            other_payments,
+        )
+    }
+
+    /// Only the pair token that is not staked can be swapped.
+    /// The unbond tokens are returned along with the rewards.
+    #[payable("*")]
+    #[endpoint(exitMetastakingPosToSingleToken)]
+    fn exit_metastaking_pos_to_single_token(
+        &self,
+        metastaking_address: ManagedAddress,
+        output_token_id: EgldOrEsdtTokenIdentifier,
+        min_amount_out: BigUint,
+    ) -> ExitToSingleTokenResultType<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        let position = self.call_value().single_esdt();
+        self.require_whitelisted_metastaking(&metastaking_address);
+
+        let args = MetastakingExitArgs {
+            ms_address: metastaking_address.clone(),
+            user: caller.clone(),
+            ms_tokens: position.clone(),
+            first_token_min_amount_out: BigUint::from(REMOVE_LIQ_MIN_AMOUNT),
+            second_token_min_amont_out: BigUint::from(REMOVE_LIQ_MIN_AMOUNT),
+        };
+        let unstake_result = self.call_metastaking_unstake(args);
+
+        let mut other_payments = PaymentsWrapper::new();
+        other_payments.push(unstake_result.lp_farm_rewards);
+        other_payments.push(unstake_result.staking_rewards);
+        other_payments.push(unstake_result.unbond_staking_farm_token);
+
+        let swap_input = PaymentsVec::from_single_item(unstake_result.other_token_payment);
+        let output_tokens =
+            self.swap_to_single_token(swap_input, &output_token_id, &min_amount_out);
+
+        self.send_exit_to_single_token_result(
+            &caller,
+            (PositionEventType::Metastaking, &metastaking_address, &position),
+            output_tokens,
+            other_payments,
+        )
+    }
+
+    fn remove_liquidity_to_pair_tokens(
+        &self,
+        pair_address: ManagedAddress,
+        lp_tokens: EsdtTokenPayment,
+    ) -> PaymentsVec<Self::Api> {
+        let remove_liq_result = self.call_pair_remove_liquidity(
+            pair_address,
+            lp_tokens,
+            BigUint::from(REMOVE_LIQ_MIN_AMOUNT),
+            BigUint::from(REMOVE_LIQ_MIN_AMOUNT),
+        );
+
+        let mut pair_tokens = PaymentsVec::from_single_item(remove_liq_result.first_tokens);
+        pair_tokens.push(remove_liq_result.second_tokens);
+
+        pair_tokens
+    }
+
+    /// There are no swap route arguments, so the routes are always discovered.
+    /// Each discovered hop is checked against its pair's configured max price deviation.
+    fn swap_to_single_token(
+        &self,
+        input_payments: PaymentsVec<Self::Api>,
+        output_token_id: &EgldOrEsdtTokenIdentifier,
+        min_amount_out: &BigUint,
+    ) -> EgldOrEsdtTokenPayment {
+        let wanted_token_id = self.get_wrapped_token_id(output_token_id);
+        let route_discovery = RouteDiscovery::Enabled {
+            opt_max_price_deviation_bps: None,
+        };
+
+        let mut total_amount = BigUint::zero();
+        for payment in &input_payments {
+            if payment.amount == 0 {
+                continue;
+            }
+
+            let output_payment = self.process_payment_for_token(
+                payment,
+                MultiValueEncoded::new(),
+                &wanted_token_id,
+                route_discovery,
+            );
+            require!(
+                output_payment.token_identifier == wanted_token_id,
+                "No swap route found to the output token"
+            );
+
+            total_amount += output_payment.amount;
+        }
+
+        require!(&total_amount >= min_amount_out, "Slippage exceeded");
+
+        if output_token_id.is_egld() {
+            let wrapped_egld_payment = EsdtTokenPayment::new(wanted_token_id, 0, total_amount);
+            let egld_amount = self.call_unwrap_egld(wrapped_egld_payment);
+
+            return EgldOrEsdtTokenPayment::new(EgldOrEsdtTokenIdentifier::egld(), 0, egld_amount);
+        }
+
+        EgldOrEsdtTokenPayment::new(output_token_id.clone(), 0, total_amount)
+    }
+
+    /// exited_position: the position type, the contract address and the position tokens
+    fn send_exit_to_single_token_result(
+        &self,
+        caller: &ManagedAddress,
+        exited_position: (PositionEventType, &ManagedAddress, &EsdtTokenPayment),
+        output_tokens: EgldOrEsdtTokenPayment,
+        other_payments: PaymentsWrapper<Self::Api>,
+    ) -> ExitToSingleTokenResultType<Self::Api> {
+        self.send().direct_non_zero(
+            caller,
+            &output_tokens.token_identifier,
+            0,
+            &output_tokens.amount,
+        );
+        let other_payments = other_payments.send_and_return(caller);
+
+        let mut event_output_payments = ManagedVec::from_single_item(output_tokens.clone());
+        event_output_payments.append_vec(self.to_egld_or_esdt_payments(&other_payments));
+
+        let (position_type, sc_address, position) = exited_position;
+        self.emit_exit_pos_event(
+            caller,
+            position_type,
+            sc_address,
+            position,
+            &event_output_payments,
+        );
+
+        (output_tokens, other_payments).into()
+    }
+}
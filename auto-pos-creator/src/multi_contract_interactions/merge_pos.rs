@@ -0,0 +1,95 @@
+multiversx_sc::imports!();
+
+use common_structs::PaymentsVec;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PositionType {
+    Farm,
+    Metastaking,
+    FarmStaking,
+}
+
+#[multiversx_sc::module]
+pub trait MergePosModule: utils::UtilsModule + read_external_storage::ReadExternalStorageModule {
+    /// Filters the given tokens, usually the user's account tokens,
+    /// keeping only the ones that can be sent as additional payments
+    /// when creating a position in the target farm, metastaking or farm staking contract
+    #[view(getMergeCompatibleTokens)]
+    fn get_merge_compatible_tokens(
+        &self,
+        target_address: ManagedAddress,
+        user_tokens: MultiValueEncoded<EsdtTokenPayment>,
+    ) -> MultiValueEncoded<EsdtTokenPayment> {
+        let position_token_id = self.get_position_token_id(&target_address);
+
+        let mut compatible_tokens = MultiValueEncoded::new();
+        for user_token in user_tokens {
+            if self.is_merge_compatible(&position_token_id, &user_token) {
+                compatible_tokens.push(user_token);
+            }
+        }
+
+        compatible_tokens
+    }
+
+    /// The additional payments are merged into the new position by the target contract,
+    /// so they must be existing positions of that same contract
+    fn require_merge_compatible_payments(
+        &self,
+        target_address: &ManagedAddress,
+        additional_payments: &PaymentsVec<Self::Api>,
+    ) {
+        if additional_payments.is_empty() {
+            return;
+        }
+
+        let position_token_id = self.get_position_token_id(target_address);
+        for payment in additional_payments {
+            require!(
+                self.is_merge_compatible(&position_token_id, &payment),
+                "Additional payments must be positions of the target contract"
+            );
+        }
+    }
+
+    fn is_merge_compatible(
+        &self,
+        position_token_id: &TokenIdentifier,
+        payment: &EsdtTokenPayment,
+    ) -> bool {
+        &payment.token_identifier == position_token_id
+            && payment.token_nonce > 0
+            && payment.amount > 0
+    }
+
+    fn get_position_token_id(&self, sc_address: &ManagedAddress) -> TokenIdentifier {
+        match self.get_position_type(sc_address) {
+            PositionType::Metastaking => self
+                .get_dual_yield_token_id_mapper(sc_address.clone())
+                .get(),
+            PositionType::Farm | PositionType::FarmStaking => {
+                self.get_farm_token_id_mapper(sc_address.clone()).get()
+            }
+        }
+    }
+
+    fn get_position_type(&self, sc_address: &ManagedAddress) -> PositionType {
+        self.require_sc_address(sc_address);
+
+        if !self
+            .get_dual_yield_token_id_mapper(sc_address.clone())
+            .is_empty()
+        {
+            PositionType::Metastaking
+        } else if !self
+            .get_farm_pair_contract_address_mapper(sc_address.clone())
+            .is_empty()
+        {
+            PositionType::Farm
+        } else if !self.get_farming_token_id_mapper(sc_address.clone()).is_empty() {
+            PositionType::FarmStaking
+        } else {
+            sc_panic!("Unsupported position contract")
+        }
+    }
+}
@@ -0,0 +1,358 @@
+multiversx_sc::imports!();
+
+use common_structs::PaymentsVec;
+
+use crate::{
+    common::payments_wrapper::PaymentsWrapper, events::PositionEventType,
+    external_sc_interactions::route_finder::RouteDiscovery,
+};
+
+use super::{
+    create_pos::{CreateFarmPosArgs, CreateMetastakingPosArgs},
+    exit_pos::MetastakingExitArgs,
+    merge_pos::PositionType,
+};
+
+pub struct MigrationExitArgs<M: ManagedTypeApi> {
+    pub source_address: ManagedAddress<M>,
+    pub payment: EsdtTokenPayment<M>,
+    pub first_token_min_amount_out: BigUint<M>,
+    pub second_token_min_amount_out: BigUint<M>,
+}
+
+pub struct MigrationEnterArgs<M: ManagedTypeApi> {
+    pub destination_address: ManagedAddress<M>,
+    pub tokens: PaymentsVec<M>,
+    pub add_liq_first_token_min_amount_out: BigUint<M>,
+    pub add_liq_second_token_min_amount_out: BigUint<M>,
+    pub opt_max_price_deviation_bps: Option<u64>,
+}
+
+#[multiversx_sc::module]
+pub trait MigratePosModule:
+    utils::UtilsModule
+    + read_external_storage::ReadExternalStorageModule
+    + crate::configs::pairs_config::PairsConfigModule
+    + crate::configs::contracts_whitelist::ContractsWhitelistModule
+    + crate::external_sc_interactions::pair_actions::PairActionsModule
+    + crate::external_sc_interactions::router_actions::RouterActionsModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::farm_staking_actions::FarmStakingActionsModule
+    + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + crate::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + crate::external_sc_interactions::route_finder::RouteFinderModule
+    + crate::events::EventsModule
+    + super::create_pos::CreatePosModule
+    + super::create_multi_token_pos::CreateMultiTokenPosModule
+    + crate::fees::FeesModule
+    + super::exit_to_single_token::ExitToSingleTokenModule
+    + super::merge_pos::MergePosModule
+    + super::pending_unbonds::PendingUnbondsModule
+{
+    /// Exits the LP, farm or metastaking position received as payment,
+    /// and uses the resulting tokens to enter the destination farm, metastaking or farm staking.
+    /// Farm staking positions can't be migrated, since their tokens are only released
+    /// after the unbond period.
+    /// For metastaking positions, only the pair token that is not staked enters the new position.
+    /// The farm staking unbond tokens of the staked one are held by the contract,
+    /// and the user can unbond them through claimUnbondedTokens.
+    /// There are no swap route arguments, so the swap routes are always discovered on-chain.
+    /// source_address: the pair, farm or metastaking contract of the position
+    /// exit_first_token_min_amount_out, exit_second_token_min_amount_out: the min amounts
+    ///     of the pair tokens received when removing the liquidity of the source position
+    /// add_liq_first_token_min_amount_out, add_liq_second_token_min_amount_out: the min amounts
+    ///     of the pair tokens used when adding liquidity for the destination position
+    /// min_amount_out: the minimum amount of new position tokens
+    /// opt_max_price_deviation_bps: overrides the max price deviation of the destination pair
+    ///     and of the pairs in the discovered routes.
+    ///     Routes to farm staking tokens use the configured values.
+    /// The protocol fee is taken from the tokens received from exiting the old position.
+    /// Emits the exitPos event of the old position, then the createPos event of the new one.
+    /// Returns the rewards, the leftovers and the new position
+    #[payable("*")]
+    #[endpoint(migratePosition)]
+    fn migrate_position(
+        &self,
+        source_address: ManagedAddress,
+        destination_address: ManagedAddress,
+        exit_first_token_min_amount_out: BigUint,
+        exit_second_token_min_amount_out: BigUint,
+        add_liq_first_token_min_amount_out: BigUint,
+        add_liq_second_token_min_amount_out: BigUint,
+        min_amount_out: BigUint,
+        opt_max_price_deviation_bps: OptionalValue<u64>,
+    ) -> PaymentsVec<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        let payment = self.call_value().single_esdt();
+        require!(
+            source_address != destination_address,
+            "Cannot migrate to the same contract"
+        );
+        self.require_whitelisted_position_sc(&destination_address);
+
+        let mut output_payments = PaymentsWrapper::new();
+        let exit_args = MigrationExitArgs {
+            source_address: source_address.clone(),
+            payment: payment.clone(),
+            first_token_min_amount_out: exit_first_token_min_amount_out,
+            second_token_min_amount_out: exit_second_token_min_amount_out,
+        };
+        let (source_position_type, exited_tokens) =
+            self.exit_pos_for_migration(&caller, exit_args, &mut output_payments);
+
+        let mut exit_output_payments = output_payments.get_payments().clone();
+        exit_output_payments.append_vec(exited_tokens.clone());
+        self.emit_exit_pos_event(
+            &caller,
+            source_position_type,
+            &source_address,
+            &payment,
+            &self.to_egld_or_esdt_payments(&exit_output_payments),
+        );
+
+        let enter_tokens = self.deduct_protocol_fees(&caller, exited_tokens);
+        let enter_input_payments = self.to_egld_or_esdt_payments(&enter_tokens);
+        let mut enter_output_payments = PaymentsWrapper::new();
+        let enter_args = MigrationEnterArgs {
+            destination_address: destination_address.clone(),
+            tokens: enter_tokens,
+            add_liq_first_token_min_amount_out,
+            add_liq_second_token_min_amount_out,
+            opt_max_price_deviation_bps: opt_max_price_deviation_bps.into_option(),
+        };
+        let (destination_position_type, new_position) =
+            self.enter_pos_for_migration(&caller, enter_args, &mut enter_output_payments);
+        require!(new_position.amount >= min_amount_out, "Slippage exceeded");
+
+        self.emit_create_pos_event(
+            &caller,
+            destination_position_type,
+            &destination_address,
+            &enter_input_payments,
+            &new_position,
+            enter_output_payments.get_payments(),
+        );
+
+        output_payments.append(enter_output_payments);
+        output_payments.push(new_position);
+
+        output_payments.send_and_return(&caller)
+    }
+
+    /// Returns the type of the old position and the tokens that can be used
+    /// to enter the new position
+    fn exit_pos_for_migration(
+        &self,
+        caller: &ManagedAddress,
+        args: MigrationExitArgs<Self::Api>,
+        output_payments: &mut PaymentsWrapper<Self::Api>,
+    ) -> (PositionEventType, PaymentsVec<Self::Api>) {
+        let source_address = args.source_address;
+        let payment = args.payment;
+        if !self
+            .get_lp_token_id_mapper(source_address.clone())
+            .is_empty()
+        {
+            self.require_whitelisted_pair(&source_address);
+
+            let lp_token_id = self.get_lp_token_id_mapper(source_address.clone()).get();
+            require!(
+                payment.token_identifier == lp_token_id,
+                "Invalid position token"
+            );
+
+            let pair_tokens = self.remove_liquidity_for_migration(
+                source_address,
+                payment,
+                args.first_token_min_amount_out,
+                args.second_token_min_amount_out,
+            );
+
+            return (PositionEventType::Lp, pair_tokens);
+        }
+
+        self.require_whitelisted_position_sc(&source_address);
+        match self.get_position_type(&source_address) {
+            PositionType::Farm => {
+                let farm_token_id = self.get_farm_token_id_mapper(source_address.clone()).get();
+                require!(
+                    payment.token_identifier == farm_token_id,
+                    "Invalid position token"
+                );
+
+                let pair_address = self
+                    .get_farm_pair_contract_address_mapper(source_address.clone())
+                    .get();
+                let exit_farm_result = self.call_exit_farm(source_address, caller.clone(), payment);
+                output_payments.push(exit_farm_result.rewards);
+
+                let pair_tokens = self.remove_liquidity_for_migration(
+                    pair_address,
+                    exit_farm_result.farming_tokens,
+                    args.first_token_min_amount_out,
+                    args.second_token_min_amount_out,
+                );
+
+                (PositionEventType::Farm, pair_tokens)
+            }
+            PositionType::Metastaking => {
+                let dual_yield_token_id = self
+                    .get_dual_yield_token_id_mapper(source_address.clone())
+                    .get();
+                require!(
+                    payment.token_identifier == dual_yield_token_id,
+                    "Invalid position token"
+                );
+
+                let farm_staking_address = self
+                    .get_staking_farm_address_mapper(source_address.clone())
+                    .get();
+                let exit_args = MetastakingExitArgs {
+                    ms_address: source_address,
+                    user: caller.clone(),
+                    ms_tokens: payment,
+                    first_token_min_amount_out: args.first_token_min_amount_out,
+                    second_token_min_amont_out: args.second_token_min_amount_out,
+                };
+                let unstake_result = self.call_metastaking_unstake(exit_args);
+                output_payments.push(unstake_result.lp_farm_rewards);
+                output_payments.push(unstake_result.staking_rewards);
+                self.add_pending_unbond(
+                    caller,
+                    farm_staking_address,
+                    unstake_result.unbond_staking_farm_token,
+                );
+
+                let other_tokens =
+                    PaymentsVec::from_single_item(unstake_result.other_token_payment);
+
+                (PositionEventType::Metastaking, other_tokens)
+            }
+            PositionType::FarmStaking => {
+                sc_panic!("Farm staking positions cannot be migrated before the unbond period")
+            }
+        }
+    }
+
+    fn remove_liquidity_for_migration(
+        &self,
+        pair_address: ManagedAddress,
+        lp_tokens: EsdtTokenPayment,
+        first_token_min_amount_out: BigUint,
+        second_token_min_amount_out: BigUint,
+    ) -> PaymentsVec<Self::Api> {
+        let remove_liq_result = self.call_pair_remove_liquidity(
+            pair_address,
+            lp_tokens,
+            first_token_min_amount_out,
+            second_token_min_amount_out,
+        );
+
+        let mut pair_tokens = PaymentsVec::from_single_item(remove_liq_result.first_tokens);
+        pair_tokens.push(remove_liq_result.second_tokens);
+
+        pair_tokens
+    }
+
+    /// Returns the type and the tokens of the new position.
+    /// The add liquidity min amounts are not used for farm staking destinations.
+    fn enter_pos_for_migration(
+        &self,
+        caller: &ManagedAddress,
+        args: MigrationEnterArgs<Self::Api>,
+        output_payments: &mut PaymentsWrapper<Self::Api>,
+    ) -> (PositionEventType, EsdtTokenPayment) {
+        let destination_address = args.destination_address;
+        let tokens = args.tokens;
+        let route_discovery = RouteDiscovery::Enabled {
+            opt_max_price_deviation_bps: args.opt_max_price_deviation_bps,
+        };
+        match self.get_position_type(&destination_address) {
+            PositionType::Farm => {
+                let pair_address = self
+                    .get_farm_pair_contract_address_mapper(destination_address.clone())
+                    .get();
+                let max_price_deviation_bps =
+                    self.get_max_price_deviation(&pair_address, args.opt_max_price_deviation_bps);
+                let pair_tokens = self.swap_payments_to_pair_tokens(
+                    &pair_address,
+                    tokens,
+                    max_price_deviation_bps,
+                    route_discovery,
+                    MultiValueEncoded::new(),
+                );
+
+                let create_args = CreateFarmPosArgs {
+                    caller: caller.clone(),
+                    first_token_payment: pair_tokens.first_tokens,
+                    second_token_payment: pair_tokens.second_tokens,
+                    additional_payments: PaymentsVec::new(),
+                    add_liq_first_token_min_amount_out: args.add_liq_first_token_min_amount_out,
+                    add_liq_second_token_min_amount_out: args.add_liq_second_token_min_amount_out,
+                    pair_address,
+                    farm_address: destination_address,
+                };
+                let (new_farm_tokens, create_output_payments) = self.create_farm_pos(create_args);
+                output_payments.append(create_output_payments);
+
+                (PositionEventType::Farm, new_farm_tokens)
+            }
+            PositionType::Metastaking => {
+                let farm_address = self
+                    .get_lp_farm_address_mapper(destination_address.clone())
+                    .get();
+                let pair_address = self
+                    .get_farm_pair_contract_address_mapper(farm_address.clone())
+                    .get();
+                let max_price_deviation_bps =
+                    self.get_max_price_deviation(&pair_address, args.opt_max_price_deviation_bps);
+                let pair_tokens = self.swap_payments_to_pair_tokens(
+                    &pair_address,
+                    tokens,
+                    max_price_deviation_bps,
+                    route_discovery,
+                    MultiValueEncoded::new(),
+                );
+
+                let create_args = CreateMetastakingPosArgs {
+                    caller: caller.clone(),
+                    first_token_payment: pair_tokens.first_tokens,
+                    second_token_payment: pair_tokens.second_tokens,
+                    additional_payments: PaymentsVec::new(),
+                    add_liq_first_token_min_amount_out: args.add_liq_first_token_min_amount_out,
+                    add_liq_second_token_min_amount_out: args.add_liq_second_token_min_amount_out,
+                    pair_address,
+                    farm_address,
+                    metastaking_address: destination_address,
+                };
+                let (new_metastaking_tokens, create_output_payments) =
+                    self.create_metastaking_pos(create_args);
+                output_payments.append(create_output_payments);
+
+                (PositionEventType::Metastaking, new_metastaking_tokens)
+            }
+            PositionType::FarmStaking => {
+                let farming_token_id =
+                    self.get_farm_staking_farming_token_id(destination_address.clone());
+                let staking_tokens = self
+                    .swap_to_single_token(
+                        tokens,
+                        &EgldOrEsdtTokenIdentifier::esdt(farming_token_id),
+                        &BigUint::zero(),
+                    )
+                    .unwrap_esdt();
+
+                let (new_farm_tokens, boosted_rewards_payment) = self
+                    .call_farm_staking_stake(
+                        destination_address,
+                        caller.clone(),
+                        PaymentsVec::from_single_item(staking_tokens),
+                    )
+                    .into_tuple();
+                output_payments.push(boosted_rewards_payment);
+
+                (PositionEventType::FarmStaking, new_farm_tokens)
+            }
+        }
+    }
+}
@@ -1,4 +1,10 @@
+pub mod batch_create_pos;
+pub mod create_multi_token_pos;
 pub mod create_pos;
 pub mod create_pos_endpoints;
 pub mod exit_pos;
 pub mod exit_pos_endpoints;
+pub mod exit_to_single_token;
+pub mod merge_pos;
+pub mod migrate_pos;
+pub mod pending_unbonds;
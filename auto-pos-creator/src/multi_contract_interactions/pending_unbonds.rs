@@ -0,0 +1,129 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::PaymentsVec;
+use farm_staking::token_attributes::UnbondSftAttributes;
+
+#[derive(
+    TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, Clone, PartialEq,
+)]
+pub struct PendingUnbond<M: ManagedTypeApi> {
+    pub farm_staking_address: ManagedAddress<M>,
+    pub unbond_tokens: EsdtTokenPayment<M>,
+    pub unlock_epoch: u64,
+}
+
+#[multiversx_sc::module]
+pub trait PendingUnbondsModule:
+    utils::UtilsModule
+    + read_external_storage::ReadExternalStorageModule
+    + crate::configs::pairs_config::PairsConfigModule
+    + crate::configs::contracts_whitelist::ContractsWhitelistModule
+    + crate::external_sc_interactions::pair_actions::PairActionsModule
+    + crate::external_sc_interactions::router_actions::RouterActionsModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::farm_staking_actions::FarmStakingActionsModule
+    + crate::external_sc_interactions::metastaking_actions::MetastakingActionsModule
+    + crate::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + crate::external_sc_interactions::route_finder::RouteFinderModule
+    + crate::events::EventsModule
+    + super::exit_to_single_token::ExitToSingleTokenModule
+{
+    /// Unbonds all the user's tokens for which the unbond period has passed.
+    /// opt_swap_args: output token and min amount out.
+    /// If given, the unbonded tokens are swapped to the output token, which can also be EGLD.
+    #[endpoint(claimUnbondedTokens)]
+    fn claim_unbonded_tokens(
+        &self,
+        opt_swap_args: OptionalValue<MultiValue2<EgldOrEsdtTokenIdentifier, BigUint>>,
+    ) -> MultiValueEncoded<EgldOrEsdtTokenPayment> {
+        let caller = self.blockchain().get_caller();
+        let unbonded_tokens = self.unbond_unlocked_tokens(&caller);
+        require!(!unbonded_tokens.is_empty(), "No unbonded tokens to claim");
+
+        let output_payments = match opt_swap_args {
+            OptionalValue::Some(swap_args) => {
+                let (output_token_id, min_amount_out) = swap_args.into_tuple();
+                let output_tokens = self.swap_to_single_token(
+                    unbonded_tokens.clone(),
+                    &output_token_id,
+                    &min_amount_out,
+                );
+                self.send().direct_non_zero(
+                    &caller,
+                    &output_tokens.token_identifier,
+                    0,
+                    &output_tokens.amount,
+                );
+
+                ManagedVec::from_single_item(output_tokens)
+            }
+            OptionalValue::None => {
+                self.send().direct_multi(&caller, &unbonded_tokens);
+
+                self.to_egld_or_esdt_payments(&unbonded_tokens)
+            }
+        };
+
+        self.emit_claim_unbonded_event(&caller, &unbonded_tokens, &output_payments);
+
+        output_payments.into()
+    }
+
+    /// The unbond tokens are held by the contract until claimUnbondedTokens is called
+    fn add_pending_unbond(
+        &self,
+        user: &ManagedAddress,
+        farm_staking_address: ManagedAddress,
+        unbond_tokens: EsdtTokenPayment,
+    ) {
+        if unbond_tokens.amount == 0 {
+            return;
+        }
+
+        let attributes: UnbondSftAttributes = self
+            .blockchain()
+            .get_token_attributes(&unbond_tokens.token_identifier, unbond_tokens.token_nonce);
+        let pending_unbond = PendingUnbond {
+            farm_staking_address,
+            unbond_tokens,
+            unlock_epoch: attributes.unlock_epoch,
+        };
+
+        self.pending_unbonds(user)
+            .update(|pending_unbonds| pending_unbonds.push(pending_unbond));
+    }
+
+    fn unbond_unlocked_tokens(&self, user: &ManagedAddress) -> PaymentsVec<Self::Api> {
+        let current_epoch = self.blockchain().get_block_epoch();
+        let pending_unbonds = self.pending_unbonds(user).take();
+
+        let mut remaining_unbonds = ManagedVec::<Self::Api, PendingUnbond<Self::Api>>::new();
+        let mut unbonded_tokens = PaymentsVec::new();
+        for pending_unbond in &pending_unbonds {
+            if pending_unbond.unlock_epoch > current_epoch {
+                remaining_unbonds.push(pending_unbond);
+                continue;
+            }
+
+            let base_tokens = self.call_farm_staking_unbond(
+                pending_unbond.farm_staking_address,
+                pending_unbond.unbond_tokens,
+            );
+            unbonded_tokens.push(base_tokens);
+        }
+
+        if !remaining_unbonds.is_empty() {
+            self.pending_unbonds(user).set(remaining_unbonds);
+        }
+
+        unbonded_tokens
+    }
+
+    #[view(getPendingUnbonds)]
+    #[storage_mapper("pendingUnbonds")]
+    fn pending_unbonds(
+        &self,
+        user: &ManagedAddress,
+    ) -> SingleValueMapper<ManagedVec<PendingUnbond<Self::Api>>>;
+}
@@ -0,0 +1,2 @@
+pub mod quote_views;
+pub mod simulated_pair;
@@ -0,0 +1,1085 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_structs::PaymentsVec;
+use pair_math::MAX_BPS;
+
+use crate::{
+    configs::pairs_config::PairConfig,
+    external_sc_interactions::{
+        route_finder::{RouteHop, SwapRoute, SWAP_TOKENS_FIXED_INPUT_FUNC_NAME},
+        router_actions::{SwapOperation, SwapOperationType},
+    },
+    multi_contract_interactions::{
+        batch_create_pos::BatchPosEntry, create_multi_token_pos::SwapRouteArg,
+        merge_pos::PositionType,
+    },
+};
+
+use super::simulated_pair::{SimulatedPair, SimulatedPairs, SimulatedSwapResult};
+
+/// The added amounts can be used to derive the add liquidity min amounts.
+/// price_impact_bps: the highest price impact among the swaps, fees included
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq)]
+pub struct CreatePosQuote<M: ManagedTypeApi> {
+    pub lp_tokens: EsdtTokenPayment<M>,
+    pub first_tokens_added: EsdtTokenPayment<M>,
+    pub second_tokens_added: EsdtTokenPayment<M>,
+    pub first_tokens_remaining: EsdtTokenPayment<M>,
+    pub second_tokens_remaining: EsdtTokenPayment<M>,
+    pub price_impact_bps: u64,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq)]
+pub struct ExitPosQuote<M: ManagedTypeApi> {
+    pub first_tokens: EsdtTokenPayment<M>,
+    pub second_tokens: EsdtTokenPayment<M>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq)]
+pub struct SwapQuote<M: ManagedTypeApi> {
+    pub output_tokens: EgldOrEsdtTokenPayment<M>,
+    pub price_impact_bps: u64,
+}
+
+/// The quote of a new position, depending on the type of its contract
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq)]
+pub enum NewPosQuote<M: ManagedTypeApi> {
+    Lp(CreatePosQuote<M>),
+    FarmStaking(SwapQuote<M>),
+}
+
+/// Views simulating the endpoints from the pairs' reserves and fees, without executing anything.
+/// Swap routes are given the same way as for the endpoints, and only fixed input swaps are quoted.
+/// When no swap route is given, the quote uses the route the endpoints discover on-chain
+/// when route discovery is requested. The min amounts of the swap operations are not checked.
+/// Each quote keeps the simulated state of the pairs it touches,
+/// so swaps and liquidity changes see the reserves left by the previous steps.
+/// Rewards, farm exit penalties and the pairs' special fees are not taken into account.
+/// Neither is the protocol fee, since fee exemptions depend on the caller.
+#[multiversx_sc::module]
+pub trait QuoteViewsModule:
+    utils::UtilsModule
+    + read_external_storage::ReadExternalStorageModule
+    + crate::configs::pairs_config::PairsConfigModule
+    + crate::external_sc_interactions::pair_actions::PairActionsModule
+    + crate::external_sc_interactions::router_actions::RouterActionsModule
+    + crate::external_sc_interactions::egld_wrapper_actions::EgldWrapperActionsModule
+    + crate::external_sc_interactions::route_finder::RouteFinderModule
+    + crate::multi_contract_interactions::merge_pos::MergePosModule
+{
+    #[view(quoteCreateLpPosFromSingleToken)]
+    fn quote_create_lp_pos_from_single_token(
+        &self,
+        pair_address: ManagedAddress,
+        input_token_id: EgldOrEsdtTokenIdentifier,
+        input_amount: BigUint,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> CreatePosQuote<Self::Api> {
+        self.quote_create_pos(pair_address, input_token_id, input_amount, swap_operations)
+    }
+
+    /// The farm tokens amount is the same as the LP tokens amount
+    #[view(quoteCreateFarmPosFromSingleToken)]
+    fn quote_create_farm_pos_from_single_token(
+        &self,
+        farm_address: ManagedAddress,
+        input_token_id: EgldOrEsdtTokenIdentifier,
+        input_amount: BigUint,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> CreatePosQuote<Self::Api> {
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(farm_address)
+            .get();
+
+        self.quote_create_pos(pair_address, input_token_id, input_amount, swap_operations)
+    }
+
+    /// The LP tokens are the ones staked through the metastaking contract
+    #[view(quoteCreateMetastakingPosFromSingleToken)]
+    fn quote_create_metastaking_pos_from_single_token(
+        &self,
+        metastaking_address: ManagedAddress,
+        input_token_id: EgldOrEsdtTokenIdentifier,
+        input_amount: BigUint,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> CreatePosQuote<Self::Api> {
+        let farm_address = self.get_lp_farm_address_mapper(metastaking_address).get();
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(farm_address)
+            .get();
+
+        self.quote_create_pos(pair_address, input_token_id, input_amount, swap_operations)
+    }
+
+    /// The output tokens are the ones staked, which is also the new farm tokens amount
+    #[view(quoteCreateFarmStakingPosFromSingleToken)]
+    fn quote_create_farm_staking_pos_from_single_token(
+        &self,
+        farm_staking_address: ManagedAddress,
+        input_token_id: EgldOrEsdtTokenIdentifier,
+        input_amount: BigUint,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> SwapQuote<Self::Api> {
+        let input_tokens = self.get_quote_input_tokens(&input_token_id, input_amount);
+        let swap_route = self.to_swap_route_arg(swap_operations);
+        let mut pairs = SimulatedPairs::default();
+
+        self.quote_farm_staking_pos(&mut pairs, farm_staking_address, &input_tokens, &swap_route)
+    }
+
+    /// The amounts are the pair's first and second token amounts
+    #[view(quoteCreateLpPosFromTwoTokens)]
+    fn quote_create_lp_pos_from_two_tokens(
+        &self,
+        pair_address: ManagedAddress,
+        first_token_amount: BigUint,
+        second_token_amount: BigUint,
+    ) -> CreatePosQuote<Self::Api> {
+        require!(
+            first_token_amount > 0 && second_token_amount > 0,
+            "Invalid input amount"
+        );
+        let mut pair = self.load_simulated_pair(&pair_address);
+
+        self.quote_add_liquidity(&mut pair, first_token_amount, second_token_amount, 0)
+    }
+
+    #[view(quoteCreateFarmPosFromTwoTokens)]
+    fn quote_create_farm_pos_from_two_tokens(
+        &self,
+        farm_address: ManagedAddress,
+        first_token_amount: BigUint,
+        second_token_amount: BigUint,
+    ) -> CreatePosQuote<Self::Api> {
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(farm_address)
+            .get();
+
+        self.quote_create_lp_pos_from_two_tokens(
+            pair_address,
+            first_token_amount,
+            second_token_amount,
+        )
+    }
+
+    #[view(quoteCreateMetastakingPosFromTwoTokens)]
+    fn quote_create_metastaking_pos_from_two_tokens(
+        &self,
+        metastaking_address: ManagedAddress,
+        first_token_amount: BigUint,
+        second_token_amount: BigUint,
+    ) -> CreatePosQuote<Self::Api> {
+        let farm_address = self.get_lp_farm_address_mapper(metastaking_address).get();
+
+        self.quote_create_farm_pos_from_two_tokens(
+            farm_address,
+            first_token_amount,
+            second_token_amount,
+        )
+    }
+
+    /// payments: the payments of the multiple tokens endpoints, nonces are ignored
+    /// swap_routes: same as for the endpoints, one route for each payment or none at all
+    #[view(quoteCreateFarmPosFromMultipleTokens)]
+    fn quote_create_farm_pos_from_multiple_tokens(
+        &self,
+        farm_address: ManagedAddress,
+        payments: ManagedVec<EgldOrEsdtTokenPayment>,
+        swap_routes: MultiValueEncoded<SwapRouteArg<Self::Api>>,
+    ) -> CreatePosQuote<Self::Api> {
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(farm_address)
+            .get();
+        let input_tokens = self.get_quote_input_payments(&payments);
+        let mut pairs = SimulatedPairs::default();
+
+        self.quote_create_pos_from_multiple_tokens(
+            &mut pairs,
+            pair_address,
+            input_tokens,
+            swap_routes,
+        )
+    }
+
+    /// Same arguments as quoteCreateFarmPosFromMultipleTokens
+    #[view(quoteCreateMetastakingPosFromMultipleTokens)]
+    fn quote_create_metastaking_pos_from_multiple_tokens(
+        &self,
+        metastaking_address: ManagedAddress,
+        payments: ManagedVec<EgldOrEsdtTokenPayment>,
+        swap_routes: MultiValueEncoded<SwapRouteArg<Self::Api>>,
+    ) -> CreatePosQuote<Self::Api> {
+        let farm_address = self.get_lp_farm_address_mapper(metastaking_address).get();
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(farm_address)
+            .get();
+        let input_tokens = self.get_quote_input_payments(&payments);
+        let mut pairs = SimulatedPairs::default();
+
+        self.quote_create_pos_from_multiple_tokens(
+            &mut pairs,
+            pair_address,
+            input_tokens,
+            swap_routes,
+        )
+    }
+
+    /// Same split as createPositionsFromSingleToken, with one quote for each entry, in entry order.
+    /// All entries see the pair states left by the previous ones.
+    /// Entries with no swap route are quoted with route discovery, even if discover_route is not set.
+    /// The min amounts of the entries are not checked.
+    #[view(quoteCreatePositionsFromSingleToken)]
+    fn quote_create_positions_from_single_token(
+        &self,
+        input_token_id: EgldOrEsdtTokenIdentifier,
+        input_amount: BigUint,
+        entries: MultiValueEncoded<BatchPosEntry<Self::Api>>,
+    ) -> MultiValueEncoded<NewPosQuote<Self::Api>> {
+        require!(!entries.is_empty(), "No entries given");
+
+        let entries = entries.to_vec();
+        let mut total_weight_bps = 0u64;
+        for entry in &entries {
+            require!(entry.weight_bps > 0, "Invalid weight");
+            total_weight_bps += entry.weight_bps;
+        }
+        require!(total_weight_bps == MAX_BPS, "Weights must add up to 100%");
+
+        let input_tokens = self.get_quote_input_tokens(&input_token_id, input_amount);
+        let mut pairs = SimulatedPairs::default();
+        let mut quotes = MultiValueEncoded::new();
+        let mut remaining_amount = input_tokens.amount.clone();
+        let last_index = entries.len() - 1;
+        for (i, entry) in entries.iter().enumerate() {
+            let amount = if i == last_index {
+                remaining_amount.clone()
+            } else {
+                &input_tokens.amount * entry.weight_bps / MAX_BPS
+            };
+            remaining_amount -= &amount;
+            require!(amount > 0, "Payment too small for the entry weight");
+
+            let entry_tokens =
+                EsdtTokenPayment::new(input_tokens.token_identifier.clone(), 0, amount);
+            let quote = self.quote_new_pos(
+                &mut pairs,
+                entry.target_address,
+                entry_tokens,
+                &entry.swap_route,
+            );
+            quotes.push(quote);
+        }
+
+        quotes
+    }
+
+    /// Same steps as migratePosition, with the swap routes discovered on-chain.
+    /// position_amount: the LP or farm tokens of the source position,
+    ///     or the LP farm tokens of the exited metastaking tokens, as for quoteExitMetastakingPos.
+    /// For metastaking sources, only the pair token that is not staked enters the new position.
+    #[view(quoteMigratePosition)]
+    fn quote_migrate_position(
+        &self,
+        source_address: ManagedAddress,
+        destination_address: ManagedAddress,
+        position_amount: BigUint,
+    ) -> NewPosQuote<Self::Api> {
+        require!(
+            source_address != destination_address,
+            "Cannot migrate to the same contract"
+        );
+
+        let mut pairs = SimulatedPairs::default();
+        let exited_tokens =
+            self.quote_exit_pos_for_migration(&mut pairs, source_address, &position_amount);
+
+        match self.get_position_type(&destination_address) {
+            PositionType::Farm => {
+                let pair_address = self
+                    .get_farm_pair_contract_address_mapper(destination_address)
+                    .get();
+                let quote = self.quote_create_pos_from_multiple_tokens(
+                    &mut pairs,
+                    pair_address,
+                    exited_tokens,
+                    MultiValueEncoded::new(),
+                );
+
+                NewPosQuote::Lp(quote)
+            }
+            PositionType::Metastaking => {
+                let farm_address = self.get_lp_farm_address_mapper(destination_address).get();
+                let pair_address = self
+                    .get_farm_pair_contract_address_mapper(farm_address)
+                    .get();
+                let quote = self.quote_create_pos_from_multiple_tokens(
+                    &mut pairs,
+                    pair_address,
+                    exited_tokens,
+                    MultiValueEncoded::new(),
+                );
+
+                NewPosQuote::Lp(quote)
+            }
+            PositionType::FarmStaking => {
+                let farming_token_id = self.get_farming_token_id_mapper(destination_address).get();
+                let mut total_amount = BigUint::zero();
+                let mut price_impact_bps = 0;
+                for payment in &exited_tokens {
+                    if payment.amount == 0 {
+                        continue;
+                    }
+
+                    let (output_tokens, swap_price_impact_bps) =
+                        self.quote_swap_to_token(&mut pairs, &payment, &farming_token_id);
+                    total_amount += output_tokens.amount;
+                    price_impact_bps = core::cmp::max(price_impact_bps, swap_price_impact_bps);
+                }
+
+                NewPosQuote::FarmStaking(SwapQuote {
+                    output_tokens: EgldOrEsdtTokenPayment::new(
+                        EgldOrEsdtTokenIdentifier::esdt(farming_token_id),
+                        0,
+                        total_amount,
+                    ),
+                    price_impact_bps,
+                })
+            }
+        }
+    }
+
+    #[view(quoteExitLpPos)]
+    fn quote_exit_lp_pos(
+        &self,
+        pair_address: ManagedAddress,
+        lp_amount: BigUint,
+    ) -> ExitPosQuote<Self::Api> {
+        let mut pair = self.load_simulated_pair(&pair_address);
+        let (first_amount, second_amount) = self.simulate_remove_liquidity(&mut pair, &lp_amount);
+
+        ExitPosQuote {
+            first_tokens: EsdtTokenPayment::new(pair.first_token_id, 0, first_amount),
+            second_tokens: EsdtTokenPayment::new(pair.second_token_id, 0, second_amount),
+        }
+    }
+
+    /// The LP tokens amount is the same as the farm tokens amount
+    #[view(quoteExitFarmPos)]
+    fn quote_exit_farm_pos(
+        &self,
+        farm_address: ManagedAddress,
+        farm_token_amount: BigUint,
+    ) -> ExitPosQuote<Self::Api> {
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(farm_address)
+            .get();
+
+        self.quote_exit_lp_pos(pair_address, farm_token_amount)
+    }
+
+    /// lp_farm_token_amount: the LP farm tokens of the exited metastaking tokens,
+    /// proportional to the dual yield token attributes.
+    /// The staked pair token amount is returned as farm staking unbond tokens.
+    #[view(quoteExitMetastakingPos)]
+    fn quote_exit_metastaking_pos(
+        &self,
+        metastaking_address: ManagedAddress,
+        lp_farm_token_amount: BigUint,
+    ) -> ExitPosQuote<Self::Api> {
+        let farm_address = self.get_lp_farm_address_mapper(metastaking_address).get();
+
+        self.quote_exit_farm_pos(farm_address, lp_farm_token_amount)
+    }
+
+    #[view(quoteExitLpPosToSingleToken)]
+    fn quote_exit_lp_pos_to_single_token(
+        &self,
+        pair_address: ManagedAddress,
+        lp_amount: BigUint,
+        output_token_id: EgldOrEsdtTokenIdentifier,
+    ) -> SwapQuote<Self::Api> {
+        self.quote_exit_to_single_token(pair_address, lp_amount, output_token_id)
+    }
+
+    #[view(quoteExitFarmPosToSingleToken)]
+    fn quote_exit_farm_pos_to_single_token(
+        &self,
+        farm_address: ManagedAddress,
+        farm_token_amount: BigUint,
+        output_token_id: EgldOrEsdtTokenIdentifier,
+    ) -> SwapQuote<Self::Api> {
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(farm_address)
+            .get();
+
+        self.quote_exit_to_single_token(pair_address, farm_token_amount, output_token_id)
+    }
+
+    /// Same arguments as quoteExitMetastakingPos.
+    /// Only the pair token that is not staked is swapped.
+    #[view(quoteExitMetastakingPosToSingleToken)]
+    fn quote_exit_metastaking_pos_to_single_token(
+        &self,
+        metastaking_address: ManagedAddress,
+        lp_farm_token_amount: BigUint,
+        output_token_id: EgldOrEsdtTokenIdentifier,
+    ) -> SwapQuote<Self::Api> {
+        let staking_token_id = self
+            .get_staking_token_id_mapper(metastaking_address.clone())
+            .get();
+        let farm_address = self.get_lp_farm_address_mapper(metastaking_address).get();
+        let pair_address = self
+            .get_farm_pair_contract_address_mapper(farm_address)
+            .get();
+
+        let wanted_token_id = self.get_wrapped_token_id(&output_token_id);
+        let mut pair = self.load_simulated_pair(&pair_address);
+        let (first_amount, second_amount) =
+            self.simulate_remove_liquidity(&mut pair, &lp_farm_token_amount);
+        let other_tokens = if pair.first_token_id == staking_token_id {
+            EsdtTokenPayment::new(pair.second_token_id.clone(), 0, second_amount)
+        } else {
+            EsdtTokenPayment::new(pair.first_token_id.clone(), 0, first_amount)
+        };
+
+        let mut pairs = SimulatedPairs::default();
+        pairs.set(&pair_address, pair);
+        let (output_tokens, price_impact_bps) = self.quote_pair_tokens_to_single_token(
+            &mut pairs,
+            &pair_address,
+            &PaymentsVec::from_single_item(other_tokens),
+            &wanted_token_id,
+        );
+
+        // wrapped EGLD is unwrapped 1:1
+        SwapQuote {
+            output_tokens: EgldOrEsdtTokenPayment::new(output_token_id, 0, output_tokens.amount),
+            price_impact_bps,
+        }
+    }
+
+    fn quote_create_pos(
+        &self,
+        pair_address: ManagedAddress,
+        input_token_id: EgldOrEsdtTokenIdentifier,
+        input_amount: BigUint,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> CreatePosQuote<Self::Api> {
+        let input_tokens = self.get_quote_input_tokens(&input_token_id, input_amount);
+        let swap_route = self.to_swap_route_arg(swap_operations);
+        let mut pairs = SimulatedPairs::default();
+
+        self.quote_create_pos_from_single_token(&mut pairs, pair_address, input_tokens, &swap_route)
+    }
+
+    /// Same steps as the createPos endpoints: swap to one of the pair tokens,
+    /// swap the optimal part to the other pair token, then add liquidity
+    fn quote_create_pos_from_single_token(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        pair_address: ManagedAddress,
+        input_tokens: EsdtTokenPayment,
+        swap_route: &SwapRouteArg<Self::Api>,
+    ) -> CreatePosQuote<Self::Api> {
+        let pair_config = self.get_pair_config(&pair_address);
+        let zero_first_tokens =
+            EsdtTokenPayment::new(pair_config.first_token_id.clone(), 0, BigUint::zero());
+        let zero_second_tokens =
+            EsdtTokenPayment::new(pair_config.second_token_id.clone(), 0, BigUint::zero());
+        if swap_route.is_empty() && input_tokens.token_identifier == pair_config.lp_token_id {
+            return CreatePosQuote {
+                lp_tokens: input_tokens,
+                first_tokens_added: zero_first_tokens.clone(),
+                second_tokens_added: zero_second_tokens.clone(),
+                first_tokens_remaining: zero_first_tokens,
+                second_tokens_remaining: zero_second_tokens,
+                price_impact_bps: 0,
+            };
+        }
+
+        let (mut pair_tokens, route_price_impact_bps) = self.quote_payment_for_pair(
+            pairs,
+            input_tokens,
+            &pair_address,
+            &pair_config,
+            swap_route,
+        );
+
+        let mut pair = self.get_simulated_pair(pairs, &pair_address);
+        let swap_amount = pair.get_optimal_swap_amount(&pair_tokens);
+        pair_tokens.amount -= &swap_amount;
+        let swap_input_tokens =
+            EsdtTokenPayment::new(pair_tokens.token_identifier.clone(), 0, swap_amount);
+        let swap_result = pair.swap(&swap_input_tokens);
+
+        let (first_amount, second_amount) =
+            if pair_tokens.token_identifier == pair_config.first_token_id {
+                (pair_tokens.amount, swap_result.output_tokens.amount)
+            } else {
+                (swap_result.output_tokens.amount, pair_tokens.amount)
+            };
+        let price_impact_bps = core::cmp::max(route_price_impact_bps, swap_result.price_impact_bps);
+
+        let quote =
+            self.quote_add_liquidity(&mut pair, first_amount, second_amount, price_impact_bps);
+        pairs.set(&pair_address, pair);
+
+        quote
+    }
+
+    /// Same steps as the multiple tokens endpoints: swap each payment to one of the pair tokens,
+    /// then swap the optimal part of the excess to the other pair token and add liquidity
+    fn quote_create_pos_from_multiple_tokens(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        pair_address: ManagedAddress,
+        input_tokens: PaymentsVec<Self::Api>,
+        swap_routes: MultiValueEncoded<SwapRouteArg<Self::Api>>,
+    ) -> CreatePosQuote<Self::Api> {
+        require!(
+            swap_routes.is_empty() || swap_routes.len() == input_tokens.len(),
+            "Invalid number of swap routes"
+        );
+
+        let pair_config = self.get_pair_config(&pair_address);
+        let mut first_amount = BigUint::zero();
+        let mut second_amount = BigUint::zero();
+        let mut price_impact_bps = 0;
+        let mut swap_routes_iter = swap_routes.into_iter();
+        for payment in &input_tokens {
+            let swap_route = swap_routes_iter.next().unwrap_or_default();
+            let (pair_tokens, route_price_impact_bps) = self.quote_payment_for_pair(
+                pairs,
+                payment,
+                &pair_address,
+                &pair_config,
+                &swap_route,
+            );
+            if pair_tokens.token_identifier == pair_config.first_token_id {
+                first_amount += pair_tokens.amount;
+            } else {
+                second_amount += pair_tokens.amount;
+            }
+
+            price_impact_bps = core::cmp::max(price_impact_bps, route_price_impact_bps);
+        }
+
+        let mut pair = self.get_simulated_pair(pairs, &pair_address);
+        let first_value_in_second = &first_amount * &pair.second_reserve;
+        let second_value_in_first = &second_amount * &pair.first_reserve;
+        let excess_tokens = if first_value_in_second > second_value_in_first {
+            let matched_amount = &second_value_in_first / &pair.second_reserve;
+            let excess_amount = &first_amount - &matched_amount;
+            EsdtTokenPayment::new(pair_config.first_token_id.clone(), 0, excess_amount)
+        } else {
+            let matched_amount = &first_value_in_second / &pair.first_reserve;
+            let excess_amount = &second_amount - &matched_amount;
+            EsdtTokenPayment::new(pair_config.second_token_id.clone(), 0, excess_amount)
+        };
+
+        let swap_amount = pair.get_optimal_swap_amount(&excess_tokens);
+        if swap_amount > 0 {
+            let swap_input_tokens =
+                EsdtTokenPayment::new(excess_tokens.token_identifier.clone(), 0, swap_amount);
+            let swap_result = pair.swap(&swap_input_tokens);
+            if excess_tokens.token_identifier == pair_config.first_token_id {
+                first_amount -= &swap_input_tokens.amount;
+                second_amount += swap_result.output_tokens.amount;
+            } else {
+                second_amount -= &swap_input_tokens.amount;
+                first_amount += swap_result.output_tokens.amount;
+            }
+
+            price_impact_bps = core::cmp::max(price_impact_bps, swap_result.price_impact_bps);
+        }
+
+        let quote =
+            self.quote_add_liquidity(&mut pair, first_amount, second_amount, price_impact_bps);
+        pairs.set(&pair_address, pair);
+
+        quote
+    }
+
+    fn quote_farm_staking_pos(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        farm_staking_address: ManagedAddress,
+        input_tokens: &EsdtTokenPayment,
+        swap_route: &SwapRouteArg<Self::Api>,
+    ) -> SwapQuote<Self::Api> {
+        let farming_token_id = self.get_farming_token_id_mapper(farm_staking_address).get();
+        let (output_tokens, price_impact_bps) =
+            self.quote_payment_for_token(pairs, input_tokens, &farming_token_id, swap_route);
+
+        SwapQuote {
+            output_tokens: output_tokens.into(),
+            price_impact_bps,
+        }
+    }
+
+    fn quote_new_pos(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        target_address: ManagedAddress,
+        input_tokens: EsdtTokenPayment,
+        swap_route: &SwapRouteArg<Self::Api>,
+    ) -> NewPosQuote<Self::Api> {
+        match self.get_position_type(&target_address) {
+            PositionType::Farm => {
+                let pair_address = self
+                    .get_farm_pair_contract_address_mapper(target_address)
+                    .get();
+                let quote = self.quote_create_pos_from_single_token(
+                    pairs,
+                    pair_address,
+                    input_tokens,
+                    swap_route,
+                );
+
+                NewPosQuote::Lp(quote)
+            }
+            PositionType::Metastaking => {
+                let farm_address = self.get_lp_farm_address_mapper(target_address).get();
+                let pair_address = self
+                    .get_farm_pair_contract_address_mapper(farm_address)
+                    .get();
+                let quote = self.quote_create_pos_from_single_token(
+                    pairs,
+                    pair_address,
+                    input_tokens,
+                    swap_route,
+                );
+
+                NewPosQuote::Lp(quote)
+            }
+            PositionType::FarmStaking => {
+                let quote =
+                    self.quote_farm_staking_pos(pairs, target_address, &input_tokens, swap_route);
+
+                NewPosQuote::FarmStaking(quote)
+            }
+        }
+    }
+
+    /// Returns the pair tokens used to enter the new position
+    fn quote_exit_pos_for_migration(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        source_address: ManagedAddress,
+        position_amount: &BigUint,
+    ) -> PaymentsVec<Self::Api> {
+        if !self
+            .get_lp_token_id_mapper(source_address.clone())
+            .is_empty()
+        {
+            return self.quote_remove_liquidity(pairs, &source_address, position_amount);
+        }
+
+        match self.get_position_type(&source_address) {
+            PositionType::Farm => {
+                let pair_address = self
+                    .get_farm_pair_contract_address_mapper(source_address)
+                    .get();
+
+                self.quote_remove_liquidity(pairs, &pair_address, position_amount)
+            }
+            PositionType::Metastaking => {
+                let staking_token_id = self
+                    .get_staking_token_id_mapper(source_address.clone())
+                    .get();
+                let farm_address = self.get_lp_farm_address_mapper(source_address).get();
+                let pair_address = self
+                    .get_farm_pair_contract_address_mapper(farm_address)
+                    .get();
+                let pair_tokens =
+                    self.quote_remove_liquidity(pairs, &pair_address, position_amount);
+
+                let mut other_tokens = PaymentsVec::new();
+                for payment in &pair_tokens {
+                    if payment.token_identifier != staking_token_id {
+                        other_tokens.push(payment);
+                    }
+                }
+
+                other_tokens
+            }
+            PositionType::FarmStaking => {
+                sc_panic!("Farm staking positions cannot be migrated before the unbond period")
+            }
+        }
+    }
+
+    fn quote_remove_liquidity(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        pair_address: &ManagedAddress,
+        lp_amount: &BigUint,
+    ) -> PaymentsVec<Self::Api> {
+        let mut pair = self.get_simulated_pair(pairs, pair_address);
+        let (first_amount, second_amount) = self.simulate_remove_liquidity(&mut pair, lp_amount);
+        let first_tokens = EsdtTokenPayment::new(pair.first_token_id.clone(), 0, first_amount);
+        let second_tokens = EsdtTokenPayment::new(pair.second_token_id.clone(), 0, second_amount);
+        pairs.set(pair_address, pair);
+
+        let mut pair_tokens = PaymentsVec::from_single_item(first_tokens);
+        pair_tokens.push(second_tokens);
+
+        pair_tokens
+    }
+
+    fn quote_add_liquidity(
+        &self,
+        pair: &mut SimulatedPair<Self::Api>,
+        first_amount: BigUint,
+        second_amount: BigUint,
+        price_impact_bps: u64,
+    ) -> CreatePosQuote<Self::Api> {
+        let add_liq_result = pair.add_liquidity(&first_amount, &second_amount);
+        let first_amount_remaining = &first_amount - &add_liq_result.first_amount_added;
+        let second_amount_remaining = &second_amount - &add_liq_result.second_amount_added;
+
+        CreatePosQuote {
+            lp_tokens: EsdtTokenPayment::new(pair.lp_token_id.clone(), 0, add_liq_result.lp_amount),
+            first_tokens_added: EsdtTokenPayment::new(
+                pair.first_token_id.clone(),
+                0,
+                add_liq_result.first_amount_added,
+            ),
+            second_tokens_added: EsdtTokenPayment::new(
+                pair.second_token_id.clone(),
+                0,
+                add_liq_result.second_amount_added,
+            ),
+            first_tokens_remaining: EsdtTokenPayment::new(
+                pair.first_token_id.clone(),
+                0,
+                first_amount_remaining,
+            ),
+            second_tokens_remaining: EsdtTokenPayment::new(
+                pair.second_token_id.clone(),
+                0,
+                second_amount_remaining,
+            ),
+            price_impact_bps,
+        }
+    }
+
+    fn quote_exit_to_single_token(
+        &self,
+        pair_address: ManagedAddress,
+        lp_amount: BigUint,
+        output_token_id: EgldOrEsdtTokenIdentifier,
+    ) -> SwapQuote<Self::Api> {
+        let wanted_token_id = self.get_wrapped_token_id(&output_token_id);
+        let mut pair = self.load_simulated_pair(&pair_address);
+        let (first_amount, second_amount) = self.simulate_remove_liquidity(&mut pair, &lp_amount);
+
+        let mut pair_tokens = PaymentsVec::new();
+        pair_tokens.push(EsdtTokenPayment::new(pair.first_token_id.clone(), 0, first_amount));
+        pair_tokens.push(EsdtTokenPayment::new(pair.second_token_id.clone(), 0, second_amount));
+
+        let mut pairs = SimulatedPairs::default();
+        pairs.set(&pair_address, pair);
+        let (output_tokens, price_impact_bps) = self.quote_pair_tokens_to_single_token(
+            &mut pairs,
+            &pair_address,
+            &pair_tokens,
+            &wanted_token_id,
+        );
+
+        // wrapped EGLD is unwrapped 1:1
+        SwapQuote {
+            output_tokens: EgldOrEsdtTokenPayment::new(output_token_id, 0, output_tokens.amount),
+            price_impact_bps,
+        }
+    }
+
+    /// Pair tokens are swapped directly to the other pair token, or through a route otherwise
+    fn quote_pair_tokens_to_single_token(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        pair_address: &ManagedAddress,
+        pair_tokens: &PaymentsVec<Self::Api>,
+        wanted_token_id: &TokenIdentifier,
+    ) -> (EsdtTokenPayment, u64) {
+        let is_pair_token = self
+            .get_simulated_pair(pairs, pair_address)
+            .contains(wanted_token_id);
+        let mut total_amount = BigUint::zero();
+        let mut price_impact_bps = 0;
+        for payment in pair_tokens {
+            if payment.amount == 0 {
+                continue;
+            }
+
+            let is_wanted_token = &payment.token_identifier == wanted_token_id;
+            let (output_tokens, swap_price_impact_bps) = if is_wanted_token {
+                (payment, 0)
+            } else if is_pair_token {
+                let swap_result = self.quote_pair_swap(pairs, pair_address, &payment);
+                (swap_result.output_tokens, swap_result.price_impact_bps)
+            } else {
+                self.quote_swap_to_token(pairs, &payment, wanted_token_id)
+            };
+
+            total_amount += output_tokens.amount;
+            price_impact_bps = core::cmp::max(price_impact_bps, swap_price_impact_bps);
+        }
+
+        let output_tokens = EsdtTokenPayment::new(wanted_token_id.clone(), 0, total_amount);
+
+        (output_tokens, price_impact_bps)
+    }
+
+    /// Same as process_payment_for_pair: the given route is used if there is one
+    fn quote_payment_for_pair(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        input_tokens: EsdtTokenPayment,
+        pair_address: &ManagedAddress,
+        pair_config: &PairConfig<Self::Api>,
+        swap_route: &SwapRouteArg<Self::Api>,
+    ) -> (EsdtTokenPayment, u64) {
+        if swap_route.is_empty() {
+            return self.quote_swap_to_pair(pairs, input_tokens, pair_address, pair_config);
+        }
+
+        let (output_tokens, price_impact_bps) =
+            self.quote_swap_operations(pairs, &input_tokens, swap_route);
+        let token_id = &output_tokens.token_identifier;
+        require!(
+            token_id == &pair_config.first_token_id || token_id == &pair_config.second_token_id,
+            "The output token identifier is not part of the LP"
+        );
+
+        (output_tokens, price_impact_bps)
+    }
+
+    /// Same as process_payment_for_token: the given route is used if there is one
+    fn quote_payment_for_token(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        input_tokens: &EsdtTokenPayment,
+        wanted_token_id: &TokenIdentifier,
+        swap_route: &SwapRouteArg<Self::Api>,
+    ) -> (EsdtTokenPayment, u64) {
+        if swap_route.is_empty() {
+            return self.quote_swap_to_token(pairs, input_tokens, wanted_token_id);
+        }
+
+        let (output_tokens, price_impact_bps) =
+            self.quote_swap_operations(pairs, input_tokens, swap_route);
+        require!(
+            &output_tokens.token_identifier == wanted_token_id,
+            "Invalid swap output token identifier"
+        );
+
+        (output_tokens, price_impact_bps)
+    }
+
+    fn quote_swap_operations(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        input_tokens: &EsdtTokenPayment,
+        swap_route: &SwapRouteArg<Self::Api>,
+    ) -> (EsdtTokenPayment, u64) {
+        let fixed_input_function_name = ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME);
+        let mut hops = ManagedVec::new();
+        for swap_operation in swap_route {
+            require!(
+                swap_operation.function_name == fixed_input_function_name,
+                "Only fixed input swaps can be quoted"
+            );
+
+            hops.push(RouteHop {
+                pair_address: swap_operation.pair_address,
+                output_token_id: swap_operation.token_wanted,
+            });
+        }
+
+        let route = SwapRoute {
+            hops,
+            amount_out: BigUint::zero(),
+        };
+
+        self.quote_swap_route(pairs, input_tokens, &route)
+    }
+
+    fn quote_swap_to_pair(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        input_tokens: EsdtTokenPayment,
+        pair_address: &ManagedAddress,
+        pair_config: &PairConfig<Self::Api>,
+    ) -> (EsdtTokenPayment, u64) {
+        let token_id = &input_tokens.token_identifier;
+        if token_id == &pair_config.first_token_id || token_id == &pair_config.second_token_id {
+            return (input_tokens, 0);
+        }
+
+        match self.find_best_route_to_pair(&input_tokens, pair_address, pair_config) {
+            Some(route) => self.quote_swap_route(pairs, &input_tokens, &route),
+            None => sc_panic!("The output token identifier is not part of the LP"),
+        }
+    }
+
+    fn quote_swap_to_token(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        input_tokens: &EsdtTokenPayment,
+        wanted_token_id: &TokenIdentifier,
+    ) -> (EsdtTokenPayment, u64) {
+        if &input_tokens.token_identifier == wanted_token_id {
+            return (input_tokens.clone(), 0);
+        }
+
+        match self.find_best_route(input_tokens, wanted_token_id) {
+            Some(route) => self.quote_swap_route(pairs, input_tokens, &route),
+            None => sc_panic!("No swap route found to the output token"),
+        }
+    }
+
+    /// Discovered routes are chosen from the current reserves,
+    /// while each hop is simulated on the pair state left by the previous actions
+    fn quote_swap_route(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        input_tokens: &EsdtTokenPayment,
+        route: &SwapRoute<Self::Api>,
+    ) -> (EsdtTokenPayment, u64) {
+        let mut current_tokens = input_tokens.clone();
+        let mut spot_amount_out = input_tokens.amount.clone();
+        for hop in &route.hops {
+            let pair = self.get_simulated_pair(pairs, &hop.pair_address);
+            require!(
+                pair.contains(&current_tokens.token_identifier)
+                    && pair.contains(&hop.output_token_id)
+                    && current_tokens.token_identifier != hop.output_token_id,
+                "Invalid swap route"
+            );
+
+            spot_amount_out = pair_math::get_spot_amount_out(
+                &spot_amount_out,
+                pair.get_reserve(&current_tokens.token_identifier),
+                pair.get_reserve(&hop.output_token_id),
+            );
+
+            let swap_result = self.quote_pair_swap(pairs, &hop.pair_address, &current_tokens);
+            current_tokens = swap_result.output_tokens;
+        }
+
+        let price_impact_bps =
+            pair_math::get_price_impact_bps(&spot_amount_out, &current_tokens.amount);
+
+        (current_tokens, price_impact_bps)
+    }
+
+    fn quote_pair_swap(
+        &self,
+        pairs: &mut SimulatedPairs<Self::Api>,
+        pair_address: &ManagedAddress,
+        input_tokens: &EsdtTokenPayment,
+    ) -> SimulatedSwapResult<Self::Api> {
+        let mut pair = self.get_simulated_pair(pairs, pair_address);
+        let swap_result = pair.swap(input_tokens);
+        pairs.set(pair_address, pair);
+
+        swap_result
+    }
+
+    fn get_simulated_pair(
+        &self,
+        pairs: &SimulatedPairs<Self::Api>,
+        pair_address: &ManagedAddress,
+    ) -> SimulatedPair<Self::Api> {
+        match pairs.get(pair_address) {
+            Some(pair) => pair,
+            None => self.load_simulated_pair(pair_address),
+        }
+    }
+
+    fn simulate_remove_liquidity(
+        &self,
+        pair: &mut SimulatedPair<Self::Api>,
+        lp_amount: &BigUint,
+    ) -> (BigUint, BigUint) {
+        require!(lp_amount <= &pair.lp_token_supply, "Invalid LP tokens amount");
+
+        pair.remove_liquidity(lp_amount)
+    }
+
+    fn get_quote_input_tokens(
+        &self,
+        input_token_id: &EgldOrEsdtTokenIdentifier,
+        input_amount: BigUint,
+    ) -> EsdtTokenPayment {
+        require!(input_amount > 0, "Invalid input amount");
+        let token_id = self.get_wrapped_token_id(input_token_id);
+
+        EsdtTokenPayment::new(token_id, 0, input_amount)
+    }
+
+    fn get_quote_input_payments(
+        &self,
+        payments: &ManagedVec<EgldOrEsdtTokenPayment>,
+    ) -> PaymentsVec<Self::Api> {
+        require!(!payments.is_empty(), "Invalid payments");
+
+        let mut input_tokens = PaymentsVec::new();
+        for payment in payments {
+            let tokens = self.get_quote_input_tokens(&payment.token_identifier, payment.amount);
+            input_tokens.push(tokens);
+        }
+
+        input_tokens
+    }
+
+    fn to_swap_route_arg(
+        &self,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> SwapRouteArg<Self::Api> {
+        let mut swap_route = ManagedVec::new();
+        for swap_operation in swap_operations {
+            let (pair_address, function_name, token_wanted, min_amount_out) =
+                swap_operation.into_tuple();
+            swap_route.push(SwapOperation {
+                pair_address,
+                function_name,
+                token_wanted,
+                min_amount_out,
+            });
+        }
+
+        swap_route
+    }
+
+    fn load_simulated_pair(&self, pair_address: &ManagedAddress) -> SimulatedPair<Self::Api> {
+        self.require_sc_address(pair_address);
+
+        let pair_config = self.get_pair_config(pair_address);
+        let first_reserve = self
+            .get_pair_reserve_mapper(pair_address.clone(), &pair_config.first_token_id)
+            .get();
+        let second_reserve = self
+            .get_pair_reserve_mapper(pair_address.clone(), &pair_config.second_token_id)
+            .get();
+        let lp_token_supply = self.get_lp_token_supply_mapper(pair_address.clone()).get();
+        require!(
+            first_reserve > 0 && second_reserve > 0 && lp_token_supply > 0,
+            "Pair has no liquidity"
+        );
+
+        SimulatedPair {
+            lp_token_id: pair_config.lp_token_id,
+            first_token_id: pair_config.first_token_id,
+            second_token_id: pair_config.second_token_id,
+            first_reserve,
+            second_reserve,
+            lp_token_supply,
+            total_fee_percent: self
+                .get_total_fee_percent_mapper(pair_address.clone())
+                .get(),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+pub struct SimulatedSwapResult<M: ManagedTypeApi> {
+    pub output_tokens: EsdtTokenPayment<M>,
+    pub price_impact_bps: u64,
+}
+
+pub struct SimulatedAddLiqResult<M: ManagedTypeApi> {
+    pub lp_amount: BigUint<M>,
+    pub first_amount_added: BigUint<M>,
+    pub second_amount_added: BigUint<M>,
+}
+
+/// In-memory copy of a pair's state, updated by each simulated action.
+/// The special fee sent out of the pair on swaps is not deducted from the reserves.
+#[derive(ManagedVecItem)]
+pub struct SimulatedPair<M: ManagedTypeApi> {
+    pub lp_token_id: TokenIdentifier<M>,
+    pub first_token_id: TokenIdentifier<M>,
+    pub second_token_id: TokenIdentifier<M>,
+    pub first_reserve: BigUint<M>,
+    pub second_reserve: BigUint<M>,
+    pub lp_token_supply: BigUint<M>,
+    pub total_fee_percent: u64,
+}
+
+impl<M: ManagedTypeApi> SimulatedPair<M> {
+    pub fn contains(&self, token_id: &TokenIdentifier<M>) -> bool {
+        token_id == &self.first_token_id || token_id == &self.second_token_id
+    }
+
+    pub fn get_reserve(&self, token_id: &TokenIdentifier<M>) -> &BigUint<M> {
+        if token_id == &self.first_token_id {
+            &self.first_reserve
+        } else {
+            &self.second_reserve
+        }
+    }
+
+    pub fn get_optimal_swap_amount(&self, input_tokens: &EsdtTokenPayment<M>) -> BigUint<M> {
+        pair_math::get_optimal_swap_amount(
+            &input_tokens.amount,
+            self.get_reserve(&input_tokens.token_identifier),
+            self.total_fee_percent,
+        )
+    }
+
+    pub fn swap(&mut self, input_tokens: &EsdtTokenPayment<M>) -> SimulatedSwapResult<M> {
+        let is_first_token_input = input_tokens.token_identifier == self.first_token_id;
+        let (reserve_in, reserve_out, output_token_id) = if is_first_token_input {
+            (
+                &mut self.first_reserve,
+                &mut self.second_reserve,
+                self.second_token_id.clone(),
+            )
+        } else {
+            (
+                &mut self.second_reserve,
+                &mut self.first_reserve,
+                self.first_token_id.clone(),
+            )
+        };
+
+        let amount_out = pair_math::get_amount_out(
+            &input_tokens.amount,
+            reserve_in,
+            reserve_out,
+            self.total_fee_percent,
+        );
+        let spot_amount_out =
+            pair_math::get_spot_amount_out(&input_tokens.amount, reserve_in, reserve_out);
+        let price_impact_bps = pair_math::get_price_impact_bps(&spot_amount_out, &amount_out);
+
+        *reserve_in += &input_tokens.amount;
+        *reserve_out -= &amount_out;
+
+        SimulatedSwapResult {
+            output_tokens: EsdtTokenPayment::new(output_token_id, 0, amount_out),
+            price_impact_bps,
+        }
+    }
+
+    /// Same as the pair's add liquidity: the excess of one of the tokens is not added
+    pub fn add_liquidity(
+        &mut self,
+        first_amount: &BigUint<M>,
+        second_amount: &BigUint<M>,
+    ) -> SimulatedAddLiqResult<M> {
+        let second_optimal = first_amount * &self.second_reserve / &self.first_reserve;
+        let (first_amount_added, second_amount_added) = if &second_optimal <= second_amount {
+            (first_amount.clone(), second_optimal)
+        } else {
+            let first_optimal = second_amount * &self.first_reserve / &self.second_reserve;
+            (first_optimal, second_amount.clone())
+        };
+
+        let first_liquidity = &first_amount_added * &self.lp_token_supply / &self.first_reserve;
+        let second_liquidity = &second_amount_added * &self.lp_token_supply / &self.second_reserve;
+        let lp_amount = core::cmp::min(first_liquidity, second_liquidity);
+
+        self.first_reserve += &first_amount_added;
+        self.second_reserve += &second_amount_added;
+        self.lp_token_supply += &lp_amount;
+
+        SimulatedAddLiqResult {
+            lp_amount,
+            first_amount_added,
+            second_amount_added,
+        }
+    }
+
+    /// Returns the first and second token amounts
+    pub fn remove_liquidity(&mut self, lp_amount: &BigUint<M>) -> (BigUint<M>, BigUint<M>) {
+        let first_amount = lp_amount * &self.first_reserve / &self.lp_token_supply;
+        let second_amount = lp_amount * &self.second_reserve / &self.lp_token_supply;
+
+        self.first_reserve -= &first_amount;
+        self.second_reserve -= &second_amount;
+        self.lp_token_supply -= lp_amount;
+
+        (first_amount, second_amount)
+    }
+}
+
+/// The pairs touched by a quote,
+/// so each simulated action sees the reserves left by the previous ones
+pub struct SimulatedPairs<M: ManagedTypeApi> {
+    addresses: ManagedVec<M, ManagedAddress<M>>,
+    pairs: ManagedVec<M, SimulatedPair<M>>,
+}
+
+impl<M: ManagedTypeApi> Default for SimulatedPairs<M> {
+    fn default() -> Self {
+        SimulatedPairs {
+            addresses: ManagedVec::new(),
+            pairs: ManagedVec::new(),
+        }
+    }
+}
+
+impl<M: ManagedTypeApi> SimulatedPairs<M> {
+    pub fn get(&self, pair_address: &ManagedAddress<M>) -> Option<SimulatedPair<M>> {
+        let index = self.addresses.find(pair_address)?;
+
+        Some(self.pairs.get(index))
+    }
+
+    pub fn set(&mut self, pair_address: &ManagedAddress<M>, pair: SimulatedPair<M>) {
+        match self.addresses.find(pair_address) {
+            Some(index) => {
+                let _ = self.pairs.set(index, &pair);
+            }
+            None => {
+                self.addresses.push(pair_address.clone());
+                self.pairs.push(pair);
+            }
+        }
+    }
+}
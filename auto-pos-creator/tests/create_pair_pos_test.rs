@@ -1,22 +1,46 @@
 #![allow(deprecated)]
 
+use auto_farm::{
+    user_tokens::user_farm_tokens::UserFarmTokensModule,
+    whitelists::{
+        farms_whitelist::FarmsWhitelistModule,
+        position_creators_whitelist::PositionCreatorsWhitelistModule,
+    },
+    AutoFarm,
+};
 use auto_pos_creator::{
-    external_sc_interactions::router_actions::SwapOperationType,
+    configs::price_deviation_config::PriceDeviationConfigModule,
+    external_sc_interactions::{
+        auto_farm_actions::{AutoFarmActionsModule, PositionDestination},
+        route_finder::RouteFinderModule,
+        router_actions::SwapOperationType,
+    },
+    fees::FeesModule,
     multi_contract_interactions::{
+        batch_create_pos::{BatchCreatePosModule, BatchPosEntry},
+        create_multi_token_pos::CreateMultiTokenPosModule, create_pos::CreatePosOptions,
         create_pos_endpoints::CreatePosEndpointsModule, exit_pos_endpoints::ExitPosEndpointsModule,
+        exit_to_single_token::ExitToSingleTokenModule, merge_pos::MergePosModule,
+        migrate_pos::MigratePosModule, pending_unbonds::PendingUnbondsModule,
     },
+    quotes::quote_views::{NewPosQuote, QuoteViewsModule},
 };
 use farm::exit_penalty::ExitPenaltyModule;
 use farm_staking::token_attributes::{StakingFarmTokenAttributes, UnbondSftAttributes};
 use multiversx_sc::{
-    codec::Empty,
+    codec::{multi_types::OptionalValue, Empty, NestedDecode},
     imports::ContractBase,
-    types::{BigUint, ManagedAddress, ManagedBuffer, MultiValueEncoded},
+    types::{
+        Address, BigUint, EgldOrEsdtTokenIdentifier, EgldOrEsdtTokenPayment, EsdtTokenPayment,
+        ManagedAddress, ManagedBuffer, ManagedVec, MultiValueEncoded,
+    },
 };
 use multiversx_sc_scenario::{
-    managed_address, managed_biguint, managed_token_id, rust_biguint,
-    testing_framework::TxTokenTransfer, DebugApi,
+    managed_address, managed_biguint, managed_token_id, multiversx_chain_vm::tx_mock::TxResult,
+    rust_biguint, testing_framework::TxTokenTransfer, DebugApi,
 };
+use pair::config::ConfigModule;
+use router::{config::ConfigModule as RouterConfigModule, factory::PairTokens};
 use pos_creator_setup::{PosCreatorSetup, DUAL_YIELD_TOKEN_ID, LP_TOKEN_IDS, TOKEN_IDS};
 use tests_common::{
     farm_staking_setup::STAKING_FARM_TOKEN_ID,
@@ -30,6 +54,38 @@ pub mod router_setup;
 
 pub static SWAP_TOKENS_FIXED_INPUT_FUNC_NAME: &[u8] = b"swapTokensFixedInput";
 
+/// Counts the events logged by the given contract, ignoring the ones of the called contracts
+fn count_events(tx_result: &TxResult, sc_address: &Address, identifier: &[u8]) -> usize {
+    tx_result
+        .result_logs
+        .iter()
+        .filter(|log| log.address.as_bytes() == sc_address.as_bytes())
+        .filter(|log| log.topics[0] == identifier)
+        .count()
+}
+
+/// Sums the input payments of each createPos event logged by the given contract.
+/// The input payments are the first field of the event data, encoded as (token, nonce, amount).
+fn create_pos_event_input_amounts(
+    tx_result: &TxResult,
+    sc_address: &Address,
+) -> Vec<num_bigint::BigUint> {
+    tx_result
+        .result_logs
+        .iter()
+        .filter(|log| log.address.as_bytes() == sc_address.as_bytes())
+        .filter(|log| log.topics[0] == b"createPos")
+        .map(|log| {
+            let input_payments =
+                Vec::<(Vec<u8>, u64, Vec<u8>)>::dep_decode(&mut &log.data[0][..]).unwrap();
+            input_payments
+                .iter()
+                .map(|(_, _, amount)| num_bigint::BigUint::from_bytes_be(amount))
+                .sum()
+        })
+        .collect()
+}
+
 #[test]
 fn full_pos_creator_setup_test() {
     let _ = PosCreatorSetup::new(
@@ -104,23 +160,23 @@ fn enter_lp_through_pos_creator_test() {
 
     // User adds liquidity in pool B-C, using token A
     // Route: All tokens A are swapped to token B
-    // Half of the swap output is then swapped to token C
+    // The optimal part of the swap output is then swapped to token C
     // Add liquidity using the resulted tokens B and C
-    let expected_remaining_third_token = 61_224_488u64;
-    let expected_lp_token = 142_857_142u64;
+    let expected_remaining_second_token = 1u64;
+    let expected_lp_token = 154_700_537u64;
 
     b_mock
         .borrow()
         .check_esdt_balance(&user_addr, TOKEN_IDS[0], &rust_biguint!(0));
-    b_mock
-        .borrow()
-        .check_esdt_balance(&user_addr, TOKEN_IDS[1], &rust_biguint!(0));
-    // Should have tokens remaining after add liquidity
+    // Only rounding dust should remain after add liquidity
     b_mock.borrow().check_esdt_balance(
         &user_addr,
-        TOKEN_IDS[2],
-        &rust_biguint!(expected_remaining_third_token),
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_remaining_second_token),
     );
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[2], &rust_biguint!(0));
     b_mock
         .borrow()
         .check_esdt_balance(&user_addr, LP_TOKEN_IDS[0], &rust_biguint!(0));
@@ -171,8 +227,8 @@ fn enter_lp_through_pos_creator_test() {
         )
         .assert_ok();
 
-    let expected_second_token_amount_from_lp = 166_666_665u64;
-    let expected_third_token_amount_from_lp = 367_346_937u64;
+    let expected_second_token_amount_from_lp = 178_632_793u64;
+    let expected_third_token_amount_from_lp = 401_923_785u64;
 
     b_mock
         .borrow()
@@ -180,12 +236,12 @@ fn enter_lp_through_pos_creator_test() {
     b_mock.borrow().check_esdt_balance(
         &user_addr,
         TOKEN_IDS[1],
-        &rust_biguint!(expected_second_token_amount_from_lp),
+        &rust_biguint!(expected_second_token_amount_from_lp + expected_remaining_second_token),
     );
     b_mock.borrow().check_esdt_balance(
         &user_addr,
         TOKEN_IDS[2],
-        &rust_biguint!(expected_third_token_amount_from_lp + expected_remaining_third_token),
+        &rust_biguint!(expected_third_token_amount_from_lp),
     );
     b_mock
         .borrow()
@@ -214,9 +270,9 @@ fn enter_lp_with_swap_to_second_token_of_the_pair_test() {
     );
 
     // user enter (A, B) pair with token C
-    // Route: token C -> full swap to token B (second token) -> half swap to token A -> add LP to (A, B)
-    let expected_remaining_first_token = 1_600_000u64;
-    let expected_lp_tokens = 39_999_998u64;
+    // Route: token C -> full swap to token B (second token) -> optimal swap to token A -> add LP to (A, B)
+    let expected_remaining_second_token = 3u64;
+    let expected_lp_tokens = 40_832_998u64;
     let first_pair_addr = pos_creator_setup.pair_setups[0]
         .pair_wrapper
         .address_ref()
@@ -255,11 +311,11 @@ fn enter_lp_with_swap_to_second_token_of_the_pair_test() {
                 assert_eq!(output_payments.len(), 2);
                 assert_eq!(
                     output_payments.get(0).token_identifier,
-                    managed_token_id!(TOKEN_IDS[0])
+                    managed_token_id!(TOKEN_IDS[1])
                 );
                 assert_eq!(
                     output_payments.get(0).amount,
-                    managed_biguint!(expected_remaining_first_token)
+                    managed_biguint!(expected_remaining_second_token)
                 );
                 assert_eq!(
                     output_payments.get(1).token_identifier,
@@ -274,10 +330,13 @@ fn enter_lp_with_swap_to_second_token_of_the_pair_test() {
         .assert_ok();
 
     // Check user balance
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[0], &rust_biguint!(0));
     b_mock.borrow().check_esdt_balance(
         &user_addr,
-        TOKEN_IDS[0],
-        &rust_biguint!(expected_remaining_first_token),
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_remaining_second_token),
     );
     b_mock.borrow().check_esdt_balance(
         &user_addr,
@@ -346,20 +405,20 @@ fn enter_lp_and_farm_through_pos_creator() {
 
     // User adds liquidity in pool (A, B), using token C
     // Route: All tokens C are swapped to token A
-    // Half of the swap output is then swapped to token B
+    // The optimal part of the swap output is then swapped to token B
     // Add liquidity using the resulted tokens A and B
-    let expected_remaining_second_token = 3_780_718u64;
-    let expected_farm_token = 43_478_260u64;
+    let expected_remaining_first_token = 1u64;
+    let expected_farm_token = 44_465_934u64;
 
-    b_mock
-        .borrow()
-        .check_esdt_balance(&user_addr, TOKEN_IDS[0], &rust_biguint!(0));
-    // Should have tokens remaining after add liquidity
+    // Only rounding dust should remain after add liquidity
     b_mock.borrow().check_esdt_balance(
         &user_addr,
-        TOKEN_IDS[1],
-        &rust_biguint!(expected_remaining_second_token),
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_remaining_first_token),
     );
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[1], &rust_biguint!(0));
     b_mock
         .borrow()
         .check_esdt_balance(&user_addr, TOKEN_IDS[2], &rust_biguint!(0));
@@ -418,18 +477,18 @@ fn enter_lp_and_farm_through_pos_creator() {
         )
         .assert_ok();
 
-    let expected_first_token_amount_from_lp = 45_454_544u64;
-    let expected_second_token_amount_from_lp = 83_175_801u64;
+    let expected_first_token_amount_from_lp = 46_443_153u64;
+    let expected_second_token_amount_from_lp = 85_145_781u64;
 
     b_mock.borrow().check_esdt_balance(
         &user_addr,
         TOKEN_IDS[0],
-        &rust_biguint!(expected_first_token_amount_from_lp),
+        &rust_biguint!(expected_first_token_amount_from_lp + expected_remaining_first_token),
     );
     b_mock.borrow().check_esdt_balance(
         &user_addr,
         TOKEN_IDS[1],
-        &rust_biguint!(expected_second_token_amount_from_lp + expected_remaining_second_token),
+        &rust_biguint!(expected_second_token_amount_from_lp),
     );
     b_mock
         .borrow()
@@ -497,7 +556,7 @@ fn enter_lp_farm_and_metastaking_through_pos_creator_test() {
 
     // User enters metastaking by adding liquidity in pool (A, B) -> farm (A, B) -> metastaking (A, B)
     // Route: All tokens C are swapped to token A in pool (A, C)
-    // Half of the swap output is then swapped to token B
+    // The optimal part of the swap output is then swapped to token B
     // Add liquidity using the resulted tokens A and B
     let second_pair_addr = pos_creator_setup.pair_setups[1]
         .pair_wrapper
@@ -533,18 +592,18 @@ fn enter_lp_farm_and_metastaking_through_pos_creator_test() {
         )
         .assert_ok();
 
-    let expected_remaining_second_token = 3_780_718u64;
-    let expected_dual_yield_token = 43_478_260u64;
+    let expected_remaining_first_token = 1u64;
+    let expected_dual_yield_token = 44_465_934u64;
 
-    b_mock
-        .borrow()
-        .check_esdt_balance(&user_addr, TOKEN_IDS[0], &rust_biguint!(0));
-    // Should have tokens remaining after add liquidity
+    // Only rounding dust should remain after add liquidity
     b_mock.borrow().check_esdt_balance(
         &user_addr,
-        TOKEN_IDS[1],
-        &rust_biguint!(expected_remaining_second_token),
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_remaining_first_token),
     );
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[1], &rust_biguint!(0));
     b_mock
         .borrow()
         .check_esdt_balance(&user_addr, TOKEN_IDS[2], &rust_biguint!(0));
@@ -616,22 +675,25 @@ fn enter_lp_farm_and_metastaking_through_pos_creator_test() {
                     managed_address!(&ms_addr),
                     1u32.into(),
                     1u32.into(),
+                    OptionalValue::None,
                 );
             },
         )
         .assert_ok();
 
-    let expected_second_token_amount_from_lp = 83_175_801u64;
-    let expected_staking_farm_token_amount = 45_454_544u64;
+    let expected_second_token_amount_from_lp = 85_145_781u64;
+    let expected_staking_farm_token_amount = 46_443_153u64;
 
-    // Passes through unbond contract, so balance should be 0 until tokens are unbonded
-    b_mock
-        .borrow()
-        .check_esdt_balance(&user_addr, TOKEN_IDS[0], &rust_biguint!(0));
+    // Passes through unbond contract, so balance should not change until tokens are unbonded
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_remaining_first_token),
+    );
     b_mock.borrow().check_esdt_balance(
         &user_addr,
         TOKEN_IDS[1],
-        &rust_biguint!(expected_second_token_amount_from_lp + expected_remaining_second_token),
+        &rust_biguint!(expected_second_token_amount_from_lp),
     );
     b_mock
         .borrow()
@@ -725,7 +787,7 @@ fn enter_metastaking_with_merge_through_pos_creator_test() {
         .address_ref()
         .clone();
     let ms_addr = pos_creator_setup.ms_wrapper.address_ref().clone();
-    let expected_dual_yield_tokens = 23_255_813u64;
+    let expected_dual_yield_tokens = 23_532_630u64;
     b_mock
         .borrow_mut()
         .execute_esdt_transfer(
@@ -752,12 +814,14 @@ fn enter_metastaking_with_merge_through_pos_creator_test() {
                     swap_operations,
                 );
 
+                // The optimal swap leaves no remaining tokens for this input
+                assert_eq!(output_payments.len(), 1);
                 assert_eq!(
-                    output_payments.get(1).token_identifier,
+                    output_payments.get(0).token_identifier,
                     managed_token_id!(DUAL_YIELD_TOKEN_ID)
                 );
                 assert_eq!(
-                    output_payments.get(1).amount,
+                    output_payments.get(0).amount,
                     managed_biguint!(expected_dual_yield_tokens)
                 );
             },
@@ -766,7 +830,7 @@ fn enter_metastaking_with_merge_through_pos_creator_test() {
 
     // Enter metastaking again, with the previous dual yield tokens, as additional payments
     // Use the same input LP token amount as the one obtained in the first operation
-    let exact_input_amount = 23_255_813u64;
+    let exact_input_amount = 23_532_630u64;
     b_mock.borrow_mut().set_esdt_balance(
         &user_addr,
         LP_TOKEN_IDS[0],
@@ -1213,7 +1277,7 @@ fn create_farm_from_lp_test() {
         )
         .assert_ok();
 
-    let expected_lp_tokens = 43_478_260u64;
+    let expected_lp_tokens = 44_465_934u64;
     b_mock.borrow().check_esdt_balance(
         &user_addr,
         LP_TOKEN_IDS[0],
@@ -1334,10 +1398,10 @@ fn enter_lp_through_pos_creator_long_swap_path_test() {
     // Route: All tokens A are swapped to token B
     // All tokens B are swapped to C
     // All tokens C are swapped to B
-    // Half of the swap output is then swapped to token C
+    // The optimal part of the swap output is then swapped to token C
     // Add liquidity using the resulted tokens B and C
-    // Same amount, as fees are set to 0
-    let expected_lp_token = 142_857_142u64;
+    // Same amount as the direct route (except rounding), as fees are set to 0
+    let expected_lp_token = 154_700_536u64;
     b_mock.borrow().check_esdt_balance(
         &user_addr,
         LP_TOKEN_IDS[2],
@@ -1426,10 +1490,10 @@ fn try_create_lp_pos_from_same_lp_token_test() {
     // Route: All tokens A are swapped to token B
     // All tokens B are swapped to C
     // All tokens C are swapped to B
-    // Half of the swap output is then swapped to token C
+    // The optimal part of the swap output is then swapped to token C
     // Add liquidity using the resulted tokens B and C
-    // Same amount, as fees are set to 0
-    let expected_lp_token = 142_857_142u64;
+    // Same amount as the direct route (except rounding), as fees are set to 0
+    let expected_lp_token = 154_700_536u64;
     b_mock.borrow().check_esdt_balance(
         &user_addr,
         LP_TOKEN_IDS[2],
@@ -1543,10 +1607,10 @@ fn try_create_lp_pos_from_different_lp_token_test() {
     // Route: All tokens A are swapped to token B
     // All tokens B are swapped to C
     // All tokens C are swapped to B
-    // Half of the swap output is then swapped to token C
+    // The optimal part of the swap output is then swapped to token C
     // Add liquidity using the resulted tokens B and C
-    // Same amount, as fees are set to 0
-    let expected_lp_token = 142_857_142u64;
+    // Same amount as the direct route (except rounding), as fees are set to 0
+    let expected_lp_token = 154_700_536u64;
     b_mock.borrow().check_esdt_balance(
         &user_addr,
         LP_TOKEN_IDS[2],
@@ -1680,6 +1744,7 @@ fn user_exit_metastaking_with_penalty_test() {
                     managed_address!(&ms_addr),
                     1u32.into(),
                     1u32.into(),
+                    OptionalValue::None,
                 );
             },
         )
@@ -1784,6 +1849,7 @@ fn user_exit_metastaking_without_penalty_test() {
                     managed_address!(&ms_addr),
                     1u32.into(),
                     1u32.into(),
+                    OptionalValue::None,
                 );
             },
         )
@@ -1806,3 +1872,2966 @@ fn user_exit_metastaking_without_penalty_test() {
         Some(&UnbondSftAttributes { unlock_epoch: 5 }),
     );
 }
+
+#[test]
+fn enter_lp_with_fees_leaves_only_dust_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+    let owner = pos_creator_setup.farm_setup.owner;
+
+    // set a 0.3% fee for the (B, C) pair
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pair_setups[2].pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.total_fee_percent().set(300u64);
+            },
+        )
+        .assert_ok();
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_second_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(user_second_token_balance),
+    );
+
+    // user enter (B, C) pair with token B
+    // Only the optimal part of token B is swapped to token C,
+    // so the remaining tokens should be negligible
+    let leftover_tolerance = 10u64;
+    let expected_lp_tokens = 48_735_525u64;
+    let third_pair_addr = pos_creator_setup.pair_setups[2]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[1], // Token B
+            0,
+            &rust_biguint!(user_second_token_balance),
+            |sc| {
+                let output_payments = sc.create_lp_pos_from_single_token(
+                    managed_address!(&third_pair_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+
+                let last_index = output_payments.len() - 1;
+                for (i, payment) in output_payments.iter().enumerate() {
+                    if i == last_index {
+                        assert_eq!(
+                            payment.token_identifier,
+                            managed_token_id!(LP_TOKEN_IDS[2])
+                        );
+                        assert_eq!(payment.amount, managed_biguint!(expected_lp_tokens));
+                    } else {
+                        assert!(payment.amount <= leftover_tolerance);
+                    }
+                }
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        LP_TOKEN_IDS[2],
+        &rust_biguint!(expected_lp_tokens),
+    );
+}
+
+#[test]
+fn enter_farm_with_discovered_route_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_third_token_balance = 600_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[2],
+        &rust_biguint!(user_third_token_balance),
+    );
+
+    let farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    let enter_farm = |discover_routes: bool| {
+        b_mock.borrow_mut().execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[2], // Token C
+            0,
+            &rust_biguint!(user_third_token_balance),
+            |sc| {
+                let options = CreatePosOptions {
+                    destination: PositionDestination::Caller,
+                    opt_max_price_deviation_bps: None,
+                    discover_routes,
+                };
+                let _ = sc.create_farm_pos_from_single_token_with_options(
+                    managed_address!(&farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    options,
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+    };
+
+    // routes are only discovered when requested
+    enter_farm(false).assert_user_error("The output token identifier is not part of the LP");
+
+    // user enter (A, B) farm with token C, without giving a swap route
+    // Candidates: C -> A through (A, C), or C -> B through (B, C)
+    // Swapping to token A gives more value, so the result is the same as with the explicit route
+    let expected_remaining_first_token = 1u64;
+    let expected_farm_token = 44_465_934u64;
+    enter_farm(true).assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_remaining_first_token),
+    );
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[1], &rust_biguint!(0));
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[2], &rust_biguint!(0));
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(expected_farm_token),
+        None,
+    );
+}
+
+#[test]
+fn enter_farm_from_multiple_tokens_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 100_000_000u64;
+    let user_third_token_balance = 600_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[2],
+        &rust_biguint!(user_third_token_balance),
+    );
+
+    let farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    let payments = vec![
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[0].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_first_token_balance),
+        },
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[2].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_third_token_balance),
+        },
+    ];
+
+    // one route must be given for each payment
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &payments,
+            |sc| {
+                let mut swap_routes = MultiValueEncoded::new();
+                swap_routes.push(ManagedVec::new());
+
+                let _ = sc.create_farm_pos_from_multiple_tokens(
+                    managed_address!(&farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    swap_routes,
+                );
+            },
+        )
+        .assert_user_error("Invalid number of swap routes");
+
+    // routes are only discovered when requested
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &payments,
+            |sc| {
+                let _ = sc.create_farm_pos_from_multiple_tokens(
+                    managed_address!(&farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_user_error("The output token identifier is not part of the LP");
+
+    // user enter (A, B) farm with tokens A and C, letting the contract discover the routes
+    // Token C is swapped to token A, through the (A, C) pair
+    // The excess of token A is then swapped to token B, and liquidity is added once
+    let expected_remaining_first_token = 2u64;
+    let expected_farm_token = 91_287_811u64;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &payments,
+            |sc| {
+                let options = CreatePosOptions {
+                    destination: PositionDestination::Caller,
+                    opt_max_price_deviation_bps: None,
+                    discover_routes: true,
+                };
+                let output_payments = sc.create_farm_pos_from_multiple_tokens_with_options(
+                    managed_address!(&farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    options,
+                    MultiValueEncoded::new(),
+                );
+
+                assert_eq!(output_payments.len(), 2);
+                assert_eq!(
+                    output_payments.get(1).amount,
+                    managed_biguint!(expected_farm_token)
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_remaining_first_token),
+    );
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[1], &rust_biguint!(0));
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[2], &rust_biguint!(0));
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(expected_farm_token),
+        None,
+    );
+}
+
+#[test]
+fn enter_farm_staking_with_hub_token_route_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+    let owner = pos_creator_setup.farm_setup.owner;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_third_token_balance = 300_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[2],
+        &rust_biguint!(user_third_token_balance),
+    );
+
+    // remove the (A, C) pair from the router, so token C can only reach token A through a hub
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.router_setup.router_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let _ = sc.pair_map().remove(&PairTokens {
+                    first_token_id: managed_token_id!(TOKEN_IDS[0]),
+                    second_token_id: managed_token_id!(TOKEN_IDS[2]),
+                });
+            },
+        )
+        .assert_ok();
+
+    let farm_staking_addr = pos_creator_setup.fs_wrapper.address_ref().clone();
+    let enter_farm_staking = |opt_max_price_deviation_bps: Option<u64>| {
+        b_mock.borrow_mut().execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[2], // Token C
+            0,
+            &rust_biguint!(user_third_token_balance),
+            |sc| {
+                let options = CreatePosOptions {
+                    destination: PositionDestination::Caller,
+                    opt_max_price_deviation_bps,
+                    discover_routes: true,
+                };
+                let _ = sc.create_farm_staking_pos_from_single_token_with_options(
+                    managed_address!(&farm_staking_addr),
+                    1u32.into(),
+                    options,
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+    };
+
+    // no route without hub tokens
+    enter_farm_staking(None).assert_user_error("Invalid swap output token identifier");
+
+    // token B is used as hub, e.g. WEGLD or USDC on mainnet
+    // the C -> B hop in the (B, C) pair deviates ~9.09% from the safe price
+    let third_pair_addr = pos_creator_setup.pair_setups[2]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut hub_tokens = MultiValueEncoded::new();
+                hub_tokens.push(managed_token_id!(TOKEN_IDS[1]));
+                sc.add_hub_tokens(hub_tokens);
+
+                sc.set_max_price_deviation(managed_address!(&third_pair_addr), 100);
+            },
+        )
+        .assert_ok();
+
+    // the hop pair's config is used by default
+    enter_farm_staking(None).assert_user_error("Price deviation too high on discovered route swap");
+
+    // user override, used for every hop
+    // C -> B through (B, C): 300_000_000 C -> 90_909_090 B
+    // B -> A through (A, B): 90_909_090 B -> 43_478_260 A
+    let expected_farm_staking_token = 43_478_260u64;
+    enter_farm_staking(Some(1_000)).assert_ok();
+
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[2], &rust_biguint!(0));
+    b_mock
+        .borrow()
+        .check_nft_balance::<StakingFarmTokenAttributes<DebugApi>>(
+            &user_addr,
+            STAKING_FARM_TOKEN_ID,
+            1,
+            &rust_biguint!(expected_farm_staking_token),
+            None,
+        );
+}
+
+#[test]
+fn enter_farm_max_price_deviation_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+    let owner = pos_creator_setup.farm_setup.owner;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_third_token_balance = 600_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[2],
+        &rust_biguint!(user_third_token_balance),
+    );
+
+    // the zap swap in the (A, B) pair deviates ~4.26% from the safe price
+    let first_pair_addr = pos_creator_setup.pair_setups[0]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_max_price_deviation(managed_address!(&first_pair_addr), 100);
+            },
+        )
+        .assert_ok();
+
+    let second_pair_addr = pos_creator_setup.pair_setups[1]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    let farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    let enter_farm = |opt_max_price_deviation_bps: Option<u64>| {
+        b_mock.borrow_mut().execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[2], // Token C
+            0,
+            &rust_biguint!(user_third_token_balance),
+            |sc| {
+                let mut swap_operations = MultiValueEncoded::new();
+                let swap_operation: SwapOperationType<DebugApi> = (
+                    managed_address!(&second_pair_addr),
+                    ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    managed_token_id!(TOKEN_IDS[0]), // Want token A
+                    BigUint::from(1u64),
+                )
+                    .into();
+                swap_operations.push(swap_operation);
+                let options = CreatePosOptions {
+                    destination: PositionDestination::Caller,
+                    opt_max_price_deviation_bps,
+                    discover_routes: false,
+                };
+                let _ = sc.create_farm_pos_from_single_token_with_options(
+                    managed_address!(&farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    options,
+                    swap_operations,
+                );
+            },
+        )
+    };
+
+    // pair config is used by default
+    enter_farm(None).assert_user_error("Price deviation too high on optimal zap swap");
+
+    // user override
+    enter_farm(Some(500)).assert_ok();
+
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[2], &rust_biguint!(0));
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(44_465_934u64),
+        None,
+    );
+}
+
+#[test]
+fn exit_lp_pos_to_single_token_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_second_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(user_second_token_balance),
+    );
+
+    // user enter (B, C) pair with token B
+    let expected_remaining_second_token = 1u64;
+    let expected_lp_tokens = 48_808_847u64;
+    let third_pair_addr = pos_creator_setup.pair_setups[2]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[1], // Token B
+            0,
+            &rust_biguint!(user_second_token_balance),
+            |sc| {
+                let _ = sc.create_lp_pos_from_single_token(
+                    managed_address!(&third_pair_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        LP_TOKEN_IDS[2],
+        &rust_biguint!(expected_lp_tokens),
+    );
+
+    // Removed liquidity: 51_191_150 tokens B and 139_612_229 tokens C
+    // Tokens C are swapped back to 48_808_847 tokens B
+    let expected_second_token_amount = 99_999_997u64;
+
+    // min amount out not reached
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            LP_TOKEN_IDS[2],
+            0,
+            &rust_biguint!(expected_lp_tokens),
+            |sc| {
+                let _ = sc.exit_lp_pos_to_single_token(
+                    managed_address!(&third_pair_addr),
+                    EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[1])),
+                    managed_biguint!(expected_second_token_amount + 1),
+                );
+            },
+        )
+        .assert_user_error("Slippage exceeded");
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            LP_TOKEN_IDS[2],
+            0,
+            &rust_biguint!(expected_lp_tokens),
+            |sc| {
+                let (output_tokens, other_payments) = sc
+                    .exit_lp_pos_to_single_token(
+                        managed_address!(&third_pair_addr),
+                        EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[1])),
+                        managed_biguint!(expected_second_token_amount),
+                    )
+                    .into_tuple();
+
+                assert_eq!(
+                    output_tokens.token_identifier,
+                    EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[1]))
+                );
+                assert_eq!(output_tokens.amount, managed_biguint!(expected_second_token_amount));
+                assert!(other_payments.is_empty());
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_second_token_amount + expected_remaining_second_token),
+    );
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[2], &rust_biguint!(0));
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, LP_TOKEN_IDS[2], &rust_biguint!(0));
+    b_mock.borrow().check_esdt_balance(
+        pos_creator_setup.pos_creator_wrapper.address_ref(),
+        TOKEN_IDS[2],
+        &rust_biguint!(0),
+    );
+}
+
+#[test]
+fn migrate_farm_pos_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // user enter (A, B) farm with token A
+    let first_farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    let second_farm_addr = pos_creator_setup.farm_setup.farm_wrappers[1]
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[0], // Token A
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let _ = sc.create_farm_pos_from_single_token(
+                    managed_address!(&first_farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_ok();
+
+    let first_farm_tokens = 48_808_847u64;
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(first_farm_tokens),
+        None,
+    );
+
+    // Removed liquidity: 51_191_150 tokens A and 93_074_819 tokens B
+    // Tokens B are swapped to 48_808_846 tokens A
+    // The optimal part of tokens A is then swapped to tokens C
+    // Add liquidity in pool (A, C) and enter the (A, C) farm
+    let second_farm_tokens = 48_808_845u64;
+    let expected_remaining_first_token = 1u64;
+
+    // min amount out not reached
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            FARM_TOKEN_ID[0],
+            1,
+            &rust_biguint!(first_farm_tokens),
+            |sc| {
+                let _ = sc.migrate_position(
+                    managed_address!(&first_farm_addr),
+                    managed_address!(&second_farm_addr),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(second_farm_tokens + 1),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_user_error("Slippage exceeded");
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            FARM_TOKEN_ID[0],
+            1,
+            &rust_biguint!(first_farm_tokens),
+            |sc| {
+                let _ = sc.migrate_position(
+                    managed_address!(&first_farm_addr),
+                    managed_address!(&second_farm_addr),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(second_farm_tokens),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(0),
+        None,
+    );
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[1],
+        1,
+        &rust_biguint!(second_farm_tokens),
+        None,
+    );
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_remaining_first_token),
+    );
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[1], &rust_biguint!(0));
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[2], &rust_biguint!(0));
+    b_mock.borrow().check_esdt_balance(
+        pos_creator_setup.pos_creator_wrapper.address_ref(),
+        TOKEN_IDS[0],
+        &rust_biguint!(0),
+    );
+}
+
+#[test]
+fn migrate_lp_pos_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // user enter (A, B) pair with token A
+    let first_pair_addr = pos_creator_setup.pair_setups[0]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    let second_farm_addr = pos_creator_setup.farm_setup.farm_wrappers[1]
+        .address_ref()
+        .clone();
+    let farm_staking_addr = pos_creator_setup.fs_wrapper.address_ref().clone();
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[0], // Token A
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let _ = sc.create_lp_pos_from_single_token(
+                    managed_address!(&first_pair_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_ok();
+
+    // same LP amount as the farm position of migrate_farm_pos_test
+    let lp_tokens = 48_808_847u64;
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, LP_TOKEN_IDS[0], &rust_biguint!(lp_tokens));
+
+    // farm staking positions are only released after the unbond period
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            LP_TOKEN_IDS[0],
+            0,
+            &rust_biguint!(lp_tokens),
+            |sc| {
+                let _ = sc.migrate_position(
+                    managed_address!(&farm_staking_addr),
+                    managed_address!(&second_farm_addr),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_user_error("Farm staking positions cannot be migrated before the unbond period");
+
+    // Same swaps as migrate_farm_pos_test, without exiting a farm first
+    let second_farm_tokens = 48_808_845u64;
+    let expected_remaining_first_token = 1u64;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            LP_TOKEN_IDS[0],
+            0,
+            &rust_biguint!(lp_tokens),
+            |sc| {
+                let _ = sc.migrate_position(
+                    managed_address!(&first_pair_addr),
+                    managed_address!(&second_farm_addr),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(second_farm_tokens),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, LP_TOKEN_IDS[0], &rust_biguint!(0));
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[1],
+        1,
+        &rust_biguint!(second_farm_tokens),
+        None,
+    );
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_remaining_first_token),
+    );
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[1], &rust_biguint!(0));
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[2], &rust_biguint!(0));
+}
+
+#[test]
+fn migrate_metastaking_pos_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 200_000_000u64;
+    let user_second_token_balance = 400_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(user_second_token_balance),
+    );
+
+    let payments = vec![
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[0].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_first_token_balance),
+        },
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[1].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_second_token_balance),
+        },
+    ];
+
+    // user enter (A, B) metastaking farm with (A, B) tokens
+    let ms_addr = pos_creator_setup.ms_wrapper.address_ref().clone();
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &payments,
+            |sc| {
+                let _ = sc.create_metastaking_pos_from_two_tokens(
+                    managed_address!(&ms_addr),
+                    1u32.into(),
+                    1u32.into(),
+                );
+            },
+        )
+        .assert_ok();
+
+    // user migrates to the (A, B) farm, only the tokens B enter the new position
+    let expected_dual_yield_tokens = 200_000_000u64;
+    let farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            DUAL_YIELD_TOKEN_ID,
+            1,
+            &rust_biguint!(expected_dual_yield_tokens),
+            |sc| {
+                let output_payments = sc.migrate_position(
+                    managed_address!(&ms_addr),
+                    managed_address!(&farm_addr),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+
+                let new_position = output_payments.get(output_payments.len() - 1);
+                assert_eq!(
+                    new_position.token_identifier,
+                    managed_token_id!(FARM_TOKEN_ID[0])
+                );
+                for payment in &output_payments {
+                    assert!(payment.token_identifier != managed_token_id!(STAKING_FARM_TOKEN_ID));
+                }
+            },
+        )
+        .assert_ok();
+
+    // the contract holds the unbond tokens of the staked tokens A
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        STAKING_FARM_TOKEN_ID,
+        2,
+        &rust_biguint!(0),
+        None,
+    );
+    b_mock.borrow().check_nft_balance(
+        pos_creator_setup.pos_creator_wrapper.address_ref(),
+        STAKING_FARM_TOKEN_ID,
+        2,
+        &rust_biguint!(user_first_token_balance),
+        Some(&UnbondSftAttributes { unlock_epoch: 5 }),
+    );
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let pending_unbonds = sc.pending_unbonds(&managed_address!(&user_addr)).get();
+            assert_eq!(pending_unbonds.len(), 1);
+
+            let pending_unbond = pending_unbonds.get(0);
+            assert_eq!(pending_unbond.unlock_epoch, 5);
+            assert_eq!(
+                pending_unbond.unbond_tokens.amount,
+                managed_biguint!(user_first_token_balance)
+            );
+        })
+        .assert_ok();
+
+    b_mock.borrow_mut().set_block_epoch(5);
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let _ = sc.claim_unbonded_tokens(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+}
+
+#[test]
+fn create_exit_and_migrate_pos_events_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    let pos_creator_addr = pos_creator_setup.pos_creator_wrapper.address_ref().clone();
+    let first_pair_addr = pos_creator_setup.pair_setups[0]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    let second_farm_addr = pos_creator_setup.farm_setup.farm_wrappers[1]
+        .address_ref()
+        .clone();
+
+    // the optimal swap to token B is logged before the new position
+    let tx_result = b_mock.borrow_mut().execute_esdt_transfer(
+        &user_addr,
+        &pos_creator_setup.pos_creator_wrapper,
+        TOKEN_IDS[0], // Token A
+        0,
+        &rust_biguint!(user_first_token_balance),
+        |sc| {
+            let _ = sc.create_lp_pos_from_single_token(
+                managed_address!(&first_pair_addr),
+                1u32.into(),
+                1u32.into(),
+                MultiValueEncoded::new(),
+            );
+        },
+    );
+    tx_result.assert_ok();
+    assert_eq!(count_events(&tx_result, &pos_creator_addr, b"swap"), 1);
+    assert_eq!(count_events(&tx_result, &pos_creator_addr, b"createPos"), 1);
+    assert_eq!(count_events(&tx_result, &pos_creator_addr, b"exitPos"), 0);
+
+    // same amounts as migrate_lp_pos_test
+    let lp_tokens = 48_808_847u64;
+    let half_lp_tokens = lp_tokens / 2;
+
+    let tx_result = b_mock.borrow_mut().execute_esdt_transfer(
+        &user_addr,
+        &pos_creator_setup.pos_creator_wrapper,
+        LP_TOKEN_IDS[0],
+        0,
+        &rust_biguint!(half_lp_tokens),
+        |sc| {
+            let _ = sc.exit_lp_pos(
+                managed_address!(&first_pair_addr),
+                managed_biguint!(1),
+                managed_biguint!(1),
+            );
+        },
+    );
+    tx_result.assert_ok();
+    assert_eq!(count_events(&tx_result, &pos_creator_addr, b"swap"), 0);
+    assert_eq!(count_events(&tx_result, &pos_creator_addr, b"createPos"), 0);
+    assert_eq!(count_events(&tx_result, &pos_creator_addr, b"exitPos"), 1);
+
+    // the old position is logged as exited, then the new one as created
+    let tx_result = b_mock.borrow_mut().execute_esdt_transfer(
+        &user_addr,
+        &pos_creator_setup.pos_creator_wrapper,
+        LP_TOKEN_IDS[0],
+        0,
+        &rust_biguint!(lp_tokens - half_lp_tokens),
+        |sc| {
+            let _ = sc.migrate_position(
+                managed_address!(&first_pair_addr),
+                managed_address!(&second_farm_addr),
+                managed_biguint!(1),
+                managed_biguint!(1),
+                managed_biguint!(1),
+                managed_biguint!(1),
+                managed_biguint!(1),
+                OptionalValue::None,
+            );
+        },
+    );
+    tx_result.assert_ok();
+    assert_eq!(count_events(&tx_result, &pos_creator_addr, b"exitPos"), 1);
+    assert_eq!(count_events(&tx_result, &pos_creator_addr, b"createPos"), 1);
+
+    let exit_event_index = tx_result
+        .result_logs
+        .iter()
+        .position(|log| log.topics[0] == b"exitPos")
+        .unwrap();
+    let create_event_index = tx_result
+        .result_logs
+        .iter()
+        .position(|log| log.topics[0] == b"createPos")
+        .unwrap();
+    assert!(exit_event_index < create_event_index);
+}
+
+#[test]
+fn exit_metastaking_and_claim_unbonded_tokens_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 200_000_000u64;
+    let user_second_token_balance = 400_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(user_second_token_balance),
+    );
+
+    let payments = vec![
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[0].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_first_token_balance),
+        },
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[1].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_second_token_balance),
+        },
+    ];
+
+    // user enter (A, B) metastaking farm with (A, B) tokens
+    let ms_addr = pos_creator_setup.ms_wrapper.address_ref().clone();
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &payments,
+            |sc| {
+                let _ = sc.create_metastaking_pos_from_two_tokens(
+                    managed_address!(&ms_addr),
+                    1u32.into(),
+                    1u32.into(),
+                );
+            },
+        )
+        .assert_ok();
+
+    // user exit metastaking pos, the contract holds the unbond tokens
+    let expected_dual_yield_tokens = 200_000_000u64;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            DUAL_YIELD_TOKEN_ID,
+            1,
+            &rust_biguint!(expected_dual_yield_tokens),
+            |sc| {
+                let _ = sc.exit_metastaking_pos_endpoint(
+                    managed_address!(&ms_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    OptionalValue::Some(true),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(user_second_token_balance),
+    );
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        STAKING_FARM_TOKEN_ID,
+        2,
+        &rust_biguint!(0),
+        None,
+    );
+    b_mock.borrow().check_nft_balance(
+        pos_creator_setup.pos_creator_wrapper.address_ref(),
+        STAKING_FARM_TOKEN_ID,
+        2,
+        &rust_biguint!(user_first_token_balance),
+        Some(&UnbondSftAttributes { unlock_epoch: 5 }),
+    );
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let pending_unbonds = sc.pending_unbonds(&managed_address!(&user_addr)).get();
+            assert_eq!(pending_unbonds.len(), 1);
+
+            let pending_unbond = pending_unbonds.get(0);
+            assert_eq!(pending_unbond.unlock_epoch, 5);
+            assert_eq!(
+                pending_unbond.unbond_tokens.amount,
+                managed_biguint!(user_first_token_balance)
+            );
+        })
+        .assert_ok();
+
+    // unbond period not passed yet
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let _ = sc.claim_unbonded_tokens(OptionalValue::None);
+            },
+        )
+        .assert_user_error("No unbonded tokens to claim");
+
+    b_mock.borrow_mut().set_block_epoch(5);
+
+    let tx_result = b_mock.borrow_mut().execute_tx(
+        &user_addr,
+        &pos_creator_setup.pos_creator_wrapper,
+        &rust_biguint!(0),
+        |sc| {
+            let _ = sc.claim_unbonded_tokens(OptionalValue::None);
+        },
+    );
+    tx_result.assert_ok();
+    assert_eq!(
+        count_events(
+            &tx_result,
+            pos_creator_setup.pos_creator_wrapper.address_ref(),
+            b"claimUnbonded"
+        ),
+        1
+    );
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            assert!(sc.pending_unbonds(&managed_address!(&user_addr)).is_empty());
+        })
+        .assert_ok();
+}
+
+#[test]
+fn quote_create_and_exit_lp_pos_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_second_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(user_second_token_balance),
+    );
+
+    // Same amounts as in exit_lp_pos_to_single_token_test
+    let expected_lp_tokens = 48_808_847u64;
+    let expected_remaining_second_token = 1u64;
+    let expected_exit_amount = 99_999_997u64;
+    let expected_price_impact_bps = 465u64;
+    let third_pair_addr = pos_creator_setup.pair_setups[2]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let quote = sc.quote_create_lp_pos_from_single_token(
+                managed_address!(&third_pair_addr),
+                EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[1])),
+                managed_biguint!(user_second_token_balance),
+                MultiValueEncoded::new(),
+            );
+
+            assert_eq!(
+                quote.lp_tokens.token_identifier,
+                managed_token_id!(LP_TOKEN_IDS[2])
+            );
+            assert_eq!(quote.lp_tokens.amount, managed_biguint!(expected_lp_tokens));
+            assert_eq!(
+                quote.first_tokens_remaining.amount,
+                managed_biguint!(expected_remaining_second_token)
+            );
+            assert_eq!(quote.second_tokens_remaining.amount, managed_biguint!(0));
+            assert_eq!(quote.price_impact_bps, expected_price_impact_bps);
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[1], // Token B
+            0,
+            &rust_biguint!(user_second_token_balance),
+            |sc| {
+                let _ = sc.create_lp_pos_from_single_token(
+                    managed_address!(&third_pair_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        LP_TOKEN_IDS[2],
+        &rust_biguint!(expected_lp_tokens),
+    );
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let quote = sc.quote_exit_lp_pos_to_single_token(
+                managed_address!(&third_pair_addr),
+                managed_biguint!(expected_lp_tokens),
+                EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[1])),
+            );
+
+            assert_eq!(
+                quote.output_tokens.amount,
+                managed_biguint!(expected_exit_amount)
+            );
+            assert_eq!(quote.price_impact_bps, expected_price_impact_bps);
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            LP_TOKEN_IDS[2],
+            0,
+            &rust_biguint!(expected_lp_tokens),
+            |sc| {
+                let _ = sc.exit_lp_pos_to_single_token(
+                    managed_address!(&third_pair_addr),
+                    EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[1])),
+                    managed_biguint!(expected_exit_amount),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_exit_amount + expected_remaining_second_token),
+    );
+}
+
+#[test]
+fn quote_create_from_two_tokens_and_exit_through_same_pair_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+    let owner = pos_creator_setup.farm_setup.owner;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 100_000_000u64;
+    let user_second_token_balance = 300_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(user_second_token_balance),
+    );
+
+    let first_pair_addr = pos_creator_setup.pair_setups[0]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    let expected_lp_tokens = 100_000_000u64;
+    let expected_remaining_second_token = 100_000_000u64;
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let quote = sc.quote_create_lp_pos_from_two_tokens(
+                managed_address!(&first_pair_addr),
+                managed_biguint!(user_first_token_balance),
+                managed_biguint!(user_second_token_balance),
+            );
+
+            assert_eq!(quote.lp_tokens.amount, managed_biguint!(expected_lp_tokens));
+            assert_eq!(quote.first_tokens_remaining.amount, managed_biguint!(0));
+            assert_eq!(
+                quote.second_tokens_remaining.amount,
+                managed_biguint!(expected_remaining_second_token)
+            );
+            assert_eq!(quote.price_impact_bps, 0);
+        })
+        .assert_ok();
+
+    let payments = vec![
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[0].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_first_token_balance),
+        },
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[1].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_second_token_balance),
+        },
+    ];
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &payments,
+            |sc| {
+                let _ = sc.create_lp_pos_from_two_tokens(
+                    managed_address!(&first_pair_addr),
+                    1u32.into(),
+                    1u32.into(),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        LP_TOKEN_IDS[0],
+        &rust_biguint!(expected_lp_tokens),
+    );
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_remaining_second_token),
+    );
+
+    // token C can only be reached from token A through the hub token B
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.router_setup.router_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let _ = sc.pair_map().remove(&PairTokens {
+                    first_token_id: managed_token_id!(TOKEN_IDS[0]),
+                    second_token_id: managed_token_id!(TOKEN_IDS[2]),
+                });
+            },
+        )
+        .assert_ok();
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut hub_tokens = MultiValueEncoded::new();
+                hub_tokens.push(managed_token_id!(TOKEN_IDS[1]));
+                sc.add_hub_tokens(hub_tokens);
+            },
+        )
+        .assert_ok();
+
+    // Removed liquidity: 100_000_000 tokens A and 200_000_000 tokens B
+    // A -> B through the exited (A, B) pair: 181_818_181 B, then B -> C: 461_538_459 C
+    // B -> C through the (B, C) pair left by the previous hop: 367_408_907 C
+    let expected_exit_amount = 828_947_366u64;
+    let expected_price_impact_bps = 2_307u64;
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let quote = sc.quote_exit_lp_pos_to_single_token(
+                managed_address!(&first_pair_addr),
+                managed_biguint!(expected_lp_tokens),
+                EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[2])),
+            );
+
+            assert_eq!(
+                quote.output_tokens.amount,
+                managed_biguint!(expected_exit_amount)
+            );
+            assert_eq!(quote.price_impact_bps, expected_price_impact_bps);
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            LP_TOKEN_IDS[0],
+            0,
+            &rust_biguint!(expected_lp_tokens),
+            |sc| {
+                let _ = sc.exit_lp_pos_to_single_token(
+                    managed_address!(&first_pair_addr),
+                    EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[2])),
+                    managed_biguint!(expected_exit_amount),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[2],
+        &rust_biguint!(expected_exit_amount),
+    );
+}
+
+#[test]
+fn quote_multiple_tokens_and_metastaking_pos_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    // Same amounts as in enter_farm_from_multiple_tokens_test
+    let farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let mut payments = ManagedVec::new();
+            payments.push(EgldOrEsdtTokenPayment::new(
+                EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[0])),
+                0,
+                managed_biguint!(100_000_000u64),
+            ));
+            payments.push(EgldOrEsdtTokenPayment::new(
+                EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[2])),
+                0,
+                managed_biguint!(600_000_000u64),
+            ));
+            let quote = sc.quote_create_farm_pos_from_multiple_tokens(
+                managed_address!(&farm_addr),
+                payments,
+                MultiValueEncoded::new(),
+            );
+
+            assert_eq!(quote.lp_tokens.amount, managed_biguint!(91_287_811u64));
+            assert_eq!(quote.first_tokens_remaining.amount, managed_biguint!(2u64));
+            assert_eq!(quote.second_tokens_remaining.amount, managed_biguint!(0));
+            assert_eq!(quote.price_impact_bps, 909);
+        })
+        .assert_ok();
+
+    // Same amounts as in exit_metastaking_and_claim_unbonded_tokens_test
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 200_000_000u64;
+    let user_second_token_balance = 400_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(user_second_token_balance),
+    );
+
+    let ms_addr = pos_creator_setup.ms_wrapper.address_ref().clone();
+    let expected_lp_farm_tokens = 200_000_000u64;
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let quote = sc.quote_create_metastaking_pos_from_two_tokens(
+                managed_address!(&ms_addr),
+                managed_biguint!(user_first_token_balance),
+                managed_biguint!(user_second_token_balance),
+            );
+
+            assert_eq!(
+                quote.lp_tokens.amount,
+                managed_biguint!(expected_lp_farm_tokens)
+            );
+            assert_eq!(quote.first_tokens_remaining.amount, managed_biguint!(0));
+            assert_eq!(quote.second_tokens_remaining.amount, managed_biguint!(0));
+        })
+        .assert_ok();
+
+    let payments = vec![
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[0].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_first_token_balance),
+        },
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[1].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_second_token_balance),
+        },
+    ];
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &payments,
+            |sc| {
+                let _ = sc.create_metastaking_pos_from_two_tokens(
+                    managed_address!(&ms_addr),
+                    1u32.into(),
+                    1u32.into(),
+                );
+            },
+        )
+        .assert_ok();
+
+    // token A is staked, so only token B is swapped to token C, through the (B, C) pair
+    let expected_exit_amount = 857_142_857u64;
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let quote = sc.quote_exit_metastaking_pos(
+                managed_address!(&ms_addr),
+                managed_biguint!(expected_lp_farm_tokens),
+            );
+
+            assert_eq!(
+                quote.first_tokens.amount,
+                managed_biguint!(user_first_token_balance)
+            );
+            assert_eq!(
+                quote.second_tokens.amount,
+                managed_biguint!(user_second_token_balance)
+            );
+
+            let quote = sc.quote_exit_metastaking_pos_to_single_token(
+                managed_address!(&ms_addr),
+                managed_biguint!(expected_lp_farm_tokens),
+                EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[2])),
+            );
+
+            assert_eq!(
+                quote.output_tokens.amount,
+                managed_biguint!(expected_exit_amount)
+            );
+            assert_eq!(quote.price_impact_bps, 2_857);
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            DUAL_YIELD_TOKEN_ID,
+            1,
+            &rust_biguint!(expected_lp_farm_tokens),
+            |sc| {
+                let _ = sc.exit_metastaking_pos_to_single_token(
+                    managed_address!(&ms_addr),
+                    EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[2])),
+                    managed_biguint!(expected_exit_amount),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[2],
+        &rust_biguint!(expected_exit_amount),
+    );
+}
+
+#[test]
+fn quote_explicit_swap_route_and_batch_positions_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    // Same route and amounts as in enter_lp_through_pos_creator_long_swap_path_test
+    let first_pair_addr = pos_creator_setup.pair_setups[0]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    let third_pair_addr = pos_creator_setup.pair_setups[2]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let mut swap_operations = MultiValueEncoded::new();
+            let swap_operation: SwapOperationType<DebugApi> = (
+                managed_address!(&first_pair_addr),
+                ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                managed_token_id!(TOKEN_IDS[1]), // Want token B
+                BigUint::from(1u64),
+            )
+                .into();
+            swap_operations.push(swap_operation);
+
+            let second_swap_operation: SwapOperationType<DebugApi> = (
+                managed_address!(&third_pair_addr),
+                ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                managed_token_id!(TOKEN_IDS[2]), // Want token C
+                BigUint::from(1u64),
+            )
+                .into();
+            swap_operations.push(second_swap_operation);
+
+            let third_swap_operation: SwapOperationType<DebugApi> = (
+                managed_address!(&third_pair_addr),
+                ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                managed_token_id!(TOKEN_IDS[1]), // Want token B
+                BigUint::from(1u64),
+            )
+                .into();
+            swap_operations.push(third_swap_operation);
+
+            let quote = sc.quote_create_lp_pos_from_single_token(
+                managed_address!(&third_pair_addr),
+                EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[0])),
+                managed_biguint!(200_000_000u64),
+                swap_operations,
+            );
+
+            assert_eq!(
+                quote.lp_tokens.token_identifier,
+                managed_token_id!(LP_TOKEN_IDS[2])
+            );
+            assert_eq!(quote.lp_tokens.amount, managed_biguint!(154_700_536u64));
+        })
+        .assert_ok();
+
+    // the route must end with one of the pair tokens
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let mut swap_operations = MultiValueEncoded::new();
+            let swap_operation: SwapOperationType<DebugApi> = (
+                managed_address!(&first_pair_addr),
+                ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                managed_token_id!(TOKEN_IDS[0]), // Want token A
+                BigUint::from(1u64),
+            )
+                .into();
+            swap_operations.push(swap_operation);
+
+            let _ = sc.quote_create_lp_pos_from_single_token(
+                managed_address!(&third_pair_addr),
+                EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[1])),
+                managed_biguint!(200_000_000u64),
+                swap_operations,
+            );
+        })
+        .assert_user_error("The output token identifier is not part of the LP");
+
+    // Same entries and amounts as in
+    // create_farm_metastaking_and_farm_staking_positions_from_single_token_test
+    let farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    let ms_addr = pos_creator_setup.ms_wrapper.address_ref().clone();
+    let fs_addr = pos_creator_setup.fs_wrapper.address_ref().clone();
+    let expected_farm_token = 24_695_075u64;
+    let expected_dual_yield_token = 14_535_405u64;
+    let expected_staking_farm_token = 20_000_000u64;
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let mut entries = MultiValueEncoded::new();
+            entries.push(BatchPosEntry {
+                target_address: managed_address!(&farm_addr),
+                weight_bps: 5_000,
+                swap_route: ManagedVec::new(),
+                discover_route: false,
+                first_token_min_amount_out: 1u32.into(),
+                second_token_min_amount_out: 1u32.into(),
+            });
+            entries.push(BatchPosEntry {
+                target_address: managed_address!(&ms_addr),
+                weight_bps: 3_000,
+                swap_route: ManagedVec::new(),
+                discover_route: false,
+                first_token_min_amount_out: 1u32.into(),
+                second_token_min_amount_out: 1u32.into(),
+            });
+            entries.push(BatchPosEntry {
+                target_address: managed_address!(&fs_addr),
+                weight_bps: 2_000,
+                swap_route: ManagedVec::new(),
+                discover_route: false,
+                first_token_min_amount_out: 1u32.into(),
+                second_token_min_amount_out: 0u32.into(),
+            });
+
+            let quotes: Vec<NewPosQuote<DebugApi>> = sc
+                .quote_create_positions_from_single_token(
+                    EgldOrEsdtTokenIdentifier::esdt(managed_token_id!(TOKEN_IDS[0])),
+                    managed_biguint!(100_000_000u64),
+                    entries,
+                )
+                .into_iter()
+                .collect();
+            assert_eq!(quotes.len(), 3);
+
+            // the metastaking entry sees the (A, B) reserves left by the farm entry
+            let expected_lp_tokens = [expected_farm_token, expected_dual_yield_token];
+            for (quote, expected_amount) in quotes.iter().zip(expected_lp_tokens) {
+                match quote {
+                    NewPosQuote::Lp(lp_quote) => {
+                        assert_eq!(
+                            lp_quote.lp_tokens.token_identifier,
+                            managed_token_id!(LP_TOKEN_IDS[0])
+                        );
+                        assert_eq!(lp_quote.lp_tokens.amount, managed_biguint!(expected_amount));
+                    }
+                    NewPosQuote::FarmStaking(_) => panic!("Expected an LP quote"),
+                }
+            }
+
+            match &quotes[2] {
+                NewPosQuote::FarmStaking(staking_quote) => {
+                    assert_eq!(
+                        staking_quote.output_tokens.amount,
+                        managed_biguint!(expected_staking_farm_token)
+                    );
+                    assert_eq!(staking_quote.price_impact_bps, 0);
+                }
+                NewPosQuote::Lp(_) => panic!("Expected a farm staking quote"),
+            }
+        })
+        .assert_ok();
+}
+
+#[test]
+fn quote_migrate_position_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // Same amounts as in migrate_farm_pos_test
+    let first_farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    let second_farm_addr = pos_creator_setup.farm_setup.farm_wrappers[1]
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[0], // Token A
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let _ = sc.create_farm_pos_from_single_token(
+                    managed_address!(&first_farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_ok();
+
+    let first_farm_tokens = 48_808_847u64;
+    let second_farm_tokens = 48_808_845u64;
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let quote = sc.quote_migrate_position(
+                managed_address!(&first_farm_addr),
+                managed_address!(&second_farm_addr),
+                managed_biguint!(first_farm_tokens),
+            );
+
+            match quote {
+                NewPosQuote::Lp(lp_quote) => {
+                    assert_eq!(
+                        lp_quote.lp_tokens.token_identifier,
+                        managed_token_id!(LP_TOKEN_IDS[1])
+                    );
+                    assert_eq!(
+                        lp_quote.lp_tokens.amount,
+                        managed_biguint!(second_farm_tokens)
+                    );
+                }
+                NewPosQuote::FarmStaking(_) => panic!("Expected an LP quote"),
+            }
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let _ = sc.quote_migrate_position(
+                managed_address!(&first_farm_addr),
+                managed_address!(&first_farm_addr),
+                managed_biguint!(first_farm_tokens),
+            );
+        })
+        .assert_user_error("Cannot migrate to the same contract");
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            FARM_TOKEN_ID[0],
+            1,
+            &rust_biguint!(first_farm_tokens),
+            |sc| {
+                let _ = sc.migrate_position(
+                    managed_address!(&first_farm_addr),
+                    managed_address!(&second_farm_addr),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(second_farm_tokens),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[1],
+        1,
+        &rust_biguint!(second_farm_tokens),
+        None,
+    );
+}
+
+#[test]
+fn enter_farm_with_foreign_position_merge_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance * 2),
+    );
+
+    // user enter (A, B) farm with token A
+    let first_farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    let second_farm_addr = pos_creator_setup.farm_setup.farm_wrappers[1]
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[0], // Token A
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let _ = sc.create_farm_pos_from_single_token(
+                    managed_address!(&first_farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_ok();
+
+    let first_farm_tokens = 48_808_847u64;
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(first_farm_tokens),
+        None,
+    );
+
+    // only the (A, B) farm tokens can be merged in the (A, B) farm
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let mut user_tokens = MultiValueEncoded::new();
+            user_tokens.push(EsdtTokenPayment::new(
+                managed_token_id!(TOKEN_IDS[0]),
+                0,
+                managed_biguint!(user_first_token_balance),
+            ));
+            user_tokens.push(EsdtTokenPayment::new(
+                managed_token_id!(FARM_TOKEN_ID[0]),
+                1,
+                managed_biguint!(first_farm_tokens),
+            ));
+
+            let compatible_tokens = sc
+                .get_merge_compatible_tokens(
+                    managed_address!(&first_farm_addr),
+                    user_tokens.clone(),
+                )
+                .to_vec();
+            assert_eq!(compatible_tokens.len(), 1);
+            assert_eq!(
+                compatible_tokens.get(0).token_identifier,
+                managed_token_id!(FARM_TOKEN_ID[0])
+            );
+
+            let compatible_tokens = sc
+                .get_merge_compatible_tokens(managed_address!(&second_farm_addr), user_tokens)
+                .to_vec();
+            assert!(compatible_tokens.is_empty());
+        })
+        .assert_ok();
+
+    // try enter the (A, C) farm, merging the (A, B) farm tokens
+    let payments = vec![
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[0].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_first_token_balance),
+        },
+        TxTokenTransfer {
+            token_identifier: FARM_TOKEN_ID[0].to_vec(),
+            nonce: 1,
+            value: rust_biguint!(first_farm_tokens),
+        },
+    ];
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &payments,
+            |sc| {
+                let _ = sc.create_farm_pos_from_single_token(
+                    managed_address!(&second_farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_user_error("Additional payments must be positions of the target contract");
+
+    // merging in the same farm works
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &payments,
+            |sc| {
+                let _ = sc.create_farm_pos_from_single_token(
+                    managed_address!(&first_farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(0),
+        None,
+    );
+}
+
+#[test]
+fn create_lp_pos_with_protocol_fee_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let owner = pos_creator_setup.farm_setup.owner;
+    let fee_collector = pos_creator_setup.farm_setup.second_user;
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_second_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(user_second_token_balance),
+    );
+
+    // 1% fee
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_fee_bps(100);
+                sc.set_fee_collector(managed_address!(&fee_collector));
+            },
+        )
+        .assert_ok();
+
+    // user enter (B, C) pair with token B
+    // The fee is taken before swapping, so only 99_000_000 tokens B are used
+    let expected_fee_amount = 1_000_000u64;
+    let expected_lp_tokens = 48_332_007u64;
+    let expected_remaining_second_token = 2u64;
+    let third_pair_addr = pos_creator_setup.pair_setups[2]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[1], // Token B
+            0,
+            &rust_biguint!(user_second_token_balance),
+            |sc| {
+                let _ = sc.create_lp_pos_from_single_token(
+                    managed_address!(&third_pair_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        LP_TOKEN_IDS[2],
+        &rust_biguint!(expected_lp_tokens),
+    );
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_remaining_second_token),
+    );
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let accumulated_fees = sc.get_accumulated_fees().to_vec();
+            assert_eq!(accumulated_fees.len(), 1);
+            assert_eq!(
+                accumulated_fees.get(0).token_identifier,
+                managed_token_id!(TOKEN_IDS[1])
+            );
+            assert_eq!(
+                accumulated_fees.get(0).amount,
+                managed_biguint!(expected_fee_amount)
+            );
+        })
+        .assert_ok();
+
+    // only the fee collector can claim
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let _ = sc.claim_fees();
+            },
+        )
+        .assert_user_error("Only the fee collector can claim fees");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &fee_collector,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let fees = sc.claim_fees();
+                assert_eq!(fees.len(), 1);
+                assert_eq!(fees.get(0).amount, managed_biguint!(expected_fee_amount));
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &fee_collector,
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_fee_amount),
+    );
+
+    // fee exempt addresses pay no fee
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut addresses = MultiValueEncoded::new();
+                addresses.push(managed_address!(&user_addr));
+                sc.add_fee_exempt_addresses(addresses);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(user_second_token_balance),
+    );
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[1], // Token B
+            0,
+            &rust_biguint!(user_second_token_balance),
+            |sc| {
+                let _ = sc.create_lp_pos_from_single_token(
+                    managed_address!(&third_pair_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            assert!(sc.get_accumulated_fees().is_empty());
+        })
+        .assert_ok();
+}
+
+#[test]
+fn migrate_pos_with_protocol_fee_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let owner = pos_creator_setup.farm_setup.owner;
+    let fee_collector = pos_creator_setup.farm_setup.second_user;
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    let first_pair_addr = pos_creator_setup.pair_setups[0]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    let second_farm_addr = pos_creator_setup.farm_setup.farm_wrappers[1]
+        .address_ref()
+        .clone();
+
+    // the position is created before the fee is set
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[0], // Token A
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let _ = sc.create_lp_pos_from_single_token(
+                    managed_address!(&first_pair_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_fee_collector(ManagedAddress::zero());
+            },
+        )
+        .assert_user_error("Invalid fee collector address");
+
+    // 1% fee
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_fee_bps(100);
+                sc.set_fee_collector(managed_address!(&fee_collector));
+            },
+        )
+        .assert_ok();
+
+    // same LP amount as migrate_lp_pos_test
+    // Removed liquidity: 51_191_150 tokens A and 93_074_819 tokens B, both charged 1%
+    let lp_tokens = 48_808_847u64;
+    let expected_first_token_fee = 511_911u64;
+    let expected_second_token_fee = 930_748u64;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            LP_TOKEN_IDS[0],
+            0,
+            &rust_biguint!(lp_tokens),
+            |sc| {
+                let _ = sc.migrate_position(
+                    managed_address!(&first_pair_addr),
+                    managed_address!(&second_farm_addr),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&pos_creator_setup.pos_creator_wrapper, |sc| {
+            let accumulated_fees = sc.get_accumulated_fees().to_vec();
+            assert_eq!(accumulated_fees.len(), 2);
+            for fee in &accumulated_fees {
+                let expected_fee = if fee.token_identifier == managed_token_id!(TOKEN_IDS[0]) {
+                    expected_first_token_fee
+                } else {
+                    assert_eq!(fee.token_identifier, managed_token_id!(TOKEN_IDS[1]));
+                    expected_second_token_fee
+                };
+                assert_eq!(fee.amount, managed_biguint!(expected_fee));
+            }
+        })
+        .assert_ok();
+}
+
+#[test]
+fn create_positions_from_single_token_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    let first_farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    let second_farm_addr = pos_creator_setup.farm_setup.farm_wrappers[1]
+        .address_ref()
+        .clone();
+
+    // weights must add up to 100%
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[0], // Token A
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut entries = MultiValueEncoded::new();
+                entries.push(BatchPosEntry {
+                    target_address: managed_address!(&first_farm_addr),
+                    weight_bps: 6_000,
+                    swap_route: ManagedVec::new(),
+                    discover_route: false,
+                    first_token_min_amount_out: 1u32.into(),
+                    second_token_min_amount_out: 1u32.into(),
+                });
+                entries.push(BatchPosEntry {
+                    target_address: managed_address!(&second_farm_addr),
+                    weight_bps: 3_000,
+                    swap_route: ManagedVec::new(),
+                    discover_route: false,
+                    first_token_min_amount_out: 1u32.into(),
+                    second_token_min_amount_out: 1u32.into(),
+                });
+
+                let _ = sc.create_positions_from_single_token(entries);
+            },
+        )
+        .assert_user_error("Weights must add up to 100%");
+
+    // user enters the (A, B) farm with 60% and the (A, C) farm with 40% of their tokens A
+    let expected_first_farm_token = 29_563_013u64;
+    let expected_second_farm_token = 19_803_901u64;
+    let expected_remaining_first_token = 2u64;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[0], // Token A
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut entries = MultiValueEncoded::new();
+                entries.push(BatchPosEntry {
+                    target_address: managed_address!(&first_farm_addr),
+                    weight_bps: 6_000,
+                    swap_route: ManagedVec::new(),
+                    discover_route: false,
+                    first_token_min_amount_out: 1u32.into(),
+                    second_token_min_amount_out: 1u32.into(),
+                });
+                entries.push(BatchPosEntry {
+                    target_address: managed_address!(&second_farm_addr),
+                    weight_bps: 4_000,
+                    swap_route: ManagedVec::new(),
+                    discover_route: false,
+                    first_token_min_amount_out: 1u32.into(),
+                    second_token_min_amount_out: 1u32.into(),
+                });
+
+                let output_payments = sc.create_positions_from_single_token(entries);
+                assert_eq!(output_payments.len(), 3);
+                assert_eq!(
+                    output_payments.get(1).amount,
+                    managed_biguint!(expected_first_farm_token)
+                );
+                assert_eq!(
+                    output_payments.get(2).amount,
+                    managed_biguint!(expected_second_farm_token)
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_remaining_first_token),
+    );
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(expected_first_farm_token),
+        None,
+    );
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[1],
+        1,
+        &rust_biguint!(expected_second_farm_token),
+        None,
+    );
+}
+
+#[test]
+fn create_farm_metastaking_and_farm_staking_positions_from_single_token_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    let farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    let ms_addr = pos_creator_setup.ms_wrapper.address_ref().clone();
+    let fs_addr = pos_creator_setup.fs_wrapper.address_ref().clone();
+
+    // a weight too small for the payment would leave an entry with no tokens
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[0], // Token A
+            0,
+            &rust_biguint!(1_000u64),
+            |sc| {
+                let mut entries = MultiValueEncoded::new();
+                entries.push(BatchPosEntry {
+                    target_address: managed_address!(&fs_addr),
+                    weight_bps: 1,
+                    swap_route: ManagedVec::new(),
+                    discover_route: false,
+                    first_token_min_amount_out: 1u32.into(),
+                    second_token_min_amount_out: 1u32.into(),
+                });
+                entries.push(BatchPosEntry {
+                    target_address: managed_address!(&farm_addr),
+                    weight_bps: 9_999,
+                    swap_route: ManagedVec::new(),
+                    discover_route: false,
+                    first_token_min_amount_out: 1u32.into(),
+                    second_token_min_amount_out: 1u32.into(),
+                });
+
+                let _ = sc.create_positions_from_single_token(entries);
+            },
+        )
+        .assert_user_error("Payment too small for the entry weight");
+
+    // user enters the (A, B) farm with 50%, the (A, B) metastaking with 30%
+    // and the (A) farm staking with 20% of their tokens A.
+    // The metastaking entry adds liquidity after the farm entry changed the (A, B) reserves.
+    let expected_farm_token = 24_695_075u64;
+    let expected_dual_yield_token = 14_535_405u64;
+    let expected_staking_farm_token = 20_000_000u64;
+    let expected_remaining_first_token = 5u64;
+    let tx_result = b_mock.borrow_mut().execute_esdt_transfer(
+        &user_addr,
+        &pos_creator_setup.pos_creator_wrapper,
+        TOKEN_IDS[0], // Token A
+        0,
+        &rust_biguint!(user_first_token_balance),
+        |sc| {
+            let mut entries = MultiValueEncoded::new();
+            entries.push(BatchPosEntry {
+                target_address: managed_address!(&farm_addr),
+                weight_bps: 5_000,
+                swap_route: ManagedVec::new(),
+                discover_route: false,
+                first_token_min_amount_out: 1u32.into(),
+                second_token_min_amount_out: 1u32.into(),
+            });
+            entries.push(BatchPosEntry {
+                target_address: managed_address!(&ms_addr),
+                weight_bps: 3_000,
+                swap_route: ManagedVec::new(),
+                discover_route: false,
+                first_token_min_amount_out: 1u32.into(),
+                second_token_min_amount_out: 1u32.into(),
+            });
+            entries.push(BatchPosEntry {
+                target_address: managed_address!(&fs_addr),
+                weight_bps: 2_000,
+                swap_route: ManagedVec::new(),
+                discover_route: false,
+                first_token_min_amount_out: managed_biguint!(expected_staking_farm_token),
+                second_token_min_amount_out: 0u32.into(),
+            });
+
+            let output_payments = sc.create_positions_from_single_token(entries);
+            let positions_start = output_payments.len() - 3;
+
+            let farm_position = output_payments.get(positions_start);
+            assert_eq!(
+                farm_position.token_identifier,
+                managed_token_id!(FARM_TOKEN_ID[0])
+            );
+            assert_eq!(farm_position.amount, managed_biguint!(expected_farm_token));
+
+            let metastaking_position = output_payments.get(positions_start + 1);
+            assert_eq!(
+                metastaking_position.token_identifier,
+                managed_token_id!(DUAL_YIELD_TOKEN_ID)
+            );
+            assert_eq!(
+                metastaking_position.amount,
+                managed_biguint!(expected_dual_yield_token)
+            );
+
+            let farm_staking_position = output_payments.get(positions_start + 2);
+            assert_eq!(
+                farm_staking_position.token_identifier,
+                managed_token_id!(STAKING_FARM_TOKEN_ID)
+            );
+            assert_eq!(
+                farm_staking_position.amount,
+                managed_biguint!(expected_staking_farm_token)
+            );
+
+            let attributes: StakingFarmTokenAttributes<DebugApi> =
+                sc.blockchain().get_token_attributes(
+                    &farm_staking_position.token_identifier,
+                    farm_staking_position.token_nonce,
+                );
+            assert_eq!(attributes.original_owner, managed_address!(&user_addr));
+        },
+    );
+    tx_result.assert_ok();
+
+    // each createPos event logs the share of the payment used for its entry
+    let pos_creator_addr = pos_creator_setup.pos_creator_wrapper.address_ref().clone();
+    let event_input_amounts = create_pos_event_input_amounts(&tx_result, &pos_creator_addr);
+    assert_eq!(
+        event_input_amounts,
+        vec![
+            rust_biguint!(50_000_000u64),
+            rust_biguint!(30_000_000u64),
+            rust_biguint!(20_000_000u64),
+        ]
+    );
+    assert_eq!(
+        event_input_amounts.iter().sum::<num_bigint::BigUint>(),
+        rust_biguint!(user_first_token_balance)
+    );
+
+    b_mock.borrow().check_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_remaining_first_token),
+    );
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[1], &rust_biguint!(0));
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(expected_farm_token),
+        None,
+    );
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        DUAL_YIELD_TOKEN_ID,
+        1,
+        &rust_biguint!(expected_dual_yield_token),
+        None,
+    );
+    b_mock.borrow().check_esdt_balance(
+        pos_creator_setup.pos_creator_wrapper.address_ref(),
+        TOKEN_IDS[0],
+        &rust_biguint!(0),
+    );
+}
+
+#[test]
+fn enter_farm_and_deposit_in_auto_farm_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+    let owner = pos_creator_setup.farm_setup.owner;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_third_token_balance = 600_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[2],
+        &rust_biguint!(user_third_token_balance),
+    );
+
+    let second_pair_addr = pos_creator_setup.pair_setups[1]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+    let farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    let energy_factory_addr = pos_creator_setup
+        .farm_setup
+        .energy_factory_wrapper
+        .address_ref()
+        .clone();
+
+    // setup auto-farm
+    let auto_farm_wrapper = b_mock.borrow_mut().create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner),
+        auto_farm::contract_obj,
+        "auto farm",
+    );
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &auto_farm_wrapper, &rust_biguint!(0), |sc| {
+            sc.init(
+                managed_address!(&owner),
+                1_000,
+                managed_address!(&energy_factory_addr),
+                managed_address!(&energy_factory_addr), // unused here
+                managed_address!(&energy_factory_addr), // unused here
+            );
+
+            let mut farms = MultiValueEncoded::new();
+            farms.push(managed_address!(&farm_addr));
+            sc.add_farms(farms);
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_auto_farm_address(managed_address!(auto_farm_wrapper.address_ref()));
+            },
+        )
+        .assert_ok();
+
+    let enter_farm = || {
+        b_mock.borrow_mut().execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[2], // Token C
+            0,
+            &rust_biguint!(user_third_token_balance),
+            |sc| {
+                let mut swap_operations = MultiValueEncoded::new();
+                let swap_operation: SwapOperationType<DebugApi> = (
+                    managed_address!(&second_pair_addr),
+                    ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    managed_token_id!(TOKEN_IDS[0]), // Want token A
+                    BigUint::from(1u64),
+                )
+                    .into();
+                swap_operations.push(swap_operation);
+                let options = CreatePosOptions {
+                    destination: PositionDestination::AutoFarm,
+                    opt_max_price_deviation_bps: None,
+                    discover_routes: false,
+                };
+                let _ = sc.create_farm_pos_from_single_token_with_options(
+                    managed_address!(&farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    options,
+                    swap_operations,
+                );
+            },
+        )
+    };
+
+    // auto pos creator is not yet a trusted position creator in auto-farm
+    enter_farm().assert_user_error("Caller is not a trusted position creator");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &auto_farm_wrapper, &rust_biguint!(0), |sc| {
+            let mut position_creators = MultiValueEncoded::new();
+            position_creators.push(managed_address!(pos_creator_setup
+                .pos_creator_wrapper
+                .address_ref()));
+            sc.add_position_creators(position_creators);
+        })
+        .assert_ok();
+
+    enter_farm().assert_ok();
+
+    // same position as enter_lp_and_farm_through_pos_creator, but held by auto-farm for the user
+    let expected_farm_token = 44_465_934u64;
+    b_mock
+        .borrow()
+        .check_esdt_balance(&user_addr, TOKEN_IDS[2], &rust_biguint!(0));
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(0),
+        None,
+    );
+    b_mock.borrow().check_nft_balance::<Empty>(
+        auto_farm_wrapper.address_ref(),
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(expected_farm_token),
+        None,
+    );
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&auto_farm_wrapper, |sc| {
+            let user_farm_tokens = sc.get_user_farm_tokens_view(managed_address!(&user_addr));
+            assert_eq!(user_farm_tokens.len(), 1);
+
+            let farm_tokens = user_farm_tokens.get(0);
+            assert_eq!(farm_tokens.token_identifier, managed_token_id!(FARM_TOKEN_ID[0]));
+            assert_eq!(farm_tokens.token_nonce, 1);
+            assert_eq!(farm_tokens.amount, managed_biguint!(expected_farm_token));
+        })
+        .assert_ok();
+}
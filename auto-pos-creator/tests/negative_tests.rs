@@ -1,14 +1,18 @@
 #![allow(deprecated)]
 
 use auto_pos_creator::{
+    configs::contracts_whitelist::ContractsWhitelistModule,
     external_sc_interactions::router_actions::SwapOperationType,
     multi_contract_interactions::{
-        create_pos_endpoints::CreatePosEndpointsModule, exit_pos_endpoints::ExitPosEndpointsModule,
+        create_multi_token_pos::{CreateMultiTokenPosModule, EGLD_000000_TOKEN_ID},
+        create_pos_endpoints::CreatePosEndpointsModule,
+        exit_pos_endpoints::ExitPosEndpointsModule,
     },
+    AutoPosCreator,
 };
 use metastaking_setup::setup_metastaking;
 use multiversx_sc::{
-    codec::Empty,
+    codec::{multi_types::OptionalValue, Empty},
     types::{BigUint, ManagedBuffer, MultiValueEncoded},
 };
 use multiversx_sc_scenario::{
@@ -321,13 +325,13 @@ fn try_create_lp_slippage_error_test() {
                 swap_operations.push(swap_operation);
                 let _ = sc.create_lp_pos_from_single_token(
                     managed_address!(&third_pair_addr),
-                    1u64.into(),
                     2_000_000_001u64.into(),
+                    1u64.into(),
                     swap_operations,
                 );
             },
         )
-        .assert_user_error("Insufficient second token computed amount");
+        .assert_user_error("Insufficient first token computed amount");
 }
 
 #[test]
@@ -552,13 +556,13 @@ fn try_create_position_wrong_slippage_test() {
                 swap_operations.push(swap_operation);
                 let _ = sc.create_farm_pos_from_single_token(
                     managed_address!(&farm_addr),
-                    1u32.into(),
                     2_000_000_001u64.into(),
+                    1u32.into(),
                     swap_operations,
                 );
             },
         )
-        .assert_user_error("Insufficient second token computed amount");
+        .assert_user_error("Insufficient first token computed amount");
 
     // Try create metastaking position
     let ms_addr = pos_creator_setup.ms_wrapper.address_ref().clone();
@@ -583,13 +587,13 @@ fn try_create_position_wrong_slippage_test() {
                 swap_operations.push(swap_operation);
                 let _ = sc.create_metastaking_pos_from_single_token(
                     managed_address!(&ms_addr),
-                    1u32.into(),
                     2_000_000_001u64.into(),
+                    1u32.into(),
                     swap_operations,
                 );
             },
         )
-        .assert_user_error("Insufficient second token computed amount");
+        .assert_user_error("Insufficient first token computed amount");
 
     // Try create farm staking position
     let fs_addr = pos_creator_setup.fs_wrapper.address_ref().clone();
@@ -799,7 +803,7 @@ fn try_create_position_wrong_address_test() {
                 );
             },
         )
-        .assert_user_error("storage decode error: bad array length");
+        .assert_user_error("Farm not whitelisted");
 
     // Try create metastaking position
     b_mock
@@ -829,7 +833,7 @@ fn try_create_position_wrong_address_test() {
                 );
             },
         )
-        .assert_user_error("storage decode error: bad array length");
+        .assert_user_error("Farm not whitelisted");
 }
 
 #[test]
@@ -1089,6 +1093,22 @@ fn try_exit_metastaking_wrong_address_test() {
         )
         .assert_ok();
 
+    // add the new metastaking SC to auto pos creator whitelist
+    let new_ms_address = ms_wrapper.address_ref().clone();
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &pos_creator_setup.farm_setup.owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut metastaking_scs = MultiValueEncoded::new();
+                metastaking_scs.push(managed_address!(&new_ms_address));
+                sc.add_metastaking_scs(metastaking_scs);
+            },
+        )
+        .assert_ok();
+
     b_mock
         .borrow_mut()
         .execute_esdt_transfer(
@@ -1102,8 +1122,220 @@ fn try_exit_metastaking_wrong_address_test() {
                     managed_address!(ms_wrapper.address_ref()),
                     1u32.into(),
                     1u32.into(),
+                    OptionalValue::None,
                 );
             },
         )
         .assert_user_error("Invalid payment token");
 }
+
+#[test]
+fn try_create_farm_pos_not_whitelisted_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let owner = pos_creator_setup.farm_setup.owner;
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // remove (A, B) farm from whitelist
+    let farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut farms = MultiValueEncoded::new();
+                farms.push(managed_address!(&farm_addr));
+                sc.remove_farms(farms);
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[0], // Token A
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let _ = sc.create_farm_pos_from_single_token(
+                    managed_address!(&farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_user_error("Farm not whitelisted");
+
+    // any farm can be used in permissionless mode
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_permissionless(true);
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            TOKEN_IDS[0], // Token A
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let _ = sc.create_farm_pos_from_single_token(
+                    managed_address!(&farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &user_addr,
+        FARM_TOKEN_ID[0],
+        1,
+        &rust_biguint!(48_808_847u64),
+        None,
+    );
+}
+
+#[test]
+fn upgrade_without_whitelists_is_permissionless_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+    let owner = pos_creator_setup.farm_setup.owner;
+
+    // the whitelists are already filled, so the upgrade keeps them enforced
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.upgrade();
+
+                assert!(!sc.permissionless().get());
+            },
+        )
+        .assert_ok();
+
+    // contracts deployed before the whitelists keep accepting any contract
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &pos_creator_setup.pos_creator_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.whitelisted_pairs().clear();
+                sc.whitelisted_farms().clear();
+                sc.whitelisted_metastaking_scs().clear();
+                sc.whitelisted_farm_staking_scs().clear();
+                sc.upgrade();
+
+                assert!(sc.permissionless().get());
+            },
+        )
+        .assert_ok();
+}
+
+#[test]
+fn try_create_farm_pos_from_egld_multi_transfer_test() {
+    let pos_creator_setup = PosCreatorSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+        pair::contract_obj,
+        router::contract_obj,
+        farm_staking::contract_obj,
+        farm_staking_proxy::contract_obj,
+        auto_pos_creator::contract_obj,
+    );
+    let b_mock = pos_creator_setup.farm_setup.b_mock;
+
+    let user_addr = pos_creator_setup.farm_setup.first_user;
+    let user_egld_balance = 100_000_000u64;
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        EGLD_000000_TOKEN_ID,
+        &rust_biguint!(user_egld_balance),
+    );
+    b_mock.borrow_mut().set_esdt_balance(
+        &user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    let farm_addr = pos_creator_setup.farm_setup.farm_wrappers[0]
+        .address_ref()
+        .clone();
+    let payments = vec![
+        TxTokenTransfer {
+            token_identifier: EGLD_000000_TOKEN_ID.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_egld_balance),
+        },
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[0].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_first_token_balance),
+        },
+    ];
+
+    // EGLD is only wrapped when sent alone
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &user_addr,
+            &pos_creator_setup.pos_creator_wrapper,
+            &payments,
+            |sc| {
+                let _ = sc.create_farm_pos_from_multiple_tokens(
+                    managed_address!(&farm_addr),
+                    1u32.into(),
+                    1u32.into(),
+                    MultiValueEncoded::new(),
+                );
+            },
+        )
+        .assert_user_error("EGLD must be sent alone");
+}
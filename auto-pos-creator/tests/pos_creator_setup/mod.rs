@@ -4,8 +4,9 @@ use crate::pair_setup::PairSetup;
 use crate::router_setup::RouterSetup;
 
 use super::metastaking_setup::setup_metastaking;
-use auto_pos_creator::AutoPosCreator;
+use auto_pos_creator::{configs::contracts_whitelist::ContractsWhitelistModule, AutoPosCreator};
 use farm::exit_penalty::ExitPenaltyModule;
+use multiversx_sc::types::MultiValueEncoded;
 use multiversx_sc_scenario::{
     managed_address, managed_biguint, rust_biguint, testing_framework::ContractObjWrapper, DebugApi,
 };
@@ -355,6 +356,31 @@ where
             )
             .assert_ok();
 
+        // whitelist all the contracts in auto pos creator
+        b_mock
+            .borrow_mut()
+            .execute_tx(&owner, &pos_creator_wrapper, &rust_biguint!(0), |sc| {
+                let mut pairs = MultiValueEncoded::new();
+                pairs.push(managed_address!(first_pair_setup.pair_wrapper.address_ref()));
+                pairs.push(managed_address!(second_pair_setup.pair_wrapper.address_ref()));
+                pairs.push(managed_address!(third_pair_setup.pair_wrapper.address_ref()));
+                sc.add_pairs(pairs);
+
+                let mut farms = MultiValueEncoded::new();
+                farms.push(managed_address!(farm_setup.farm_wrappers[0].address_ref()));
+                farms.push(managed_address!(farm_setup.farm_wrappers[1].address_ref()));
+                sc.add_farms(farms);
+
+                let mut metastaking_scs = MultiValueEncoded::new();
+                metastaking_scs.push(managed_address!(ms_wrapper.address_ref()));
+                sc.add_metastaking_scs(metastaking_scs);
+
+                let mut farm_staking_scs = MultiValueEncoded::new();
+                farm_staking_scs.push(managed_address!(fs_wrapper.address_ref()));
+                sc.add_farm_staking_scs(farm_staking_scs);
+            })
+            .assert_ok();
+
         let pair_setups = vec![first_pair_setup, second_pair_setup, third_pair_setup];
 
         PosCreatorSetup {
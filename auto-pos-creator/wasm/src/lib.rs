@@ -6,9 +6,9 @@
 
 // Init:                                 1
 // Upgrade:                              1
-// Endpoints:                           10
+// Endpoints:                           76
 // Async Callback (empty):               1
-// Total number of exported functions:  13
+// Total number of exported functions:  79
 
 #![no_std]
 
@@ -20,16 +20,82 @@ multiversx_sc_wasm_adapter::endpoints! {
     (
         init => init
         upgrade => upgrade
+        setMaxPriceDeviation => set_max_price_deviation
+        setPriceOracle => set_price_oracle
+        removePriceOracle => remove_price_oracle
+        getMaxPriceDeviation => max_price_deviation
+        getPriceOracle => price_oracle
+        addPairs => add_pairs
+        removePairs => remove_pairs
+        addFarms => add_farms
+        removeFarms => remove_farms
+        addMetastakingScs => add_metastaking_scs
+        removeMetastakingScs => remove_metastaking_scs
+        addFarmStakingScs => add_farm_staking_scs
+        removeFarmStakingScs => remove_farm_staking_scs
+        setPermissionless => set_permissionless
+        isPermissionless => permissionless
+        getWhitelistedPairs => whitelisted_pairs
+        getWhitelistedFarms => whitelisted_farms
+        getWhitelistedMetastakingScs => whitelisted_metastaking_scs
+        getWhitelistedFarmStakingScs => whitelisted_farm_staking_scs
         createLpPosFromSingleToken => create_lp_pos_from_single_token
+        createLpPosFromSingleTokenWithOptions => create_lp_pos_from_single_token_with_options
         createLpPosFromTwoTokens => create_lp_pos_from_two_tokens
         createFarmPosFromSingleToken => create_farm_pos_from_single_token
+        createFarmPosFromSingleTokenWithOptions => create_farm_pos_from_single_token_with_options
         createFarmPosFromTwoTokens => create_farm_pos_from_two_tokens
         createMetastakingPosFromSingleToken => create_metastaking_pos_from_single_token
+        createMetastakingPosFromSingleTokenWithOptions => create_metastaking_pos_from_single_token_with_options
         createMetastakingPosFromTwoTokens => create_metastaking_pos_from_two_tokens
         createFarmStakingPosFromSingleToken => create_farm_staking_pos_from_single_token
+        createFarmStakingPosFromSingleTokenWithOptions => create_farm_staking_pos_from_single_token_with_options
+        createFarmPosFromMultipleTokens => create_farm_pos_from_multiple_tokens
+        createFarmPosFromMultipleTokensWithOptions => create_farm_pos_from_multiple_tokens_with_options
+        createMetastakingPosFromMultipleTokens => create_metastaking_pos_from_multiple_tokens
+        createMetastakingPosFromMultipleTokensWithOptions => create_metastaking_pos_from_multiple_tokens_with_options
+        getMergeCompatibleTokens => get_merge_compatible_tokens
+        createPositionsFromSingleToken => create_positions_from_single_token
         exitMetastakingPos => exit_metastaking_pos_endpoint
         exitFarmPos => exit_farm_pos
         exitLpPos => exit_lp_pos
+        exitLpPosToSingleToken => exit_lp_pos_to_single_token
+        exitFarmPosToSingleToken => exit_farm_pos_to_single_token
+        exitMetastakingPosToSingleToken => exit_metastaking_pos_to_single_token
+        migratePosition => migrate_position
+        claimUnbondedTokens => claim_unbonded_tokens
+        getPendingUnbonds => pending_unbonds
+        quoteCreateLpPosFromSingleToken => quote_create_lp_pos_from_single_token
+        quoteCreateFarmPosFromSingleToken => quote_create_farm_pos_from_single_token
+        quoteCreateMetastakingPosFromSingleToken => quote_create_metastaking_pos_from_single_token
+        quoteCreateFarmStakingPosFromSingleToken => quote_create_farm_staking_pos_from_single_token
+        quoteCreateLpPosFromTwoTokens => quote_create_lp_pos_from_two_tokens
+        quoteCreateFarmPosFromTwoTokens => quote_create_farm_pos_from_two_tokens
+        quoteCreateMetastakingPosFromTwoTokens => quote_create_metastaking_pos_from_two_tokens
+        quoteCreateFarmPosFromMultipleTokens => quote_create_farm_pos_from_multiple_tokens
+        quoteCreateMetastakingPosFromMultipleTokens => quote_create_metastaking_pos_from_multiple_tokens
+        quoteCreatePositionsFromSingleToken => quote_create_positions_from_single_token
+        quoteMigratePosition => quote_migrate_position
+        quoteExitLpPos => quote_exit_lp_pos
+        quoteExitFarmPos => quote_exit_farm_pos
+        quoteExitMetastakingPos => quote_exit_metastaking_pos
+        quoteExitLpPosToSingleToken => quote_exit_lp_pos_to_single_token
+        quoteExitFarmPosToSingleToken => quote_exit_farm_pos_to_single_token
+        quoteExitMetastakingPosToSingleToken => quote_exit_metastaking_pos_to_single_token
+        addHubTokens => add_hub_tokens
+        removeHubTokens => remove_hub_tokens
+        getHubTokens => hub_tokens
+        setAutoFarmAddress => set_auto_farm_address
+        getAutoFarmAddress => auto_farm_address
+        setFeeBps => set_fee_bps
+        setFeeCollector => set_fee_collector
+        addFeeExemptAddresses => add_fee_exempt_addresses
+        removeFeeExemptAddresses => remove_fee_exempt_addresses
+        claimFees => claim_fees
+        getAccumulatedFees => get_accumulated_fees
+        getFeeBps => fee_bps
+        getFeeCollector => fee_collector
+        getFeeExemptAddresses => fee_exempt_addresses
     )
 }
 
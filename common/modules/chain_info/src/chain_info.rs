@@ -1,3 +1,5 @@
+#![no_std]
+
 use multiversx_sc::{api::BlockchainApi, contract_base::BlockchainWrapper};
 
 multiversx_sc::imports!();
@@ -0,0 +1,91 @@
+#![no_std]
+
+multiversx_sc::imports!();
+
+pub const PAIR_MAX_FEE_PERCENTAGE: u64 = 100_000;
+pub const MAX_BPS: u64 = 10_000;
+
+/// Same formula as the pair's get_amount_out
+pub fn get_amount_out<M: ManagedTypeApi>(
+    amount_in: &BigUint<M>,
+    reserve_in: &BigUint<M>,
+    reserve_out: &BigUint<M>,
+    total_fee_percent: u64,
+) -> BigUint<M> {
+    if reserve_in == &0u64 || reserve_out == &0u64 {
+        return BigUint::zero();
+    }
+
+    let amount_in_with_fee = amount_in * (PAIR_MAX_FEE_PERCENTAGE - total_fee_percent);
+    let numerator = &amount_in_with_fee * reserve_out;
+    let denominator = reserve_in * PAIR_MAX_FEE_PERCENTAGE + amount_in_with_fee;
+
+    numerator / denominator
+}
+
+/// Same formula as the pair's get_amount_in.
+/// The amount out must be lower than the output reserve.
+pub fn get_amount_in<M: ManagedTypeApi>(
+    amount_out: &BigUint<M>,
+    reserve_in: &BigUint<M>,
+    reserve_out: &BigUint<M>,
+    total_fee_percent: u64,
+) -> BigUint<M> {
+    let numerator = reserve_in * amount_out * PAIR_MAX_FEE_PERCENTAGE;
+    let denominator = (reserve_out - amount_out) * (PAIR_MAX_FEE_PERCENTAGE - total_fee_percent);
+
+    numerator / denominator + 1u64
+}
+
+/// Computes the amount of input tokens that have to be swapped so that the swap output
+/// and the remaining input tokens are in the pool ratio after the swap.
+///
+/// Solves for s: (amount - s) / swap_out(s) = (reserve_in + s) / (reserve_out - swap_out(s)),
+/// which, for a fee f, gives
+/// s = (sqrt(((2 - f) * r)^2 + 4 * (1 - f) * amount * r) - (2 - f) * r) / (2 * (1 - f))
+pub fn get_optimal_swap_amount<M: ManagedTypeApi>(
+    amount: &BigUint<M>,
+    reserve_in: &BigUint<M>,
+    total_fee_percent: u64,
+) -> BigUint<M> {
+    if reserve_in == &0u64 {
+        return amount / 2u64;
+    }
+
+    let two_minus_fee = BigUint::from(2 * PAIR_MAX_FEE_PERCENTAGE - total_fee_percent);
+    let one_minus_fee = BigUint::from(PAIR_MAX_FEE_PERCENTAGE - total_fee_percent);
+
+    let scaled_reserve = &two_minus_fee * reserve_in;
+    let discriminant = &scaled_reserve * &scaled_reserve
+        + &one_minus_fee * 4u64 * PAIR_MAX_FEE_PERCENTAGE * amount * reserve_in;
+    let optimal_amount = (discriminant.sqrt() - scaled_reserve) / (one_minus_fee * 2u64);
+
+    core::cmp::min(optimal_amount, amount.clone())
+}
+
+/// The output at the current price, without fees or slippage
+pub fn get_spot_amount_out<M: ManagedTypeApi>(
+    amount_in: &BigUint<M>,
+    reserve_in: &BigUint<M>,
+    reserve_out: &BigUint<M>,
+) -> BigUint<M> {
+    if reserve_in == &0u64 {
+        return BigUint::zero();
+    }
+
+    amount_in * reserve_out / reserve_in
+}
+
+/// Includes the pair fees
+pub fn get_price_impact_bps<M: ManagedTypeApi>(
+    spot_amount_out: &BigUint<M>,
+    amount_out: &BigUint<M>,
+) -> u64 {
+    if spot_amount_out == &0u64 || amount_out >= spot_amount_out {
+        return 0;
+    }
+
+    let price_impact = (spot_amount_out - amount_out) * MAX_BPS / spot_amount_out;
+
+    price_impact.to_u64().unwrap_or(MAX_BPS)
+}
@@ -28,6 +28,14 @@ pub static DUAL_YIELD_TOKEN_ID_STORAGE_KEY: &[u8] = b"dualYieldTokenId";
 pub static STAKING_TOKEN_ID_STORAGE_KEY: &[u8] = b"stakingTokenId";
 pub static DIVISION_SAFETY_CONSTANT_STORAGE_KEY: &[u8] = b"division_safety_constant";
 pub static MINIMUM_FARMING_EPOCHS_STORAGE_KEY: &[u8] = b"minimum_farming_epochs";
+pub static REWARD_PER_SHARE_STORAGE_KEY: &[u8] = b"reward_per_share";
+pub static PAIR_RESERVE_STORAGE_KEY: &[u8] = b"reserve";
+pub static TOTAL_FEE_PERCENT_STORAGE_KEY: &[u8] = b"total_fee_percent";
+pub static LP_TOKEN_SUPPLY_STORAGE_KEY: &[u8] = b"lp_token_supply";
+pub static WRAPPED_EGLD_TOKEN_ID_STORAGE_KEY: &[u8] = b"wrappedEgldTokenId";
+pub static LOCKED_TOKEN_ID_STORAGE_KEY: &[u8] = b"lockedTokenId";
+// value entries of the router's "pair_map" MapMapper
+pub static ROUTER_PAIR_MAP_VALUE_STORAGE_KEY: &[u8] = b"pair_map.mapped";
 
 #[multiversx_sc::module]
 pub trait ReadExternalStorageModule {
@@ -170,4 +178,79 @@ pub trait ReadExternalStorageModule {
             StorageKey::new(MINIMUM_FARMING_EPOCHS_STORAGE_KEY),
         )
     }
+
+    fn get_reward_per_share_mapper(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> SingleValueMapper<BigUint, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            sc_address,
+            StorageKey::new(REWARD_PER_SHARE_STORAGE_KEY),
+        )
+    }
+
+    fn get_pair_reserve_mapper(
+        &self,
+        sc_address: ManagedAddress,
+        token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<BigUint, ManagedAddress> {
+        let mut storage_key = StorageKey::new(PAIR_RESERVE_STORAGE_KEY);
+        storage_key.append_item(token_id);
+
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(sc_address, storage_key)
+    }
+
+    fn get_total_fee_percent_mapper(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> SingleValueMapper<u64, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            sc_address,
+            StorageKey::new(TOTAL_FEE_PERCENT_STORAGE_KEY),
+        )
+    }
+
+    fn get_lp_token_supply_mapper(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> SingleValueMapper<BigUint, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            sc_address,
+            StorageKey::new(LP_TOKEN_SUPPLY_STORAGE_KEY),
+        )
+    }
+
+    fn get_wrapped_egld_token_id_mapper(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> SingleValueMapper<TokenIdentifier, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            sc_address,
+            StorageKey::new(WRAPPED_EGLD_TOKEN_ID_STORAGE_KEY),
+        )
+    }
+
+    fn get_locked_token_id_mapper(
+        &self,
+        sc_address: ManagedAddress,
+    ) -> SingleValueMapper<TokenIdentifier, ManagedAddress> {
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(
+            sc_address,
+            StorageKey::new(LOCKED_TOKEN_ID_STORAGE_KEY),
+        )
+    }
+
+    /// The key is the router's PairTokens struct, so the order of the tokens matters
+    fn get_router_pair_address_mapper(
+        &self,
+        router_address: ManagedAddress,
+        first_token_id: &TokenIdentifier,
+        second_token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<ManagedAddress, ManagedAddress> {
+        let mut storage_key = StorageKey::new(ROUTER_PAIR_MAP_VALUE_STORAGE_KEY);
+        storage_key.append_item(first_token_id);
+        storage_key.append_item(second_token_id);
+
+        SingleValueMapper::<_, _, ManagedAddress>::new_from_address(router_address, storage_key)
+    }
 }
@@ -1,12 +1,11 @@
-use core::convert::TryFrom;
-
-use router::multi_pair_swap::{
-    SWAP_TOKENS_FIXED_INPUT_FUNC_NAME, SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME,
-};
-
 use crate::{
-    config::{ROUTER_SWAP_ARGS_LEN, SEND_TOKENS_ARGS_LEN, SWAP_ARGS_LEN},
-    external_sc_interactions,
+    config::MAX_PERCENTAGE,
+    external_sc_interactions::{self, pair_actions::ADD_REMOVE_LIQUIDITY_MIN_AMOUNT},
+    integrator_fees::IntegratorFee,
+    task_types::{
+        AddLiquidityArgs, CallContractArgs, RemoveLiquidityArgs, RouterSwapArgs, SendAmount,
+        SendAndContinueArgs, SwapArgs, SwapFunction, Task, TaskWithSlots,
+    },
 };
 
 multiversx_sc::imports!();
@@ -14,6 +13,9 @@ multiversx_sc::derive_imports!();
 
 pub type PaymentsVec<M> = ManagedVec<M, EsdtTokenPayment<M>>;
 
+/// The task type, the input slot, the output slot and the task arguments
+pub type SlotTaskType<M> = MultiValue4<TaskType, usize, usize, ManagedVec<M, ManagedBuffer<M>>>;
+
 #[derive(TypeAbi, TopEncode, TopDecode, PartialEq, ManagedVecItem)]
 pub enum TaskType {
     WrapEGLD,
@@ -21,13 +23,36 @@ pub enum TaskType {
     Swap,
     RouterSwap,
     SendEgldOrEsdt,
+    AddLiquidity,
+    RemoveLiquidity,
+    EnterFarm,
+    ExitFarm,
+    ClaimRewards,
+    SendAndContinue,
+    AssertMinBalance,
+    CallContract,
+    LockTokens,
+}
+
+pub struct ComposeState<M: ManagedTypeApi> {
+    pub caller: ManagedAddress<M>,
+    pub dest_addr: ManagedAddress<M>,
+    pub slots: ManagedVec<M, EgldOrEsdtTokenPayment<M>>,
+    pub result_slot: usize,
+    pub payments_to_return: PaymentsVec<M>,
 }
 
 #[multiversx_sc::module]
 pub trait TaskCall:
-    external_sc_interactions::pair_actions::PairActionsModule
+    read_external_storage::ReadExternalStorageModule
+    + external_sc_interactions::contract_call_actions::ContractCallActionsModule
+    + external_sc_interactions::energy_factory_actions::EnergyFactoryActionsModule
+    + external_sc_interactions::farm_actions::FarmActionsModule
+    + external_sc_interactions::pair_actions::PairActionsModule
     + external_sc_interactions::router_actions::RouterActionsModule
     + external_sc_interactions::wegld_swap::WegldWrapModule
+    + crate::integrator_fees::IntegratorFeesModule
+    + crate::legacy_task_decoder::LegacyTaskDecoderModule
 {
     #[payable("*")]
     #[endpoint(composeTasks)]
@@ -36,51 +61,385 @@ pub trait TaskCall:
         min_expected_token_out: EgldOrEsdtTokenPayment,
         tasks: MultiValueEncoded<MultiValue2<TaskType, ManagedVec<ManagedBuffer>>>,
     ) {
-        let mut payment_for_next_task = self.call_value().egld_or_single_esdt();
-        let mut payments_to_return = PaymentsVec::new();
+        let state = self.execute_legacy_tasks(tasks);
+        self.send_compose_result(state, min_expected_token_out, None);
+    }
 
-        let mut dest_addr = self.blockchain().get_caller();
+    /// Same as composeTasks, but fee_bps of the final output is sent to the integrator.
+    /// The fee can't be higher than the max set by the owner.
+    /// min_expected_token_out is checked against the output left after the fee.
+    #[payable("*")]
+    #[endpoint(composeTasksWithIntegratorFee)]
+    fn compose_tasks_with_integrator_fee(
+        &self,
+        integrator: ManagedAddress,
+        fee_bps: u64,
+        min_expected_token_out: EgldOrEsdtTokenPayment,
+        tasks: MultiValueEncoded<MultiValue2<TaskType, ManagedVec<ManagedBuffer>>>,
+    ) {
+        let integrator_fee = IntegratorFee {
+            integrator,
+            fee_bps,
+        };
+        let state = self.execute_legacy_tasks(tasks);
+        self.send_compose_result(state, min_expected_token_out, Some(integrator_fee));
+    }
 
-        for task in tasks.into_iter() {
-            let (task_type, args) = task.into_tuple();
+    /// Each payment is placed in its own slot, in payment order.
+    /// Every task takes the whole payment from its input slot and writes its output
+    /// to its output slot. If the output slot is not empty, it must hold the same token,
+    /// in which case the amounts are added.
+    /// A new slot is created by writing to the slot after the last one.
+    /// min_expected_token_out is checked against the output slot of the last task.
+    /// All the other payments left in slots are also sent back.
+    /// SendEgldOrEsdt has to be the last task, otherwise the call fails.
+    /// If an integrator fee is given, it is taken the same way as in composeTasksWithIntegratorFee.
+    #[payable("*")]
+    #[endpoint(composeTasksWithSlots)]
+    fn compose_tasks_with_slots(
+        &self,
+        min_expected_token_out: EgldOrEsdtTokenPayment,
+        opt_integrator_fee: Option<IntegratorFee<Self::Api>>,
+        tasks: MultiValueEncoded<SlotTaskType<Self::Api>>,
+    ) {
+        let mut state = self.new_compose_state(self.get_payments_as_slots());
+
+        let mut tasks_ended = false;
+        for (task_index, task) in tasks.into_iter().enumerate() {
+            self.require_no_task_after_send(task_index, tasks_ended);
+
+            let (task_type, input_slot, output_slot, args) = task.into_tuple();
+            let task = TaskWithSlots {
+                input_slot,
+                output_slot,
+                task: self.decode_legacy_task(task_index, task_type, args),
+            };
+            tasks_ended = !self.execute_task(&mut state, task_index, task);
+        }
+
+        self.send_compose_result(state, min_expected_token_out, opt_integrator_fee);
+    }
+
+    /// Same as composeTasksWithSlots, but the tasks and their arguments are typed.
+    /// The arguments are checked the same way as the composeTasks ones.
+    /// Each of min_expected_tokens_out is checked against the total amount of that token
+    /// sent back, after the integrator fee, so leftovers can be checked as well.
+    /// Every token sent back needs an entry in min_expected_tokens_out,
+    /// with the result of the last task as output 0, followed by the leftovers.
+    #[payable("*")]
+    #[endpoint(composeTypedTasks)]
+    fn compose_typed_tasks(
+        &self,
+        min_expected_tokens_out: ManagedVec<EgldOrEsdtTokenPayment>,
+        opt_integrator_fee: Option<IntegratorFee<Self::Api>>,
+        tasks: MultiValueEncoded<TaskWithSlots<Self::Api>>,
+    ) {
+        let mut state = self.new_compose_state(self.get_payments_as_slots());
 
-            let payment_for_current_task = payment_for_next_task.clone();
+        let mut tasks_ended = false;
+        for (task_index, task) in tasks.into_iter().enumerate() {
+            self.require_no_task_after_send(task_index, tasks_ended);
+            self.validate_typed_task(task_index, &task.task);
+            tasks_ended = !self.execute_task(&mut state, task_index, task);
+        }
 
-            payment_for_next_task = match task_type {
-                TaskType::WrapEGLD => self.wrap_egld(payment_for_current_task),
-                TaskType::UnwrapEGLD => self.unwrap_egld(payment_for_current_task),
-                TaskType::Swap => {
-                    self.swap(payment_for_current_task, &mut payments_to_return, args)
+        require!(
+            !min_expected_tokens_out.is_empty(),
+            "No expected outputs given"
+        );
+
+        let (dest_addr, result_payment, other_payments) =
+            self.collect_compose_outputs(state, opt_integrator_fee);
+        for (output_index, expected_token) in min_expected_tokens_out.iter().enumerate() {
+            let mut total_amount = BigUint::zero();
+            let all_outputs = core::iter::once(result_payment.clone()).chain(other_payments.iter());
+            for payment in all_outputs {
+                if self.is_same_token(&payment, &expected_token) {
+                    total_amount += payment.amount;
                 }
-                TaskType::RouterSwap => {
-                    self.router_swap(payment_for_current_task, &mut payments_to_return, args)
+            }
+
+            if total_amount < expected_token.amount {
+                sc_panic!(
+                    "Expected output {}: less than the min amount",
+                    output_index
+                );
+            }
+        }
+
+        let all_outputs = core::iter::once(result_payment.clone()).chain(other_payments.iter());
+        for (output_index, payment) in all_outputs.enumerate() {
+            let has_min_amount = min_expected_tokens_out
+                .iter()
+                .any(|expected_token| self.is_same_token(&payment, &expected_token));
+            if payment.amount > 0 && !has_min_amount {
+                sc_panic!("Output {}: no min amount given", output_index);
+            }
+        }
+
+        self.send_resulted_payments(dest_addr, result_payment, other_payments);
+    }
+
+    fn is_same_token(
+        &self,
+        payment: &EgldOrEsdtTokenPayment,
+        other_payment: &EgldOrEsdtTokenPayment,
+    ) -> bool {
+        payment.token_identifier == other_payment.token_identifier
+            && payment.token_nonce == other_payment.token_nonce
+    }
+
+    fn execute_legacy_tasks(
+        &self,
+        tasks: MultiValueEncoded<MultiValue2<TaskType, ManagedVec<ManagedBuffer>>>,
+    ) -> ComposeState<Self::Api> {
+        let payment = self.call_value().egld_or_single_esdt();
+        let mut state = self.new_compose_state(ManagedVec::from_single_item(payment));
+
+        for (task_index, task) in tasks.into_iter().enumerate() {
+            let (task_type, args) = task.into_tuple();
+            let task = TaskWithSlots {
+                input_slot: 0,
+                output_slot: 0,
+                task: self.decode_legacy_task(task_index, task_type, args),
+            };
+            if !self.execute_task(&mut state, task_index, task) {
+                break;
+            }
+        }
+
+        state
+    }
+
+    fn get_payments_as_slots(&self) -> ManagedVec<EgldOrEsdtTokenPayment> {
+        match self.call_value().any_payment() {
+            EgldOrMultiEsdtPayment::Egld(egld_amount) => {
+                ManagedVec::from_single_item(EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::egld(),
+                    0,
+                    egld_amount,
+                ))
+            }
+            EgldOrMultiEsdtPayment::MultiEsdt(esdt_payments) => {
+                let mut slots = ManagedVec::new();
+                for esdt_payment in &esdt_payments {
+                    slots.push(EgldOrEsdtTokenPayment::from(esdt_payment));
                 }
-                TaskType::SendEgldOrEsdt => {
-                    require!(
-                        args.len() == SEND_TOKENS_ARGS_LEN,
-                        "Invalid number of arguments!"
-                    );
-                    let new_destination = ManagedAddress::try_from(args.get(0).clone_value())
-                        .unwrap_or_else(|err| sc_panic!(err));
-
-                    dest_addr = new_destination;
-                    break;
+
+                slots
+            }
+        }
+    }
+
+    fn new_compose_state(
+        &self,
+        slots: ManagedVec<EgldOrEsdtTokenPayment>,
+    ) -> ComposeState<Self::Api> {
+        let caller = self.blockchain().get_caller();
+
+        ComposeState {
+            dest_addr: caller.clone(),
+            caller,
+            slots,
+            result_slot: 0,
+            payments_to_return: PaymentsVec::new(),
+        }
+    }
+
+    /// composeTasks skips the tasks following SendEgldOrEsdt,
+    /// while the slot formats reject them
+    fn require_no_task_after_send(&self, task_index: usize, tasks_ended: bool) {
+        if tasks_ended {
+            sc_panic!("Task {}: no task can follow SendEgldOrEsdt", task_index);
+        }
+    }
+
+    /// Returns false if no other task should be executed
+    fn execute_task(
+        &self,
+        state: &mut ComposeState<Self::Api>,
+        task_index: usize,
+        task_with_slots: TaskWithSlots<Self::Api>,
+    ) -> bool {
+        let input_slot = task_with_slots.input_slot;
+        let output_slot = task_with_slots.output_slot;
+        let payment_for_current_task = self.take_slot_payment(&mut state.slots, input_slot);
+        let payments_to_return = &mut state.payments_to_return;
+
+        let payment_out = match task_with_slots.task {
+            Task::WrapEGLD => self.wrap_egld(payment_for_current_task),
+            Task::UnwrapEGLD => self.unwrap_egld(payment_for_current_task),
+            Task::Swap(args) => self.swap(payment_for_current_task, payments_to_return, args),
+            Task::RouterSwap(args) => {
+                self.router_swap(payment_for_current_task, payments_to_return, args)
+            }
+            Task::SendEgldOrEsdt(args) => {
+                state.dest_addr = args.destination;
+                let _ = state.slots.set(input_slot, &payment_for_current_task);
+                state.result_slot = input_slot;
+
+                return false;
+            }
+            Task::AddLiquidity(args) => {
+                self.add_liquidity(payment_for_current_task, payments_to_return, args)
+            }
+            Task::RemoveLiquidity(args) => {
+                self.remove_liquidity(payment_for_current_task, payments_to_return, args)
+            }
+            Task::EnterFarm(args) => self.farm_task(
+                &TaskType::EnterFarm,
+                &state.caller,
+                payment_for_current_task,
+                payments_to_return,
+                args.farm_address,
+            ),
+            Task::ExitFarm(args) => self.farm_task(
+                &TaskType::ExitFarm,
+                &state.caller,
+                payment_for_current_task,
+                payments_to_return,
+                args.farm_address,
+            ),
+            Task::ClaimRewards(args) => self.farm_task(
+                &TaskType::ClaimRewards,
+                &state.caller,
+                payment_for_current_task,
+                payments_to_return,
+                args.farm_address,
+            ),
+            Task::SendAndContinue(args) => self.send_and_continue(
+                &state.caller,
+                payment_for_current_task,
+                payments_to_return,
+                args,
+            ),
+            Task::AssertMinBalance(args) => {
+                if payment_for_current_task.token_identifier != args.token_id
+                    || payment_for_current_task.amount < args.min_amount
+                {
+                    sc_panic!("Task {}: balance less than the min amount", task_index);
                 }
-            };
+
+                payment_for_current_task
+            }
+            Task::CallContract(args) => self.call_contract(
+                &state.caller,
+                payment_for_current_task,
+                payments_to_return,
+                args,
+            ),
+            Task::LockTokens(args) => {
+                let locked_tokens =
+                    self.lock_tokens(&state.caller, payment_for_current_task, args.lock_epochs);
+
+                EgldOrEsdtTokenPayment::from(locked_tokens)
+            }
+        };
+
+        self.put_slot_payment(&mut state.slots, output_slot, payment_out);
+        state.result_slot = output_slot;
+
+        true
+    }
+
+    fn send_compose_result(
+        &self,
+        state: ComposeState<Self::Api>,
+        min_expected_token_out: EgldOrEsdtTokenPayment,
+        opt_integrator_fee: Option<IntegratorFee<Self::Api>>,
+    ) {
+        let (dest_addr, result_payment, other_payments) =
+            self.collect_compose_outputs(state, opt_integrator_fee);
+        self.require_min_expected_token(&min_expected_token_out, &result_payment);
+
+        self.send_resulted_payments(dest_addr, result_payment, other_payments);
+    }
+
+    /// Returns the destination, the payment from the result slot
+    /// and all the other payments left in slots or to be returned.
+    /// The integrator fee, if any, is taken from the payment of the result slot.
+    fn collect_compose_outputs(
+        &self,
+        mut state: ComposeState<Self::Api>,
+        opt_integrator_fee: Option<IntegratorFee<Self::Api>>,
+    ) -> (
+        ManagedAddress,
+        EgldOrEsdtTokenPayment,
+        ManagedVec<EgldOrEsdtTokenPayment>,
+    ) {
+        let mut result_payment = self.take_slot_payment(&mut state.slots, state.result_slot);
+        if let Some(integrator_fee) = opt_integrator_fee {
+            self.require_valid_integrator_fee(&integrator_fee.integrator, integrator_fee.fee_bps);
+
+            result_payment = self.deduct_integrator_fee(
+                &state.caller,
+                &integrator_fee.integrator,
+                integrator_fee.fee_bps,
+                result_payment,
+            );
         }
-        self.send_resulted_payments(
-            dest_addr,
-            min_expected_token_out,
-            payment_for_next_task,
-            &mut payments_to_return,
-        )
+
+        let mut other_payments = ManagedVec::new();
+        for payment in &state.payments_to_return {
+            other_payments.push(EgldOrEsdtTokenPayment::from(payment));
+        }
+        for slot_payment in &state.slots {
+            if slot_payment.amount > 0 {
+                other_payments.push(slot_payment);
+            }
+        }
+
+        (state.dest_addr, result_payment, other_payments)
+    }
+
+    /// Empties the slot
+    fn take_slot_payment(
+        &self,
+        slots: &mut ManagedVec<EgldOrEsdtTokenPayment>,
+        slot_index: usize,
+    ) -> EgldOrEsdtTokenPayment {
+        require!(slot_index < slots.len(), "Invalid slot!");
+
+        let payment = slots.get(slot_index);
+        let _ = slots.set(slot_index, &EgldOrEsdtTokenPayment::no_payment());
+
+        payment
+    }
+
+    fn put_slot_payment(
+        &self,
+        slots: &mut ManagedVec<EgldOrEsdtTokenPayment>,
+        slot_index: usize,
+        payment: EgldOrEsdtTokenPayment,
+    ) {
+        require!(slot_index <= slots.len(), "Invalid slot!");
+
+        if slot_index == slots.len() {
+            slots.push(payment);
+            return;
+        }
+
+        let mut slot_payment = slots.get(slot_index);
+        if slot_payment.amount == 0 {
+            let _ = slots.set(slot_index, &payment);
+            return;
+        }
+
+        require!(
+            slot_payment.token_identifier == payment.token_identifier
+                && slot_payment.token_nonce == payment.token_nonce,
+            "Slot holds a different token!"
+        );
+
+        slot_payment.amount += payment.amount;
+        let _ = slots.set(slot_index, &slot_payment);
     }
 
     fn swap(
         &self,
         payment_for_current_task: EgldOrEsdtTokenPayment,
         payments_to_return: &mut PaymentsVec<Self::Api>,
-        args: ManagedVec<ManagedBuffer>,
+        args: SwapArgs<Self::Api>,
     ) -> EgldOrEsdtTokenPayment {
         require!(
             !payment_for_current_task.token_identifier.is_egld(),
@@ -88,46 +447,27 @@ pub trait TaskCall:
         );
         let payment_in = payment_for_current_task.unwrap_esdt();
 
-        require!(
-            args.len() == SWAP_ARGS_LEN,
-            "Incorrect arguments for swap task!"
-        );
-
-        let function_in_out = args.get(0).clone_value();
-        let token_out = TokenIdentifier::from(args.get(1).clone_value());
-        let min_amount_out = BigUint::from(args.get(2).clone_value());
-
-        // if function_in_out
-        let swap_tokens_fixed_input_function =
-            ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME);
-        let swap_tokens_fixed_output_function =
-            ManagedBuffer::from(SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME);
-        require!(
-            function_in_out == swap_tokens_fixed_input_function
-                || function_in_out == swap_tokens_fixed_output_function,
-            "Invalid function name for swap"
-        );
-
-        let payment_out = if function_in_out == swap_tokens_fixed_input_function {
-            self.perform_swap_tokens_fixed_input(
-                payment_in.token_identifier,
-                payment_in.amount,
-                token_out,
-                min_amount_out,
-            )
-        } else {
-            let returned_payments_by_pair = self.perform_swap_tokens_fixed_output(
+        let payment_out = match args.function {
+            SwapFunction::FixedInput => self.perform_swap_tokens_fixed_input(
                 payment_in.token_identifier,
                 payment_in.amount,
-                token_out,
-                min_amount_out,
-            );
-            let payment_out = returned_payments_by_pair.get(0);
-            if returned_payments_by_pair.len() == 2 {
-                let payment_in_leftover = returned_payments_by_pair.get(1);
-                payments_to_return.push(payment_in_leftover);
+                args.token_out,
+                args.min_out,
+            ),
+            SwapFunction::FixedOutput => {
+                let returned_payments_by_pair = self.perform_swap_tokens_fixed_output(
+                    payment_in.token_identifier,
+                    payment_in.amount,
+                    args.token_out,
+                    args.min_out,
+                );
+                let payment_out = returned_payments_by_pair.get(0);
+                if returned_payments_by_pair.len() == 2 {
+                    let payment_in_leftover = returned_payments_by_pair.get(1);
+                    payments_to_return.push(payment_in_leftover);
+                }
+                payment_out
             }
-            payment_out
         };
 
         payment_out.into()
@@ -137,18 +477,21 @@ pub trait TaskCall:
         &self,
         payment_for_current_task: EgldOrEsdtTokenPayment,
         payments_to_return: &mut PaymentsVec<Self::Api>,
-        args: ManagedVec<ManagedBuffer>,
+        args: RouterSwapArgs<Self::Api>,
     ) -> EgldOrEsdtTokenPayment {
         require!(
             !payment_for_current_task.token_identifier.is_egld(),
             "EGLD can't be swapped!"
         );
         require!(
-            args.len() % ROUTER_SWAP_ARGS_LEN == 0,
-            "Invalid number of router swap arguments"
+            !args.swap_operations.is_empty(),
+            "No router swap operations given"
         );
+
         let payment_in = payment_for_current_task.unwrap_esdt();
-        let mut returned_payments_by_router = self.multi_pair_swap(payment_in, args);
+        let last_operation = args.swap_operations.get(args.swap_operations.len() - 1);
+        let mut returned_payments_by_router =
+            self.multi_pair_swap(payment_in, args.swap_operations);
 
         require!(
             !returned_payments_by_router.is_empty(),
@@ -157,30 +500,310 @@ pub trait TaskCall:
 
         let last_payment_index = returned_payments_by_router.len() - 1;
         let payment_out = returned_payments_by_router.take(last_payment_index);
+        require!(
+            payment_out.token_identifier == last_operation.token_out
+                && payment_out.amount >= last_operation.min_out,
+            "Router swap output is less or different than the one required!"
+        );
         payments_to_return.append_vec(returned_payments_by_router);
         EgldOrEsdtTokenPayment::from(payment_out)
     }
 
-    fn send_resulted_payments(
+    /// If no swap percentage is given, the optimal amount is swapped to the other token.
+    /// The swap percentage uses MAX_PERCENTAGE as 100%.
+    fn add_liquidity(
         &self,
-        dest_addr: ManagedAddress,
-        min_expected_token_out: EgldOrEsdtTokenPayment,
         payment_for_current_task: EgldOrEsdtTokenPayment,
         payments_to_return: &mut PaymentsVec<Self::Api>,
-    ) {
-        self.require_min_expected_token(&min_expected_token_out, &payment_for_current_task);
-        if payment_for_current_task.token_identifier.is_egld() {
-            self.send()
-                .direct_egld(&dest_addr, &payment_for_current_task.amount);
+        args: AddLiquidityArgs<Self::Api>,
+    ) -> EgldOrEsdtTokenPayment {
+        require!(
+            !payment_for_current_task.token_identifier.is_egld(),
+            "EGLD can't be added as liquidity!"
+        );
+
+        let mut payment_in = payment_for_current_task.unwrap_esdt();
+        let other_token_id = args.other_token_id;
+        let pair_address =
+            self.get_pair(payment_in.token_identifier.clone(), other_token_id.clone());
+
+        let swap_amount = self.get_add_liquidity_swap_amount(
+            pair_address.clone(),
+            &payment_in,
+            args.opt_swap_percentage,
+        );
+        payment_in.amount -= &swap_amount;
+
+        let other_token_payment = self.perform_swap_tokens_fixed_input(
+            payment_in.token_identifier.clone(),
+            swap_amount,
+            other_token_id,
+            BigUint::from(1u64),
+        );
+
+        let first_token_id = self.get_first_token_id_mapper(pair_address.clone()).get();
+        let (first_payment, second_payment) = if payment_in.token_identifier == first_token_id {
+            (payment_in, other_token_payment)
+        } else {
+            (other_token_payment, payment_in)
+        };
+
+        let mut returned_payments_by_pair = self.perform_add_liquidity(
+            pair_address,
+            first_payment,
+            second_payment,
+            BigUint::from(ADD_REMOVE_LIQUIDITY_MIN_AMOUNT),
+            BigUint::from(ADD_REMOVE_LIQUIDITY_MIN_AMOUNT),
+        );
+        let lp_payment = returned_payments_by_pair.take(0);
+        if let Some(min_lp_out) = args.opt_min_lp_out {
+            require!(
+                lp_payment.amount >= min_lp_out,
+                "Add liquidity: less LP tokens than the min amount"
+            );
+        }
+        payments_to_return.append_vec(returned_payments_by_pair);
+
+        EgldOrEsdtTokenPayment::from(lp_payment)
+    }
+
+    fn get_add_liquidity_swap_amount(
+        &self,
+        pair_address: ManagedAddress,
+        payment: &EsdtTokenPayment,
+        opt_swap_percentage: Option<u64>,
+    ) -> BigUint {
+        match opt_swap_percentage {
+            Some(swap_percentage) => {
+                require!(
+                    swap_percentage > 0 && swap_percentage < MAX_PERCENTAGE,
+                    "Invalid swap percentage for add liquidity task!"
+                );
+
+                &payment.amount * swap_percentage / MAX_PERCENTAGE
+            }
+            None => self.get_optimal_swap_amount(pair_address, payment),
+        }
+    }
+
+    /// The other token of the pair is returned
+    fn remove_liquidity(
+        &self,
+        payment_for_current_task: EgldOrEsdtTokenPayment,
+        payments_to_return: &mut PaymentsVec<Self::Api>,
+        args: RemoveLiquidityArgs<Self::Api>,
+    ) -> EgldOrEsdtTokenPayment {
+        require!(
+            !payment_for_current_task.token_identifier.is_egld(),
+            "EGLD is not an LP token!"
+        );
+
+        let payment_in = payment_for_current_task.unwrap_esdt();
+        let token_out = args.token_out;
+        let pair_address = self.get_pair(token_out.clone(), args.other_token_id);
+
+        let min_out = args
+            .opt_min_out
+            .unwrap_or_else(|| BigUint::from(ADD_REMOVE_LIQUIDITY_MIN_AMOUNT));
+        let min_other = BigUint::from(ADD_REMOVE_LIQUIDITY_MIN_AMOUNT);
+        let first_token_id = self.get_first_token_id_mapper(pair_address.clone()).get();
+        let (first_token_amount_min, second_token_amount_min) = if first_token_id == token_out {
+            (min_out, min_other)
+        } else {
+            (min_other, min_out)
+        };
+
+        let (first_payment, second_payment) = self.perform_remove_liquidity(
+            pair_address,
+            payment_in,
+            first_token_amount_min,
+            second_token_amount_min,
+        );
+        let payment_out = if first_payment.token_identifier == token_out {
+            payments_to_return.push(second_payment);
+            first_payment
         } else {
-            payments_to_return.push(EsdtTokenPayment::new(
-                payment_for_current_task.token_identifier.unwrap_esdt(),
-                payment_for_current_task.token_nonce,
-                payment_for_current_task.amount,
+            payments_to_return.push(first_payment);
+            second_payment
+        };
+
+        EgldOrEsdtTokenPayment::from(payment_out)
+    }
+
+    /// The rewards are returned, while the farm tokens or the farming tokens, for ExitFarm,
+    /// are passed to the next task.
+    fn farm_task(
+        &self,
+        task_type: &TaskType,
+        caller: &ManagedAddress,
+        payment_for_current_task: EgldOrEsdtTokenPayment,
+        payments_to_return: &mut PaymentsVec<Self::Api>,
+        farm_address: ManagedAddress,
+    ) -> EgldOrEsdtTokenPayment {
+        require!(
+            !payment_for_current_task.token_identifier.is_egld(),
+            "EGLD can't be used in farm tasks!"
+        );
+
+        let payment_in = payment_for_current_task.unwrap_esdt();
+        let user = caller.clone();
+
+        let (payment_out, rewards) = match task_type {
+            TaskType::EnterFarm => self.call_enter_farm(farm_address, user, payment_in),
+            TaskType::ExitFarm => self.call_exit_farm(farm_address, user, payment_in),
+            TaskType::ClaimRewards => self.call_claim_rewards(farm_address, user, payment_in),
+            _ => sc_panic!("Invalid farm task!"),
+        };
+        if rewards.amount > 0 {
+            payments_to_return.push(rewards);
+        }
+
+        EgldOrEsdtTokenPayment::from(payment_out)
+    }
+
+    /// Sends a fixed amount or a percentage of the payment, optionally calling a whitelisted
+    /// endpoint. The remaining payment is passed to the next task.
+    fn send_and_continue(
+        &self,
+        caller: &ManagedAddress,
+        payment_for_current_task: EgldOrEsdtTokenPayment,
+        payments_to_return: &mut PaymentsVec<Self::Api>,
+        args: SendAndContinueArgs<Self::Api>,
+    ) -> EgldOrEsdtTokenPayment {
+        let send_amount = self.get_send_amount(&payment_for_current_task.amount, args.amount);
+
+        let payment_to_send = EgldOrEsdtTokenPayment::new(
+            payment_for_current_task.token_identifier.clone(),
+            payment_for_current_task.token_nonce,
+            send_amount.clone(),
+        );
+        match args.opt_call {
+            Some(call) => {
+                let back_transfers = self.call_whitelisted_contract(
+                    args.destination,
+                    call.endpoint_name,
+                    call.args,
+                    payment_to_send,
+                );
+                if back_transfers.total_egld_amount > 0 {
+                    self.send()
+                        .direct_egld(caller, &back_transfers.total_egld_amount);
+                }
+                payments_to_return.append_vec(back_transfers.esdt_payments);
+            }
+            None => {
+                self.send().direct(
+                    &args.destination,
+                    &payment_to_send.token_identifier,
+                    payment_to_send.token_nonce,
+                    &payment_to_send.amount,
+                );
+            }
+        }
+
+        EgldOrEsdtTokenPayment::new(
+            payment_for_current_task.token_identifier,
+            payment_for_current_task.token_nonce,
+            payment_for_current_task.amount - send_amount,
+        )
+    }
+
+    /// EGLD back transfers that are not passed on are sent to the caller
+    fn call_contract(
+        &self,
+        caller: &ManagedAddress,
+        payment_for_current_task: EgldOrEsdtTokenPayment,
+        payments_to_return: &mut PaymentsVec<Self::Api>,
+        args: CallContractArgs<Self::Api>,
+    ) -> EgldOrEsdtTokenPayment {
+        let back_transfers = self.call_whitelisted_contract(
+            args.sc_address,
+            args.endpoint_name,
+            args.args,
+            payment_for_current_task,
+        );
+
+        let mut opt_payment_out = None;
+        if args.output_token_id.is_egld() {
+            opt_payment_out = Some(EgldOrEsdtTokenPayment::new(
+                EgldOrEsdtTokenIdentifier::egld(),
+                0,
+                back_transfers.total_egld_amount,
             ));
+        } else if back_transfers.total_egld_amount > 0 {
+            self.send()
+                .direct_egld(caller, &back_transfers.total_egld_amount);
+        }
+
+        for payment in &back_transfers.esdt_payments {
+            let token_id = EgldOrEsdtTokenIdentifier::esdt(payment.token_identifier.clone());
+            if opt_payment_out.is_none() && args.output_token_id == token_id {
+                opt_payment_out = Some(EgldOrEsdtTokenPayment::from(payment));
+            } else {
+                payments_to_return.push(payment);
+            }
+        }
+
+        let payment_out = opt_payment_out
+            .unwrap_or_else(|| sc_panic!("Call contract: output token not received"));
+        require!(
+            payment_out.amount > 0,
+            "Call contract: output token not received"
+        );
+
+        payment_out
+    }
+
+    fn get_send_amount(&self, payment_amount: &BigUint, amount: SendAmount<Self::Api>) -> BigUint {
+        let send_amount = match amount {
+            SendAmount::Fixed(amount) => amount,
+            SendAmount::Percentage(percentage) => {
+                require!(
+                    percentage <= MAX_PERCENTAGE,
+                    "Invalid percentage for send and continue task!"
+                );
+
+                payment_amount * percentage / MAX_PERCENTAGE
+            }
+        };
+        require!(
+            send_amount > 0 && &send_amount <= payment_amount,
+            "Invalid amount for send and continue task!"
+        );
+
+        send_amount
+    }
+
+    fn send_resulted_payments(
+        &self,
+        dest_addr: ManagedAddress,
+        result_payment: EgldOrEsdtTokenPayment,
+        other_payments: ManagedVec<EgldOrEsdtTokenPayment>,
+    ) {
+        let mut egld_amount = BigUint::zero();
+        let mut esdt_payments = PaymentsVec::new();
+        // the whole payment might have been sent by the previous tasks
+        for payment in other_payments.iter().chain(core::iter::once(result_payment)) {
+            if payment.amount == 0 {
+                continue;
+            }
+
+            if payment.token_identifier.is_egld() {
+                egld_amount += payment.amount;
+            } else {
+                esdt_payments.push(EsdtTokenPayment::new(
+                    payment.token_identifier.unwrap_esdt(),
+                    payment.token_nonce,
+                    payment.amount,
+                ));
+            }
+        }
+
+        if egld_amount > 0 {
+            self.send().direct_egld(&dest_addr, &egld_amount);
         }
-        if !payments_to_return.is_empty() {
-            self.send().direct_multi(&dest_addr, payments_to_return);
+        if !esdt_payments.is_empty() {
+            self.send().direct_multi(&dest_addr, &esdt_payments);
         }
     }
 
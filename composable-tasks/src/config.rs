@@ -5,12 +5,28 @@ use crate::external_sc_interactions;
 pub const SWAP_ARGS_LEN: usize = 3;
 pub const ROUTER_SWAP_ARGS_LEN: usize = 4;
 pub const SEND_TOKENS_ARGS_LEN: usize = 1;
+pub const SEND_AND_CONTINUE_ARGS_LEN: usize = 3;
+pub const SEND_AND_CONTINUE_WITH_CALL_MIN_ARGS_LEN: usize = 4;
+pub const ADD_LIQUIDITY_ARGS_LEN: usize = 1;
+pub const ADD_LIQUIDITY_WITH_RATIO_ARGS_LEN: usize = 2;
+pub const ADD_LIQUIDITY_WITH_MIN_OUT_ARGS_LEN: usize = 3;
+pub const REMOVE_LIQUIDITY_ARGS_LEN: usize = 2;
+pub const REMOVE_LIQUIDITY_WITH_MIN_OUT_ARGS_LEN: usize = 3;
+pub const FARM_ARGS_LEN: usize = 1;
+pub const ASSERT_MIN_BALANCE_ARGS_LEN: usize = 2;
+pub const CALL_CONTRACT_MIN_ARGS_LEN: usize = 3;
+pub const LOCK_TOKENS_ARGS_LEN: usize = 1;
+pub const MAX_PERCENTAGE: u64 = 10_000;
 pub const SWAP_TOKENS_FIXED_INPUT_FUNC_NAME: &[u8] = b"swapTokensFixedInput";
 pub const SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME: &[u8] = b"swapTokensFixedOutput";
+pub const SEND_FIXED_AMOUNT: &[u8] = b"fixedAmount";
+pub const SEND_PERCENTAGE: &[u8] = b"percentage";
 
 #[multiversx_sc::module]
 pub trait ConfigModule:
-    external_sc_interactions::pair_actions::PairActionsModule
+    external_sc_interactions::contract_call_actions::ContractCallActionsModule
+    + external_sc_interactions::energy_factory_actions::EnergyFactoryActionsModule
+    + external_sc_interactions::pair_actions::PairActionsModule
     + external_sc_interactions::router_actions::RouterActionsModule
     + external_sc_interactions::wegld_swap::WegldWrapModule
 {
@@ -25,4 +41,45 @@ pub trait ConfigModule:
     fn set_router_address(&self, new_addr: ManagedAddress) {
         self.router_addr().set(new_addr);
     }
+
+    /// The energy factory has to whitelist this contract for the LockTokens task,
+    /// which also needs the burn role for the base token of the energy factory
+    #[only_owner]
+    #[endpoint(setEnergyFactoryAddr)]
+    fn set_energy_factory_address(&self, new_addr: ManagedAddress) {
+        self.energy_factory_addr().set(new_addr);
+    }
+
+    /// The whitelisted endpoints can be called with the users' payments
+    /// through the CallContract task
+    #[only_owner]
+    #[endpoint(addWhitelistedContractCalls)]
+    fn add_whitelisted_contract_calls(
+        &self,
+        sc_address: ManagedAddress,
+        endpoint_names: MultiValueEncoded<ManagedBuffer>,
+    ) {
+        require!(
+            self.blockchain().is_smart_contract(&sc_address),
+            "Invalid SC address"
+        );
+
+        let mut mapper = self.whitelisted_contract_calls(&sc_address);
+        for endpoint_name in endpoint_names {
+            let _ = mapper.insert(endpoint_name);
+        }
+    }
+
+    #[only_owner]
+    #[endpoint(removeWhitelistedContractCalls)]
+    fn remove_whitelisted_contract_calls(
+        &self,
+        sc_address: ManagedAddress,
+        endpoint_names: MultiValueEncoded<ManagedBuffer>,
+    ) {
+        let mut mapper = self.whitelisted_contract_calls(&sc_address);
+        for endpoint_name in endpoint_names {
+            let _ = mapper.swap_remove(&endpoint_name);
+        }
+    }
 }
@@ -0,0 +1,34 @@
+multiversx_sc::imports!();
+
+/// Only the endpoints whitelisted by the owner can be called with the users' payments
+#[multiversx_sc::module]
+pub trait ContractCallActionsModule {
+    fn call_whitelisted_contract(
+        &self,
+        sc_address: ManagedAddress,
+        endpoint_name: ManagedBuffer,
+        args: ManagedVec<ManagedBuffer>,
+        payment: EgldOrEsdtTokenPayment,
+    ) -> BackTransfers<Self::Api> {
+        require!(
+            self.whitelisted_contract_calls(&sc_address)
+                .contains(&endpoint_name),
+            "Contract call not whitelisted"
+        );
+
+        self.tx()
+            .to(&sc_address)
+            .raw_call(endpoint_name)
+            .arguments_raw(args.into())
+            .payment(payment)
+            .returns(ReturnsBackTransfers)
+            .sync_call()
+    }
+
+    #[view(getWhitelistedContractCalls)]
+    #[storage_mapper("whitelistedContractCalls")]
+    fn whitelisted_contract_calls(
+        &self,
+        sc_address: &ManagedAddress,
+    ) -> UnorderedSetMapper<ManagedBuffer>;
+}
@@ -0,0 +1,43 @@
+multiversx_sc::imports!();
+
+use energy_factory::virtual_lock::ProxyTrait as _;
+
+#[multiversx_sc::module]
+pub trait EnergyFactoryActionsModule {
+    /// The locked tokens are sent to this contract, while the energy goes to the user.
+    /// The energy factory has to whitelist this contract to lock for another address,
+    /// and this contract burns the base tokens, so it needs the burn role for them.
+    fn lock_tokens(
+        &self,
+        user: &ManagedAddress,
+        payment: EgldOrEsdtTokenPayment,
+        lock_epochs: u64,
+    ) -> EsdtTokenPayment {
+        require!(!payment.token_identifier.is_egld(), "EGLD can't be locked!");
+
+        let payment = payment.unwrap_esdt();
+        let energy_factory_addr = self.energy_factory_addr().get();
+        let own_address = self.blockchain().get_sc_address();
+        let locked_tokens: EsdtTokenPayment = self
+            .energy_factory_proxy(energy_factory_addr)
+            .lock_virtual(
+                payment.token_identifier.clone(),
+                payment.amount.clone(),
+                lock_epochs,
+                own_address,
+                user.clone(),
+            )
+            .execute_on_dest_context();
+
+        self.send()
+            .esdt_local_burn(&payment.token_identifier, 0, &payment.amount);
+
+        locked_tokens
+    }
+
+    #[proxy]
+    fn energy_factory_proxy(&self, sc_address: ManagedAddress) -> energy_factory::Proxy<Self::Api>;
+
+    #[storage_mapper("energyFactoryAddr")]
+    fn energy_factory_addr(&self) -> SingleValueMapper<ManagedAddress>;
+}
@@ -0,0 +1,62 @@
+multiversx_sc::imports!();
+
+use farm::{
+    base_functions::{ClaimRewardsResultType, ExitFarmResultType},
+    EnterFarmResultType,
+};
+
+/// The user is passed as the original caller, so the position and the boosted rewards are theirs.
+/// This requires the contract to be whitelisted in the farm.
+#[multiversx_sc::module]
+pub trait FarmActionsModule {
+    /// Returns the new farm token and the boosted rewards
+    fn call_enter_farm(
+        &self,
+        farm_address: ManagedAddress,
+        user: ManagedAddress,
+        farming_tokens: EsdtTokenPayment,
+    ) -> (EsdtTokenPayment, EsdtTokenPayment) {
+        let raw_results: EnterFarmResultType<Self::Api> = self
+            .farm_proxy(farm_address)
+            .enter_farm_endpoint(OptionalValue::Some(user))
+            .with_esdt_transfer(farming_tokens)
+            .execute_on_dest_context();
+
+        raw_results.into_tuple()
+    }
+
+    /// Returns the farming tokens and the rewards
+    fn call_exit_farm(
+        &self,
+        farm_address: ManagedAddress,
+        user: ManagedAddress,
+        farm_tokens: EsdtTokenPayment,
+    ) -> (EsdtTokenPayment, EsdtTokenPayment) {
+        let raw_results: ExitFarmResultType<Self::Api> = self
+            .farm_proxy(farm_address)
+            .exit_farm_endpoint(OptionalValue::Some(user))
+            .with_esdt_transfer(farm_tokens)
+            .execute_on_dest_context();
+
+        raw_results.into_tuple()
+    }
+
+    /// Returns the new farm token and the rewards
+    fn call_claim_rewards(
+        &self,
+        farm_address: ManagedAddress,
+        user: ManagedAddress,
+        farm_tokens: EsdtTokenPayment,
+    ) -> (EsdtTokenPayment, EsdtTokenPayment) {
+        let raw_results: ClaimRewardsResultType<Self::Api> = self
+            .farm_proxy(farm_address)
+            .claim_rewards_endpoint(OptionalValue::Some(user))
+            .with_esdt_transfer(farm_tokens)
+            .execute_on_dest_context();
+
+        raw_results.into_tuple()
+    }
+
+    #[proxy]
+    fn farm_proxy(&self, sc_address: ManagedAddress) -> farm_with_locked_rewards::Proxy<Self::Api>;
+}
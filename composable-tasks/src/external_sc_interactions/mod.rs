@@ -1,3 +1,6 @@
+pub mod contract_call_actions;
+pub mod energy_factory_actions;
+pub mod farm_actions;
 pub mod pair_actions;
 pub mod router_actions;
 pub mod wegld_swap;
@@ -1,7 +1,11 @@
 multiversx_sc::imports!();
 
 use super::router_actions;
-use pair::pair_actions::swap::ProxyTrait as _;
+use pair::pair_actions::{
+    add_liq::ProxyTrait as _, remove_liq::ProxyTrait as _, swap::ProxyTrait as _,
+};
+
+pub const ADD_REMOVE_LIQUIDITY_MIN_AMOUNT: u64 = 1;
 
 pub enum PairAddressForTokens<M: ManagedTypeApi> {
     CorrectOrder(ManagedAddress<M>),
@@ -22,7 +26,9 @@ impl<M: ManagedTypeApi> PairAddressForTokens<M> {
 }
 
 #[multiversx_sc::module]
-pub trait PairActionsModule: router_actions::RouterActionsModule {
+pub trait PairActionsModule:
+    router_actions::RouterActionsModule + read_external_storage::ReadExternalStorageModule
+{
     fn perform_swap_tokens_fixed_input(
         &self,
         from_tokens: TokenIdentifier,
@@ -95,6 +101,79 @@ pub trait PairActionsModule: router_actions::RouterActionsModule {
         back_transfers.esdt_payments
     }
 
+    /// Returns the LP tokens, followed by the leftovers, if any
+    fn perform_add_liquidity(
+        &self,
+        pair_address: ManagedAddress,
+        first_payment: EsdtTokenPayment,
+        second_payment: EsdtTokenPayment,
+        first_token_amount_min: BigUint,
+        second_token_amount_min: BigUint,
+    ) -> ManagedVec<EsdtTokenPayment> {
+        let lp_token_id = self.get_lp_token_id_mapper(pair_address.clone()).get();
+
+        let ((), back_transfers) = self
+            .pair_proxy(pair_address)
+            .add_liquidity(first_token_amount_min, second_token_amount_min)
+            .with_esdt_transfer(first_payment)
+            .with_esdt_transfer(second_payment)
+            .execute_on_dest_context_with_back_transfers();
+
+        require!(
+            !back_transfers.esdt_payments.is_empty()
+                && back_transfers.esdt_payments.get(0).token_identifier == lp_token_id,
+            "Add liquidity: LP tokens expected"
+        );
+
+        back_transfers.esdt_payments
+    }
+
+    /// Returns the first and second tokens of the pair, in this order
+    fn perform_remove_liquidity(
+        &self,
+        pair_address: ManagedAddress,
+        lp_payment: EsdtTokenPayment,
+        first_token_amount_min: BigUint,
+        second_token_amount_min: BigUint,
+    ) -> (EsdtTokenPayment, EsdtTokenPayment) {
+        let lp_token_id = self.get_lp_token_id_mapper(pair_address.clone()).get();
+        require!(
+            lp_payment.token_identifier == lp_token_id,
+            "Wrong LP token for the given pair!"
+        );
+
+        let ((), back_transfers) = self
+            .pair_proxy(pair_address)
+            .remove_liquidity(first_token_amount_min, second_token_amount_min)
+            .with_esdt_transfer(lp_payment)
+            .execute_on_dest_context_with_back_transfers();
+
+        require!(
+            back_transfers.esdt_payments.len() == 2,
+            "Remove liquidity: Back transfers expected 2 payments"
+        );
+
+        (
+            back_transfers.esdt_payments.get(0),
+            back_transfers.esdt_payments.get(1),
+        )
+    }
+
+    /// The amount of the payment that has to be swapped to the other pair token,
+    /// so that the swap output and the remaining tokens are in the pool ratio
+    fn get_optimal_swap_amount(
+        &self,
+        pair_address: ManagedAddress,
+        payment: &EsdtTokenPayment,
+    ) -> BigUint {
+        let reserve_in = self
+            .get_pair_reserve_mapper(pair_address.clone(), &payment.token_identifier)
+            .get();
+        let total_fee_percent = self.get_total_fee_percent_mapper(pair_address).get();
+
+        pair_math::get_optimal_swap_amount(&payment.amount, &reserve_in, total_fee_percent)
+    }
+
     #[proxy]
     fn pair_proxy(&self, sc_address: ManagedAddress) -> pair::Proxy<Self::Api>;
 }
@@ -0,0 +1,77 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::config::MAX_PERCENTAGE;
+
+/// fee_bps of the final output is sent to the integrator
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct IntegratorFee<M: ManagedTypeApi> {
+    pub integrator: ManagedAddress<M>,
+    pub fee_bps: u64,
+}
+
+#[multiversx_sc::module]
+pub trait IntegratorFeesModule {
+    /// Max fee integrators can take from the output of the compose endpoints.
+    /// Set to 0 to disable integrator fees.
+    #[only_owner]
+    #[endpoint(setMaxIntegratorFeeBps)]
+    fn set_max_integrator_fee_bps(&self, max_fee_bps: u64) {
+        require!(max_fee_bps <= MAX_PERCENTAGE, "Invalid fee");
+
+        self.max_integrator_fee_bps().set(max_fee_bps);
+    }
+
+    fn require_valid_integrator_fee(&self, integrator: &ManagedAddress, fee_bps: u64) {
+        require!(!integrator.is_zero(), "Invalid integrator address");
+        require!(
+            fee_bps <= self.max_integrator_fee_bps().get(),
+            "Integrator fee too high"
+        );
+    }
+
+    /// Sends the fee to the integrator and returns the remaining tokens.
+    /// The whole payment is recorded as the integrator's volume.
+    fn deduct_integrator_fee(
+        &self,
+        caller: &ManagedAddress,
+        integrator: &ManagedAddress,
+        fee_bps: u64,
+        mut payment: EgldOrEsdtTokenPayment,
+    ) -> EgldOrEsdtTokenPayment {
+        let fee_amount = &payment.amount * fee_bps / MAX_PERCENTAGE;
+        self.integrator_fee_event(
+            integrator,
+            caller,
+            &payment.token_identifier,
+            &payment.amount,
+            &fee_amount,
+        );
+
+        if fee_amount > 0 {
+            payment.amount -= &fee_amount;
+            self.send().direct(
+                integrator,
+                &payment.token_identifier,
+                payment.token_nonce,
+                &fee_amount,
+            );
+        }
+
+        payment
+    }
+
+    #[event("integratorFee")]
+    fn integrator_fee_event(
+        &self,
+        #[indexed] integrator: &ManagedAddress,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] token_id: &EgldOrEsdtTokenIdentifier,
+        volume: &BigUint,
+        fee_amount: &BigUint,
+    );
+
+    #[view(getMaxIntegratorFeeBps)]
+    #[storage_mapper("maxIntegratorFeeBps")]
+    fn max_integrator_fee_bps(&self) -> SingleValueMapper<u64>;
+}
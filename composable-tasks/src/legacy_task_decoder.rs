@@ -0,0 +1,374 @@
+multiversx_sc::imports!();
+
+use core::convert::TryFrom;
+
+use crate::{
+    compose_tasks::TaskType,
+    config::{
+        ADD_LIQUIDITY_ARGS_LEN, ADD_LIQUIDITY_WITH_MIN_OUT_ARGS_LEN,
+        ADD_LIQUIDITY_WITH_RATIO_ARGS_LEN, ASSERT_MIN_BALANCE_ARGS_LEN, CALL_CONTRACT_MIN_ARGS_LEN,
+        FARM_ARGS_LEN, LOCK_TOKENS_ARGS_LEN, REMOVE_LIQUIDITY_ARGS_LEN,
+        REMOVE_LIQUIDITY_WITH_MIN_OUT_ARGS_LEN, ROUTER_SWAP_ARGS_LEN, SEND_AND_CONTINUE_ARGS_LEN,
+        SEND_AND_CONTINUE_WITH_CALL_MIN_ARGS_LEN, SEND_FIXED_AMOUNT, SEND_PERCENTAGE,
+        SEND_TOKENS_ARGS_LEN, SWAP_ARGS_LEN, SWAP_TOKENS_FIXED_INPUT_FUNC_NAME,
+        SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME,
+    },
+    task_types::{
+        AddLiquidityArgs, AssertMinBalanceArgs, CallContractArgs, FarmArgs, LockTokensArgs,
+        RemoveLiquidityArgs, RouterSwapArgs, RouterSwapOperation, SendAmount, SendAndContinueArgs,
+        SendArgs, SwapArgs, SwapFunction, Task, TransferCall,
+    },
+};
+
+/// Decodes the positional ManagedBuffer arguments of the composeTasks format into typed tasks.
+/// Errors name the index of the task and the argument that failed.
+#[multiversx_sc::module]
+pub trait LegacyTaskDecoderModule {
+    fn decode_legacy_task(
+        &self,
+        task_index: usize,
+        task_type: TaskType,
+        args: ManagedVec<ManagedBuffer>,
+    ) -> Task<Self::Api> {
+        match task_type {
+            TaskType::WrapEGLD => Task::WrapEGLD,
+            TaskType::UnwrapEGLD => Task::UnwrapEGLD,
+            TaskType::Swap => {
+                self.require_args_len(task_index, args.len() == SWAP_ARGS_LEN);
+
+                Task::Swap(SwapArgs {
+                    function: self.decode_swap_function(task_index, &args.get(0)),
+                    token_out: self.decode_token_id(task_index, "token_out", &args.get(1)),
+                    min_out: self.decode_min_out(&args.get(2)),
+                })
+            }
+            TaskType::RouterSwap => {
+                self.require_args_len(task_index, args.len() % ROUTER_SWAP_ARGS_LEN == 0);
+
+                let mut swap_operations = ManagedVec::new();
+                let mut i = 0;
+                while i < args.len() {
+                    let pair_address =
+                        self.decode_address(task_index, "pair_address", &args.get(i));
+                    let function = self.decode_swap_function(task_index, &args.get(i + 1));
+                    let token_out = self.decode_token_id(task_index, "token_out", &args.get(i + 2));
+                    let min_out = self.decode_min_out(&args.get(i + 3));
+                    swap_operations.push(RouterSwapOperation {
+                        pair_address,
+                        function,
+                        token_out,
+                        min_out,
+                    });
+                    i += ROUTER_SWAP_ARGS_LEN;
+                }
+
+                Task::RouterSwap(RouterSwapArgs { swap_operations })
+            }
+            TaskType::SendEgldOrEsdt => {
+                self.require_args_len(task_index, args.len() == SEND_TOKENS_ARGS_LEN);
+
+                Task::SendEgldOrEsdt(SendArgs {
+                    destination: self.decode_address(task_index, "destination", &args.get(0)),
+                })
+            }
+            TaskType::AddLiquidity => {
+                self.require_args_len(
+                    task_index,
+                    args.len() == ADD_LIQUIDITY_ARGS_LEN
+                        || args.len() == ADD_LIQUIDITY_WITH_RATIO_ARGS_LEN
+                        || args.len() == ADD_LIQUIDITY_WITH_MIN_OUT_ARGS_LEN,
+                );
+
+                // with a min LP amount, a 0 swap percentage selects the optimal swap amount
+                let opt_swap_percentage = if args.len() >= ADD_LIQUIDITY_WITH_RATIO_ARGS_LEN {
+                    let swap_percentage =
+                        self.decode_u64(task_index, "swap_percentage", &args.get(1));
+                    if args.len() == ADD_LIQUIDITY_WITH_MIN_OUT_ARGS_LEN && swap_percentage == 0 {
+                        None
+                    } else {
+                        Some(swap_percentage)
+                    }
+                } else {
+                    None
+                };
+                let opt_min_lp_out = if args.len() == ADD_LIQUIDITY_WITH_MIN_OUT_ARGS_LEN {
+                    Some(self.decode_min_out(&args.get(2)))
+                } else {
+                    None
+                };
+
+                let other_token_id =
+                    self.decode_token_id(task_index, "other_token_id", &args.get(0));
+
+                Task::AddLiquidity(AddLiquidityArgs {
+                    other_token_id,
+                    opt_swap_percentage,
+                    opt_min_lp_out,
+                })
+            }
+            TaskType::RemoveLiquidity => {
+                self.require_args_len(
+                    task_index,
+                    args.len() == REMOVE_LIQUIDITY_ARGS_LEN
+                        || args.len() == REMOVE_LIQUIDITY_WITH_MIN_OUT_ARGS_LEN,
+                );
+
+                let token_out = self.decode_token_id(task_index, "token_out", &args.get(0));
+                let other_token_id =
+                    self.decode_token_id(task_index, "other_token_id", &args.get(1));
+                let opt_min_out = if args.len() == REMOVE_LIQUIDITY_WITH_MIN_OUT_ARGS_LEN {
+                    Some(self.decode_min_out(&args.get(2)))
+                } else {
+                    None
+                };
+
+                Task::RemoveLiquidity(RemoveLiquidityArgs {
+                    token_out,
+                    other_token_id,
+                    opt_min_out,
+                })
+            }
+            TaskType::EnterFarm | TaskType::ExitFarm | TaskType::ClaimRewards => {
+                self.require_args_len(task_index, args.len() == FARM_ARGS_LEN);
+
+                let farm_args = FarmArgs {
+                    farm_address: self.decode_address(task_index, "farm_address", &args.get(0)),
+                };
+                match task_type {
+                    TaskType::EnterFarm => Task::EnterFarm(farm_args),
+                    TaskType::ExitFarm => Task::ExitFarm(farm_args),
+                    _ => Task::ClaimRewards(farm_args),
+                }
+            }
+            TaskType::SendAndContinue => self.decode_send_and_continue(task_index, args),
+            TaskType::AssertMinBalance => {
+                self.require_args_len(task_index, args.len() == ASSERT_MIN_BALANCE_ARGS_LEN);
+
+                let token_id = EgldOrEsdtTokenIdentifier::parse(args.get(0).clone_value());
+                self.require_valid_egld_or_esdt_token_id(task_index, "token_id", &token_id);
+
+                Task::AssertMinBalance(AssertMinBalanceArgs {
+                    token_id,
+                    min_amount: self.decode_amount(task_index, "min_amount", &args.get(1)),
+                })
+            }
+            TaskType::CallContract => {
+                self.require_args_len(task_index, args.len() >= CALL_CONTRACT_MIN_ARGS_LEN);
+
+                let endpoint_name = args.get(1).clone_value();
+                self.require_endpoint_name(task_index, &endpoint_name);
+
+                let output_token_id = EgldOrEsdtTokenIdentifier::parse(args.get(2).clone_value());
+                self.require_valid_egld_or_esdt_token_id(
+                    task_index,
+                    "output_token_id",
+                    &output_token_id,
+                );
+
+                Task::CallContract(CallContractArgs {
+                    sc_address: self.decode_address(task_index, "sc_address", &args.get(0)),
+                    endpoint_name,
+                    output_token_id,
+                    args: args
+                        .slice(CALL_CONTRACT_MIN_ARGS_LEN, args.len())
+                        .unwrap_or_default(),
+                })
+            }
+            TaskType::LockTokens => {
+                self.require_args_len(task_index, args.len() == LOCK_TOKENS_ARGS_LEN);
+
+                Task::LockTokens(LockTokensArgs {
+                    lock_epochs: self.decode_u64(task_index, "lock_epochs", &args.get(0)),
+                })
+            }
+        }
+    }
+
+    fn decode_send_and_continue(
+        &self,
+        task_index: usize,
+        args: ManagedVec<ManagedBuffer>,
+    ) -> Task<Self::Api> {
+        self.require_args_len(
+            task_index,
+            args.len() == SEND_AND_CONTINUE_ARGS_LEN
+                || args.len() >= SEND_AND_CONTINUE_WITH_CALL_MIN_ARGS_LEN,
+        );
+
+        let send_type = args.get(1);
+        let amount = if *send_type == ManagedBuffer::from(SEND_FIXED_AMOUNT) {
+            SendAmount::Fixed(self.decode_amount(task_index, "amount", &args.get(2)))
+        } else if *send_type == ManagedBuffer::from(SEND_PERCENTAGE) {
+            SendAmount::Percentage(self.decode_u64(task_index, "amount", &args.get(2)))
+        } else {
+            self.signal_task_arg_error(task_index, "send_type");
+        };
+
+        let opt_call = if args.len() >= SEND_AND_CONTINUE_WITH_CALL_MIN_ARGS_LEN {
+            let endpoint_name = args.get(3).clone_value();
+            self.require_endpoint_name(task_index, &endpoint_name);
+
+            Some(TransferCall {
+                endpoint_name,
+                args: args
+                    .slice(SEND_AND_CONTINUE_WITH_CALL_MIN_ARGS_LEN, args.len())
+                    .unwrap_or_default(),
+            })
+        } else {
+            None
+        };
+
+        Task::SendAndContinue(SendAndContinueArgs {
+            destination: self.decode_address(task_index, "destination", &args.get(0)),
+            amount,
+            opt_call,
+        })
+    }
+
+    /// Runs the argument checks of the decoder on a typed task,
+    /// so both formats reject the same arguments with the same errors
+    fn validate_typed_task(&self, task_index: usize, task: &Task<Self::Api>) {
+        match task {
+            Task::WrapEGLD
+            | Task::UnwrapEGLD
+            | Task::SendEgldOrEsdt(_)
+            | Task::EnterFarm(_)
+            | Task::ExitFarm(_)
+            | Task::ClaimRewards(_)
+            | Task::LockTokens(_) => {}
+            Task::Swap(args) => {
+                self.require_valid_token_id(task_index, "token_out", &args.token_out);
+            }
+            Task::RouterSwap(args) => {
+                for swap_operation in &args.swap_operations {
+                    self.require_valid_token_id(task_index, "token_out", &swap_operation.token_out);
+                }
+            }
+            Task::AddLiquidity(args) => {
+                self.require_valid_token_id(task_index, "other_token_id", &args.other_token_id);
+            }
+            Task::RemoveLiquidity(args) => {
+                self.require_valid_token_id(task_index, "token_out", &args.token_out);
+                self.require_valid_token_id(task_index, "other_token_id", &args.other_token_id);
+            }
+            Task::SendAndContinue(args) => {
+                if let SendAmount::Fixed(amount) = &args.amount {
+                    self.require_non_zero_amount(task_index, "amount", amount);
+                }
+                if let Some(call) = &args.opt_call {
+                    self.require_endpoint_name(task_index, &call.endpoint_name);
+                }
+            }
+            Task::AssertMinBalance(args) => {
+                self.require_valid_egld_or_esdt_token_id(task_index, "token_id", &args.token_id);
+                self.require_non_zero_amount(task_index, "min_amount", &args.min_amount);
+            }
+            Task::CallContract(args) => {
+                self.require_endpoint_name(task_index, &args.endpoint_name);
+                self.require_valid_egld_or_esdt_token_id(
+                    task_index,
+                    "output_token_id",
+                    &args.output_token_id,
+                );
+            }
+        }
+    }
+
+    fn decode_swap_function(&self, task_index: usize, arg: &ManagedBuffer) -> SwapFunction {
+        if *arg == ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME) {
+            SwapFunction::FixedInput
+        } else if *arg == ManagedBuffer::from(SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME) {
+            SwapFunction::FixedOutput
+        } else {
+            self.signal_task_arg_error(task_index, "function");
+        }
+    }
+
+    fn decode_address(
+        &self,
+        task_index: usize,
+        field_name: &str,
+        arg: &ManagedBuffer,
+    ) -> ManagedAddress {
+        ManagedAddress::try_from(arg.clone())
+            .unwrap_or_else(|_| self.signal_task_arg_error(task_index, field_name))
+    }
+
+    fn decode_token_id(
+        &self,
+        task_index: usize,
+        field_name: &str,
+        arg: &ManagedBuffer,
+    ) -> TokenIdentifier {
+        let token_id = TokenIdentifier::from(arg.clone());
+        self.require_valid_token_id(task_index, field_name, &token_id);
+
+        token_id
+    }
+
+    /// Amounts must be greater than 0
+    fn decode_amount(&self, task_index: usize, field_name: &str, arg: &ManagedBuffer) -> BigUint {
+        let amount = BigUint::from(arg.clone());
+        self.require_non_zero_amount(task_index, field_name, &amount);
+
+        amount
+    }
+
+    /// The format allows 0 min amounts, usually sent as an empty buffer
+    fn decode_min_out(&self, arg: &ManagedBuffer) -> BigUint {
+        BigUint::from(arg.clone())
+    }
+
+    fn decode_u64(&self, task_index: usize, field_name: &str, arg: &ManagedBuffer) -> u64 {
+        arg.parse_as_u64()
+            .unwrap_or_else(|| self.signal_task_arg_error(task_index, field_name))
+    }
+
+    fn require_valid_token_id(
+        &self,
+        task_index: usize,
+        field_name: &str,
+        token_id: &TokenIdentifier,
+    ) {
+        if !token_id.is_valid_esdt_identifier() {
+            self.signal_task_arg_error(task_index, field_name);
+        }
+    }
+
+    fn require_valid_egld_or_esdt_token_id(
+        &self,
+        task_index: usize,
+        field_name: &str,
+        token_id: &EgldOrEsdtTokenIdentifier,
+    ) {
+        if !token_id.is_valid() {
+            self.signal_task_arg_error(task_index, field_name);
+        }
+    }
+
+    fn require_non_zero_amount(&self, task_index: usize, field_name: &str, amount: &BigUint) {
+        if *amount == 0 {
+            self.signal_task_arg_error(task_index, field_name);
+        }
+    }
+
+    fn require_endpoint_name(&self, task_index: usize, endpoint_name: &ManagedBuffer) {
+        if endpoint_name.is_empty() {
+            self.signal_task_arg_error(task_index, "endpoint_name");
+        }
+    }
+
+    fn require_args_len(&self, task_index: usize, valid_len: bool) {
+        if !valid_len {
+            self.signal_task_arg_error(task_index, "number of arguments");
+        }
+    }
+
+    fn signal_task_arg_error(&self, task_index: usize, field_name: &str) -> ! {
+        sc_panic!(
+            "Task {}: invalid {}",
+            task_index,
+            ManagedBuffer::<Self::Api>::from(field_name)
+        );
+    }
+}
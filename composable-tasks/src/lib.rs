@@ -5,14 +5,25 @@ multiversx_sc::imports!();
 pub mod compose_tasks;
 pub mod config;
 pub mod external_sc_interactions;
+pub mod integrator_fees;
+pub mod legacy_task_decoder;
+pub mod simulation;
+pub mod task_types;
 
 #[multiversx_sc::contract]
 pub trait ComposableTasksContract:
     compose_tasks::TaskCall
     + config::ConfigModule
+    + read_external_storage::ReadExternalStorageModule
+    + external_sc_interactions::contract_call_actions::ContractCallActionsModule
+    + external_sc_interactions::energy_factory_actions::EnergyFactoryActionsModule
+    + external_sc_interactions::farm_actions::FarmActionsModule
     + external_sc_interactions::pair_actions::PairActionsModule
     + external_sc_interactions::router_actions::RouterActionsModule
     + external_sc_interactions::wegld_swap::WegldWrapModule
+    + integrator_fees::IntegratorFeesModule
+    + legacy_task_decoder::LegacyTaskDecoderModule
+    + simulation::SimulationModule
 {
     #[init]
     fn init(&self) {}
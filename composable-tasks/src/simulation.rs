@@ -0,0 +1,490 @@
+multiversx_sc::imports!();
+
+use crate::{
+    compose_tasks::{PaymentsVec, SlotTaskType, TaskType},
+    task_types::{SwapFunction, Task, TaskWithSlots},
+};
+
+pub type SimulationResultType<M> =
+    MultiValue2<ManagedVec<M, EgldOrEsdtTokenPayment<M>>, ManagedVec<M, EgldOrEsdtTokenPayment<M>>>;
+
+pub struct SimulationState<M: ManagedTypeApi> {
+    pub slots: ManagedVec<M, EgldOrEsdtTokenPayment<M>>,
+    pub result_slot: usize,
+    pub step_outputs: ManagedVec<M, EgldOrEsdtTokenPayment<M>>,
+    pub leftovers: PaymentsVec<M>,
+}
+
+impl<M: ManagedTypeApi> SimulationState<M> {
+    pub fn new(slots: ManagedVec<M, EgldOrEsdtTokenPayment<M>>) -> Self {
+        SimulationState {
+            slots,
+            result_slot: 0,
+            step_outputs: ManagedVec::new(),
+            leftovers: PaymentsVec::new(),
+        }
+    }
+}
+
+/// Estimates the outputs of composeTasks from the current pair reserves and fees.
+/// Each step is estimated independently, so a pair used by several tasks is not updated
+/// between them. Wrapping is 1:1 and the farm tasks keep the amount of the payment.
+/// Tokens are locked 1:1, without the nonce of the locked tokens.
+/// Contract calls can't be simulated.
+#[multiversx_sc::module]
+pub trait SimulationModule:
+    read_external_storage::ReadExternalStorageModule
+    + crate::external_sc_interactions::contract_call_actions::ContractCallActionsModule
+    + crate::external_sc_interactions::energy_factory_actions::EnergyFactoryActionsModule
+    + crate::external_sc_interactions::farm_actions::FarmActionsModule
+    + crate::external_sc_interactions::pair_actions::PairActionsModule
+    + crate::external_sc_interactions::router_actions::RouterActionsModule
+    + crate::external_sc_interactions::wegld_swap::WegldWrapModule
+    + crate::integrator_fees::IntegratorFeesModule
+    + crate::legacy_task_decoder::LegacyTaskDecoderModule
+    + crate::compose_tasks::TaskCall
+{
+    /// Returns the expected output of each step, followed by the final outputs:
+    /// the result of the last task and the leftovers.
+    #[view(simulateComposeTasks)]
+    fn simulate_compose_tasks(
+        &self,
+        payment: EgldOrEsdtTokenPayment,
+        tasks: MultiValueEncoded<MultiValue2<TaskType, ManagedVec<ManagedBuffer>>>,
+    ) -> SimulationResultType<Self::Api> {
+        let mut state = SimulationState::new(ManagedVec::from_single_item(payment));
+        for (task_index, task) in tasks.into_iter().enumerate() {
+            let (task_type, args) = task.into_tuple();
+            let task = TaskWithSlots {
+                input_slot: 0,
+                output_slot: 0,
+                task: self.decode_legacy_task(task_index, task_type, args),
+            };
+            if !self.simulate_task(&mut state, task_index, task) {
+                break;
+            }
+        }
+
+        self.get_simulation_result(state)
+    }
+
+    /// Same as simulateComposeTasks, for composeTasksWithSlots.
+    /// The final outputs are the result of the last task, the leftovers
+    /// and the payments left in the other slots.
+    #[view(simulateComposeTasksWithSlots)]
+    fn simulate_compose_tasks_with_slots(
+        &self,
+        payments: ManagedVec<EgldOrEsdtTokenPayment>,
+        tasks: MultiValueEncoded<SlotTaskType<Self::Api>>,
+    ) -> SimulationResultType<Self::Api> {
+        let mut state = SimulationState::new(payments);
+        let mut tasks_ended = false;
+        for (task_index, task) in tasks.into_iter().enumerate() {
+            self.require_no_task_after_send(task_index, tasks_ended);
+
+            let (task_type, input_slot, output_slot, args) = task.into_tuple();
+            let task = TaskWithSlots {
+                input_slot,
+                output_slot,
+                task: self.decode_legacy_task(task_index, task_type, args),
+            };
+            tasks_ended = !self.simulate_task(&mut state, task_index, task);
+        }
+
+        self.get_simulation_result(state)
+    }
+
+    /// Same as simulateComposeTasksWithSlots, for composeTypedTasks
+    #[view(simulateComposeTypedTasks)]
+    fn simulate_compose_typed_tasks(
+        &self,
+        payments: ManagedVec<EgldOrEsdtTokenPayment>,
+        tasks: MultiValueEncoded<TaskWithSlots<Self::Api>>,
+    ) -> SimulationResultType<Self::Api> {
+        let mut state = SimulationState::new(payments);
+        let mut tasks_ended = false;
+        for (task_index, task) in tasks.into_iter().enumerate() {
+            self.require_no_task_after_send(task_index, tasks_ended);
+            self.validate_typed_task(task_index, &task.task);
+            tasks_ended = !self.simulate_task(&mut state, task_index, task);
+        }
+
+        self.get_simulation_result(state)
+    }
+
+    /// Uses the slots the same way as execute_task.
+    /// Returns false if no other task should be simulated.
+    fn simulate_task(
+        &self,
+        state: &mut SimulationState<Self::Api>,
+        task_index: usize,
+        task_with_slots: TaskWithSlots<Self::Api>,
+    ) -> bool {
+        let input_slot = task_with_slots.input_slot;
+        let output_slot = task_with_slots.output_slot;
+        let current_payment = self.take_slot_payment(&mut state.slots, input_slot);
+        let payment_out = match task_with_slots.task {
+            Task::WrapEGLD => {
+                require!(
+                    current_payment.token_identifier.is_egld(),
+                    "Payment token is not EGLD!"
+                );
+
+                let wrap_egld_addr = self.wrap_egld_addr().get();
+                let wegld_token_id = self.get_wrapped_egld_token_id_mapper(wrap_egld_addr).get();
+                EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(wegld_token_id),
+                    0,
+                    current_payment.amount,
+                )
+            }
+            Task::UnwrapEGLD => EgldOrEsdtTokenPayment::new(
+                EgldOrEsdtTokenIdentifier::egld(),
+                0,
+                current_payment.amount,
+            ),
+            Task::Swap(args) => {
+                let payment_in = self.require_esdt_payment(current_payment);
+                // same as perform_swap_tokens_fixed_input and perform_swap_tokens_fixed_output,
+                // which return the payment without calling any pair
+                let payment_out = if payment_in.token_identifier == args.token_out {
+                    payment_in
+                } else {
+                    let pair_address =
+                        self.find_pair(&payment_in.token_identifier, &args.token_out);
+                    self.simulate_swap(
+                        pair_address,
+                        payment_in,
+                        &args.function,
+                        args.token_out,
+                        args.min_out,
+                        &mut state.leftovers,
+                    )
+                };
+
+                EgldOrEsdtTokenPayment::from(payment_out)
+            }
+            Task::RouterSwap(args) => {
+                let mut payment_out = self.require_esdt_payment(current_payment);
+                for swap_operation in &args.swap_operations {
+                    payment_out = self.simulate_swap(
+                        swap_operation.pair_address,
+                        payment_out,
+                        &swap_operation.function,
+                        swap_operation.token_out,
+                        swap_operation.min_out,
+                        &mut state.leftovers,
+                    );
+                }
+
+                EgldOrEsdtTokenPayment::from(payment_out)
+            }
+            Task::SendEgldOrEsdt(_) => {
+                state.step_outputs.push(current_payment.clone());
+                let _ = state.slots.set(input_slot, &current_payment);
+                state.result_slot = input_slot;
+
+                return false;
+            }
+            Task::AddLiquidity(args) => {
+                let payment_in = self.require_esdt_payment(current_payment);
+                let lp_payment = self.simulate_add_liquidity(
+                    payment_in,
+                    args.other_token_id,
+                    args.opt_swap_percentage,
+                    &mut state.leftovers,
+                );
+
+                EgldOrEsdtTokenPayment::from(lp_payment)
+            }
+            Task::RemoveLiquidity(args) => {
+                let payment_in = self.require_esdt_payment(current_payment);
+                let payment_out = self.simulate_remove_liquidity(
+                    payment_in,
+                    args.token_out,
+                    args.other_token_id,
+                    &mut state.leftovers,
+                );
+
+                EgldOrEsdtTokenPayment::from(payment_out)
+            }
+            Task::EnterFarm(args) => {
+                let payment_in = self.require_esdt_payment(current_payment);
+                let farm_token_id = self.get_farm_token_id_mapper(args.farm_address).get();
+                EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(farm_token_id),
+                    0,
+                    payment_in.amount,
+                )
+            }
+            Task::ExitFarm(args) => {
+                let payment_in = self.require_esdt_payment(current_payment);
+                let farming_token_id = self.get_farming_token_id_mapper(args.farm_address).get();
+                EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(farming_token_id),
+                    0,
+                    payment_in.amount,
+                )
+            }
+            Task::ClaimRewards(_) | Task::AssertMinBalance(_) => current_payment,
+            Task::CallContract(_) => {
+                sc_panic!("Task {}: contract calls can't be simulated", task_index);
+            }
+            Task::LockTokens(_) => {
+                let energy_factory_addr = self.energy_factory_addr().get();
+                let locked_token_id = self.get_locked_token_id_mapper(energy_factory_addr).get();
+                EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(locked_token_id),
+                    0,
+                    current_payment.amount,
+                )
+            }
+            Task::SendAndContinue(args) => {
+                let send_amount = self.get_send_amount(&current_payment.amount, args.amount);
+                EgldOrEsdtTokenPayment::new(
+                    current_payment.token_identifier,
+                    current_payment.token_nonce,
+                    current_payment.amount - send_amount,
+                )
+            }
+        };
+
+        state.step_outputs.push(payment_out.clone());
+        self.put_slot_payment(&mut state.slots, output_slot, payment_out);
+        state.result_slot = output_slot;
+
+        true
+    }
+
+    fn get_simulation_result(
+        &self,
+        mut state: SimulationState<Self::Api>,
+    ) -> SimulationResultType<Self::Api> {
+        let result_payment = self.take_slot_payment(&mut state.slots, state.result_slot);
+        let mut final_outputs = ManagedVec::from_single_item(result_payment);
+        for leftover in &state.leftovers {
+            final_outputs.push(EgldOrEsdtTokenPayment::from(leftover));
+        }
+        for slot_payment in &state.slots {
+            if slot_payment.amount > 0 {
+                final_outputs.push(slot_payment);
+            }
+        }
+
+        (state.step_outputs, final_outputs).into()
+    }
+
+    fn simulate_swap(
+        &self,
+        pair_address: ManagedAddress,
+        payment_in: EsdtTokenPayment,
+        function: &SwapFunction,
+        token_out: TokenIdentifier,
+        min_out: BigUint,
+        leftovers: &mut PaymentsVec<Self::Api>,
+    ) -> EsdtTokenPayment {
+        let reserve_in = self
+            .get_pair_reserve_mapper(pair_address.clone(), &payment_in.token_identifier)
+            .get();
+        let reserve_out = self
+            .get_pair_reserve_mapper(pair_address.clone(), &token_out)
+            .get();
+        let total_fee_percent = self.get_total_fee_percent_mapper(pair_address).get();
+
+        let amount_out = match function {
+            SwapFunction::FixedInput => self.get_simulated_amount_out(
+                &payment_in.amount,
+                &reserve_in,
+                &reserve_out,
+                total_fee_percent,
+            ),
+            SwapFunction::FixedOutput => {
+                let amount_in = self.get_simulated_amount_in(
+                    &min_out,
+                    &reserve_in,
+                    &reserve_out,
+                    total_fee_percent,
+                );
+                require!(
+                    amount_in <= payment_in.amount,
+                    "Not enough tokens for the fixed output swap"
+                );
+
+                self.push_leftover(
+                    leftovers,
+                    payment_in.token_identifier,
+                    payment_in.amount - amount_in,
+                );
+
+                min_out
+            }
+        };
+
+        EsdtTokenPayment::new(token_out, 0, amount_out)
+    }
+
+    fn simulate_add_liquidity(
+        &self,
+        payment_in: EsdtTokenPayment,
+        other_token_id: TokenIdentifier,
+        opt_swap_percentage: Option<u64>,
+        leftovers: &mut PaymentsVec<Self::Api>,
+    ) -> EsdtTokenPayment {
+        let pair_address = self.find_pair(&payment_in.token_identifier, &other_token_id);
+        let swap_amount = self.get_add_liquidity_swap_amount(
+            pair_address.clone(),
+            &payment_in,
+            opt_swap_percentage,
+        );
+
+        let mut reserve_in = self
+            .get_pair_reserve_mapper(pair_address.clone(), &payment_in.token_identifier)
+            .get();
+        let mut reserve_other = self
+            .get_pair_reserve_mapper(pair_address.clone(), &other_token_id)
+            .get();
+        let total_fee_percent = self.get_total_fee_percent_mapper(pair_address.clone()).get();
+        let other_amount = self.get_simulated_amount_out(
+            &swap_amount,
+            &reserve_in,
+            &reserve_other,
+            total_fee_percent,
+        );
+        reserve_in += &swap_amount;
+        reserve_other -= &other_amount;
+        let amount_in = &payment_in.amount - &swap_amount;
+
+        // the pair computes the optimal amounts in the order of its tokens
+        let first_token_id = self.get_first_token_id_mapper(pair_address.clone()).get();
+        let is_first_token_in = payment_in.token_identifier == first_token_id;
+        let (first_amount, second_amount, first_reserve, second_reserve) = if is_first_token_in {
+            (amount_in, other_amount, reserve_in, reserve_other)
+        } else {
+            (other_amount, amount_in, reserve_other, reserve_in)
+        };
+
+        let second_amount_optimal = &first_amount * &second_reserve / &first_reserve;
+        let (first_amount_used, second_amount_used) = if second_amount_optimal <= second_amount {
+            (first_amount.clone(), second_amount_optimal)
+        } else {
+            let first_amount_optimal = &second_amount * &first_reserve / &second_reserve;
+            (first_amount_optimal, second_amount.clone())
+        };
+
+        let lp_token_supply = self.get_lp_token_supply_mapper(pair_address.clone()).get();
+        let lp_amount = core::cmp::min(
+            &first_amount_used * &lp_token_supply / &first_reserve,
+            &second_amount_used * &lp_token_supply / &second_reserve,
+        );
+
+        let second_token_id = self.get_second_token_id_mapper(pair_address.clone()).get();
+        self.push_leftover(leftovers, first_token_id, first_amount - first_amount_used);
+        self.push_leftover(leftovers, second_token_id, second_amount - second_amount_used);
+
+        let lp_token_id = self.get_lp_token_id_mapper(pair_address).get();
+        EsdtTokenPayment::new(lp_token_id, 0, lp_amount)
+    }
+
+    fn simulate_remove_liquidity(
+        &self,
+        payment_in: EsdtTokenPayment,
+        token_out: TokenIdentifier,
+        other_token_id: TokenIdentifier,
+        leftovers: &mut PaymentsVec<Self::Api>,
+    ) -> EsdtTokenPayment {
+        let pair_address = self.find_pair(&token_out, &other_token_id);
+        let lp_token_id = self.get_lp_token_id_mapper(pair_address.clone()).get();
+        require!(
+            payment_in.token_identifier == lp_token_id,
+            "Wrong LP token for the given pair!"
+        );
+
+        let lp_token_supply = self.get_lp_token_supply_mapper(pair_address.clone()).get();
+        let reserve_out = self
+            .get_pair_reserve_mapper(pair_address.clone(), &token_out)
+            .get();
+        let reserve_other = self
+            .get_pair_reserve_mapper(pair_address, &other_token_id)
+            .get();
+
+        let amount_out = &payment_in.amount * &reserve_out / &lp_token_supply;
+        let other_amount = &payment_in.amount * &reserve_other / &lp_token_supply;
+        self.push_leftover(leftovers, other_token_id, other_amount);
+
+        EsdtTokenPayment::new(token_out, 0, amount_out)
+    }
+
+    /// Same formula as the pair contract
+    fn get_simulated_amount_out(
+        &self,
+        amount_in: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+        total_fee_percent: u64,
+    ) -> BigUint {
+        require!(
+            reserve_in > &0u64 && reserve_out > &0u64,
+            "Pair has no liquidity"
+        );
+
+        pair_math::get_amount_out(amount_in, reserve_in, reserve_out, total_fee_percent)
+    }
+
+    /// Same formula as the pair contract
+    fn get_simulated_amount_in(
+        &self,
+        amount_out: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+        total_fee_percent: u64,
+    ) -> BigUint {
+        require!(
+            reserve_out > amount_out,
+            "Not enough reserve for the fixed output swap"
+        );
+
+        pair_math::get_amount_in(amount_out, reserve_in, reserve_out, total_fee_percent)
+    }
+
+    fn find_pair(
+        &self,
+        first_token_id: &TokenIdentifier,
+        second_token_id: &TokenIdentifier,
+    ) -> ManagedAddress {
+        let router_addr = self.router_addr().get();
+        let pair_mapper = self.get_router_pair_address_mapper(
+            router_addr.clone(),
+            first_token_id,
+            second_token_id,
+        );
+        if !pair_mapper.is_empty() {
+            return pair_mapper.get();
+        }
+
+        let reverse_pair_mapper =
+            self.get_router_pair_address_mapper(router_addr, second_token_id, first_token_id);
+        require!(!reverse_pair_mapper.is_empty(), "Pair not found");
+
+        reverse_pair_mapper.get()
+    }
+
+    fn require_esdt_payment(&self, payment: EgldOrEsdtTokenPayment) -> EsdtTokenPayment {
+        require!(
+            !payment.token_identifier.is_egld(),
+            "EGLD can't be used in this task!"
+        );
+
+        payment.unwrap_esdt()
+    }
+
+    fn push_leftover(
+        &self,
+        leftovers: &mut PaymentsVec<Self::Api>,
+        token_id: TokenIdentifier,
+        amount: BigUint,
+    ) {
+        if amount > 0 {
+            leftovers.push(EsdtTokenPayment::new(token_id, 0, amount));
+        }
+    }
+}
@@ -0,0 +1,139 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::config::{SWAP_TOKENS_FIXED_INPUT_FUNC_NAME, SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME};
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, PartialEq)]
+pub enum SwapFunction {
+    FixedInput,
+    FixedOutput,
+}
+
+impl SwapFunction {
+    pub fn endpoint_name(&self) -> &'static [u8] {
+        match self {
+            SwapFunction::FixedInput => SWAP_TOKENS_FIXED_INPUT_FUNC_NAME,
+            SwapFunction::FixedOutput => SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME,
+        }
+    }
+}
+
+/// For FixedOutput, min_out is the exact amount wanted
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct SwapArgs<M: ManagedTypeApi> {
+    pub function: SwapFunction,
+    pub token_out: TokenIdentifier<M>,
+    pub min_out: BigUint<M>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem)]
+pub struct RouterSwapOperation<M: ManagedTypeApi> {
+    pub pair_address: ManagedAddress<M>,
+    pub function: SwapFunction,
+    pub token_out: TokenIdentifier<M>,
+    pub min_out: BigUint<M>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct RouterSwapArgs<M: ManagedTypeApi> {
+    pub swap_operations: ManagedVec<M, RouterSwapOperation<M>>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct SendArgs<M: ManagedTypeApi> {
+    pub destination: ManagedAddress<M>,
+}
+
+/// If no swap percentage is given, the optimal amount is swapped.
+/// If a min LP amount is given, the task fails when fewer LP tokens are received.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct AddLiquidityArgs<M: ManagedTypeApi> {
+    pub other_token_id: TokenIdentifier<M>,
+    pub opt_swap_percentage: Option<u64>,
+    pub opt_min_lp_out: Option<BigUint<M>>,
+}
+
+/// If a min amount is given, the task fails when less of token_out is received
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct RemoveLiquidityArgs<M: ManagedTypeApi> {
+    pub token_out: TokenIdentifier<M>,
+    pub other_token_id: TokenIdentifier<M>,
+    pub opt_min_out: Option<BigUint<M>>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct FarmArgs<M: ManagedTypeApi> {
+    pub farm_address: ManagedAddress<M>,
+}
+
+/// The percentage uses MAX_PERCENTAGE as 100%
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub enum SendAmount<M: ManagedTypeApi> {
+    Fixed(BigUint<M>),
+    Percentage(u64),
+}
+
+/// The endpoint has to be whitelisted, same as for CallContract.
+/// The tokens sent back by the endpoint are returned to the caller.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct TransferCall<M: ManagedTypeApi> {
+    pub endpoint_name: ManagedBuffer<M>,
+    pub args: ManagedVec<M, ManagedBuffer<M>>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct SendAndContinueArgs<M: ManagedTypeApi> {
+    pub destination: ManagedAddress<M>,
+    pub amount: SendAmount<M>,
+    pub opt_call: Option<TransferCall<M>>,
+}
+
+/// Fails if the payment is of a different token or is less than min_amount.
+/// The payment is passed on unchanged.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct AssertMinBalanceArgs<M: ManagedTypeApi> {
+    pub token_id: EgldOrEsdtTokenIdentifier<M>,
+    pub min_amount: BigUint<M>,
+}
+
+/// The payment is sent to a whitelisted endpoint. The back transfer of output_token_id
+/// is passed to the next task, while the others are returned.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct CallContractArgs<M: ManagedTypeApi> {
+    pub sc_address: ManagedAddress<M>,
+    pub endpoint_name: ManagedBuffer<M>,
+    pub output_token_id: EgldOrEsdtTokenIdentifier<M>,
+    pub args: ManagedVec<M, ManagedBuffer<M>>,
+}
+
+/// The locked tokens are the output of the task, while the energy goes to the caller
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct LockTokensArgs {
+    pub lock_epochs: u64,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub enum Task<M: ManagedTypeApi> {
+    WrapEGLD,
+    UnwrapEGLD,
+    Swap(SwapArgs<M>),
+    RouterSwap(RouterSwapArgs<M>),
+    SendEgldOrEsdt(SendArgs<M>),
+    AddLiquidity(AddLiquidityArgs<M>),
+    RemoveLiquidity(RemoveLiquidityArgs<M>),
+    EnterFarm(FarmArgs<M>),
+    ExitFarm(FarmArgs<M>),
+    ClaimRewards(FarmArgs<M>),
+    SendAndContinue(SendAndContinueArgs<M>),
+    AssertMinBalance(AssertMinBalanceArgs<M>),
+    CallContract(CallContractArgs<M>),
+    LockTokens(LockTokensArgs),
+}
+
+/// The task reads the whole payment from the input slot and writes its output to the output slot
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode)]
+pub struct TaskWithSlots<M: ManagedTypeApi> {
+    pub input_slot: usize,
+    pub output_slot: usize,
+    pub task: Task<M>,
+}
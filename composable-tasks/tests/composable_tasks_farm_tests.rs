@@ -0,0 +1,398 @@
+#![allow(deprecated)]
+
+use composable_tasks::{
+    compose_tasks::{TaskCall, TaskType},
+    config::ConfigModule,
+};
+use energy_factory::energy::EnergyModule;
+use multiversx_sc::{
+    codec::Empty,
+    types::{
+        EgldOrEsdtTokenIdentifier, EgldOrEsdtTokenPayment, EsdtLocalRole, ManagedVec,
+        MultiValueEncoded,
+    },
+};
+use multiversx_sc_scenario::{managed_address, managed_biguint, managed_buffer, rust_biguint};
+use sc_whitelist_module::SCWhitelistModule;
+use tests_common::farm_with_locked_rewards_setup::{
+    FarmSetup, FARMING_TOKEN_BALANCE, FARMING_TOKEN_ID, FARM_TOKEN_ID, LOCKED_REWARD_TOKEN_ID,
+    LOCK_OPTIONS, REWARD_TOKEN_ID,
+};
+
+const FIRST_FARM_INDEX: usize = 0;
+
+#[test]
+fn enter_farm_claim_exit_farm_tasks_test() {
+    let farm_setup = FarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+    );
+    let b_mock = farm_setup.b_mock;
+    let owner = farm_setup.owner;
+    let first_user_addr = farm_setup.first_user;
+    let farm_wrapper = &farm_setup.farm_wrappers[FIRST_FARM_INDEX];
+
+    let ct_wrapper = b_mock.borrow_mut().create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner),
+        composable_tasks::contract_obj,
+        "composable tasks",
+    );
+
+    // the user is passed as the original caller
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, farm_wrapper, &rust_biguint!(0), |sc| {
+            sc.add_sc_address_to_whitelist(managed_address!(ct_wrapper.address_ref()));
+        })
+        .assert_ok();
+
+    b_mock.borrow_mut().set_block_epoch(2);
+
+    let farm_addr = farm_wrapper.address_ref().clone();
+    let farm_token_amount = 100_000_000u64;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &ct_wrapper,
+            FARMING_TOKEN_ID[FIRST_FARM_INDEX],
+            0,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                let mut farm_args = ManagedVec::new();
+                farm_args.push(managed_buffer!(farm_addr.as_bytes()));
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::EnterFarm, farm_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                    0,
+                    managed_biguint!(farm_token_amount),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &first_user_addr,
+        FARM_TOKEN_ID[FIRST_FARM_INDEX],
+        1,
+        &rust_biguint!(farm_token_amount),
+        None,
+    );
+
+    // advance blocks - 10 blocks - 10 * 1_000 = 10_000 total rewards
+    // 7_500 base farm, 2_500 boosted yields, which are not claimable yet
+    b_mock.borrow_mut().set_block_nonce(10);
+
+    let expected_rewards = 7_500u64;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &ct_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            1,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                let mut farm_args = ManagedVec::new();
+                farm_args.push(managed_buffer!(farm_addr.as_bytes()));
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::ClaimRewards, farm_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(FARM_TOKEN_ID[FIRST_FARM_INDEX]),
+                    0,
+                    managed_biguint!(farm_token_amount),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &first_user_addr,
+        FARM_TOKEN_ID[FIRST_FARM_INDEX],
+        2,
+        &rust_biguint!(farm_token_amount),
+        None,
+    );
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &first_user_addr,
+        LOCKED_REWARD_TOKEN_ID,
+        1,
+        &rust_biguint!(expected_rewards),
+        None,
+    );
+
+    // exit in the same block, so there are no new rewards
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &ct_wrapper,
+            FARM_TOKEN_ID[FIRST_FARM_INDEX],
+            2,
+            &rust_biguint!(farm_token_amount),
+            |sc| {
+                let mut farm_args = ManagedVec::new();
+                farm_args.push(managed_buffer!(farm_addr.as_bytes()));
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::ExitFarm, farm_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(FARMING_TOKEN_ID[FIRST_FARM_INDEX]),
+                    0,
+                    managed_biguint!(farm_token_amount),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &first_user_addr,
+        FARMING_TOKEN_ID[FIRST_FARM_INDEX],
+        &rust_biguint!(FARMING_TOKEN_BALANCE),
+    );
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &first_user_addr,
+        FARM_TOKEN_ID[FIRST_FARM_INDEX],
+        2,
+        &rust_biguint!(0),
+        None,
+    );
+}
+
+#[test]
+fn lock_tokens_task_test() {
+    let farm_setup = FarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+    );
+    let b_mock = farm_setup.b_mock;
+    let owner = farm_setup.owner;
+    let first_user_addr = farm_setup.first_user;
+    let energy_factory_wrapper = &farm_setup.energy_factory_wrapper;
+
+    let ct_wrapper = b_mock.borrow_mut().create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner),
+        composable_tasks::contract_obj,
+        "composable tasks",
+    );
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &ct_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_energy_factory_address(managed_address!(energy_factory_wrapper.address_ref()));
+        })
+        .assert_ok();
+
+    // the locked tokens are created by the energy factory and the base tokens are burned
+    b_mock.borrow_mut().set_esdt_local_roles(
+        ct_wrapper.address_ref(),
+        REWARD_TOKEN_ID,
+        &[EsdtLocalRole::Burn],
+    );
+
+    let lock_amount = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        REWARD_TOKEN_ID,
+        &rust_biguint!(lock_amount * 2),
+    );
+
+    let lock_tokens_tasks = || {
+        let mut lock_args = ManagedVec::new();
+        lock_args.push(managed_buffer!(LOCK_OPTIONS[0].to_string().as_bytes()));
+
+        let mut tasks = MultiValueEncoded::new();
+        tasks.push((TaskType::LockTokens, lock_args).into());
+
+        tasks
+    };
+    let expected_token_out = || {
+        EgldOrEsdtTokenPayment::new(
+            EgldOrEsdtTokenIdentifier::esdt(LOCKED_REWARD_TOKEN_ID),
+            0,
+            managed_biguint!(lock_amount),
+        )
+    };
+
+    // the contract is not whitelisted to lock for the user
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &ct_wrapper,
+            REWARD_TOKEN_ID,
+            0,
+            &rust_biguint!(lock_amount),
+            |sc| {
+                sc.compose_tasks(expected_token_out(), lock_tokens_tasks());
+            },
+        )
+        .assert_user_error("Item not whitelisted");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, energy_factory_wrapper, &rust_biguint!(0), |sc| {
+            sc.add_sc_address_to_whitelist(managed_address!(ct_wrapper.address_ref()));
+        })
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &ct_wrapper,
+            REWARD_TOKEN_ID,
+            0,
+            &rust_biguint!(lock_amount),
+            |sc| {
+                sc.compose_tasks(expected_token_out(), lock_tokens_tasks());
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_esdt_balance(
+        &first_user_addr,
+        REWARD_TOKEN_ID,
+        &rust_biguint!(lock_amount),
+    );
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &first_user_addr,
+        LOCKED_REWARD_TOKEN_ID,
+        1,
+        &rust_biguint!(lock_amount),
+        None,
+    );
+
+    // the energy goes to the user, not to the contract
+    b_mock
+        .borrow()
+        .execute_query(energy_factory_wrapper, |sc| {
+            assert!(!sc.user_energy(&managed_address!(&first_user_addr)).is_empty());
+            assert!(sc
+                .user_energy(&managed_address!(ct_wrapper.address_ref()))
+                .is_empty());
+        })
+        .assert_ok();
+}
+
+#[test]
+fn lock_tokens_and_send_part_task_test() {
+    let farm_setup = FarmSetup::new(
+        farm_with_locked_rewards::contract_obj,
+        energy_factory::contract_obj,
+    );
+    let b_mock = farm_setup.b_mock;
+    let owner = farm_setup.owner;
+    let first_user_addr = farm_setup.first_user;
+    let second_user_addr = farm_setup.second_user;
+    let energy_factory_wrapper = &farm_setup.energy_factory_wrapper;
+
+    let ct_wrapper = b_mock.borrow_mut().create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner),
+        composable_tasks::contract_obj,
+        "composable tasks",
+    );
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, &ct_wrapper, &rust_biguint!(0), |sc| {
+            sc.set_energy_factory_address(managed_address!(energy_factory_wrapper.address_ref()));
+        })
+        .assert_ok();
+    b_mock
+        .borrow_mut()
+        .execute_tx(&owner, energy_factory_wrapper, &rust_biguint!(0), |sc| {
+            sc.add_sc_address_to_whitelist(managed_address!(ct_wrapper.address_ref()));
+        })
+        .assert_ok();
+    b_mock.borrow_mut().set_esdt_local_roles(
+        ct_wrapper.address_ref(),
+        REWARD_TOKEN_ID,
+        &[EsdtLocalRole::Burn],
+    );
+
+    let lock_amount = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        REWARD_TOKEN_ID,
+        &rust_biguint!(lock_amount),
+    );
+
+    // 40% of the locked tokens go to the second user, the rest to the caller
+    let sent_amount = 40_000_000u64;
+    let expected_balance = 60_000_000u64;
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &ct_wrapper,
+            REWARD_TOKEN_ID,
+            0,
+            &rust_biguint!(lock_amount),
+            |sc| {
+                let mut lock_args = ManagedVec::new();
+                lock_args.push(managed_buffer!(LOCK_OPTIONS[0].to_string().as_bytes()));
+
+                let mut send_args = ManagedVec::new();
+                send_args.push(managed_buffer!(second_user_addr.as_bytes()));
+                send_args.push(managed_buffer!(b"percentage"));
+                send_args.push(managed_biguint!(4_000).to_bytes_be_buffer());
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::LockTokens, lock_args).into());
+                tasks.push((TaskType::SendAndContinue, send_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(LOCKED_REWARD_TOKEN_ID),
+                    0,
+                    managed_biguint!(expected_balance),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &first_user_addr,
+        LOCKED_REWARD_TOKEN_ID,
+        1,
+        &rust_biguint!(expected_balance),
+        None,
+    );
+    b_mock.borrow().check_nft_balance::<Empty>(
+        &second_user_addr,
+        LOCKED_REWARD_TOKEN_ID,
+        1,
+        &rust_biguint!(sent_amount),
+        None,
+    );
+
+    // all the energy goes to the caller
+    b_mock
+        .borrow()
+        .execute_query(energy_factory_wrapper, |sc| {
+            assert!(!sc
+                .user_energy(&managed_address!(&first_user_addr))
+                .is_empty());
+            assert!(sc
+                .user_energy(&managed_address!(ct_wrapper.address_ref()))
+                .is_empty());
+        })
+        .assert_ok();
+}
@@ -1,7 +1,17 @@
 #![allow(deprecated)]
 
-use composable_tasks::compose_tasks::{TaskCall, TaskType};
-use composable_tasks_setup::{ComposableTasksSetup, TOKEN_IDS};
+use composable_tasks::{
+    compose_tasks::{TaskCall, TaskType},
+    config::ConfigModule,
+    integrator_fees::{IntegratorFee, IntegratorFeesModule},
+    legacy_task_decoder::LegacyTaskDecoderModule,
+    simulation::SimulationModule,
+    task_types::{
+        AssertMinBalanceArgs, SendAmount, SendAndContinueArgs, SendArgs, SwapArgs, SwapFunction,
+        Task, TaskWithSlots,
+    },
+};
+use composable_tasks_setup::{ComposableTasksSetup, LP_TOKEN_IDS, TOKEN_IDS};
 use multiversx_sc::types::{
     EgldOrEsdtTokenIdentifier, EgldOrEsdtTokenPayment, ManagedVec, MultiValueEncoded,
 };
@@ -1424,3 +1434,1604 @@ fn swap_router_send_task_test() {
         &rust_biguint!(expected_balance),
     );
 }
+
+#[test]
+fn add_liquidity_task_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+
+    let user_first_token_balance = 100_000_000u64;
+
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // The optimal amount is swapped, so there are no leftovers
+    let expected_lp_balance = 48_808_847u64;
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut add_liquidity_args = ManagedVec::new();
+                add_liquidity_args.push(managed_buffer!(TOKEN_IDS[1]));
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::AddLiquidity, add_liquidity_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(LP_TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(expected_lp_balance),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        LP_TOKEN_IDS[0],
+        &rust_biguint!(expected_lp_balance),
+    );
+    b_mock
+        .borrow_mut()
+        .check_esdt_balance(&first_user_addr, TOKEN_IDS[0], &rust_biguint!(0));
+    b_mock
+        .borrow_mut()
+        .check_esdt_balance(&first_user_addr, TOKEN_IDS[1], &rust_biguint!(0));
+}
+
+#[test]
+fn add_liquidity_with_ratio_task_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+
+    let user_first_token_balance = 100_000_000u64;
+
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // Half of the tokens are swapped, so the excess of the second token is returned
+    let expected_lp_balance = 47_619_047u64;
+    let expected_second_token_leftover = 4_535_148u64;
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut add_liquidity_args = ManagedVec::new();
+                add_liquidity_args.push(managed_buffer!(TOKEN_IDS[1]));
+                add_liquidity_args.push(managed_buffer!(&rust_biguint!(5_000u64).to_bytes_be()));
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::AddLiquidity, add_liquidity_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(LP_TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(expected_lp_balance),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        LP_TOKEN_IDS[0],
+        &rust_biguint!(expected_lp_balance),
+    );
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_second_token_leftover),
+    );
+}
+
+#[test]
+fn add_remove_liquidity_task_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+
+    let user_first_token_balance = 100_000_000u64;
+
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // The chain continues with the second token, the first token is returned
+    let expected_first_token_balance = 51_191_150u64;
+    let expected_second_token_balance = 93_074_819u64;
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut add_liquidity_args = ManagedVec::new();
+                add_liquidity_args.push(managed_buffer!(TOKEN_IDS[1]));
+
+                let mut remove_liquidity_args = ManagedVec::new();
+                remove_liquidity_args.push(managed_buffer!(TOKEN_IDS[1]));
+                remove_liquidity_args.push(managed_buffer!(TOKEN_IDS[0]));
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::AddLiquidity, add_liquidity_args).into());
+                tasks.push((TaskType::RemoveLiquidity, remove_liquidity_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[1]),
+                    0,
+                    managed_biguint!(expected_second_token_balance),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .check_esdt_balance(&first_user_addr, LP_TOKEN_IDS[0], &rust_biguint!(0));
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_first_token_balance),
+    );
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_second_token_balance),
+    );
+}
+
+#[test]
+fn add_remove_liquidity_min_out_task_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+
+    let user_first_token_balance = 100_000_000u64;
+
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // same amounts as add_remove_liquidity_task_test
+    let expected_lp_amount = 48_808_847u64;
+    let expected_second_token_balance = 93_074_819u64;
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut add_liquidity_args = ManagedVec::new();
+                add_liquidity_args.push(managed_buffer!(TOKEN_IDS[1]));
+                add_liquidity_args.push(managed_buffer!(b""));
+                add_liquidity_args
+                    .push(managed_biguint!(expected_lp_amount + 1).to_bytes_be_buffer());
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::AddLiquidity, add_liquidity_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(LP_TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(1),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_error(4, "Add liquidity: less LP tokens than the min amount");
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut add_liquidity_args = ManagedVec::new();
+                add_liquidity_args.push(managed_buffer!(TOKEN_IDS[1]));
+                add_liquidity_args.push(managed_buffer!(b""));
+                add_liquidity_args.push(managed_biguint!(expected_lp_amount).to_bytes_be_buffer());
+
+                let mut remove_liquidity_args = ManagedVec::new();
+                remove_liquidity_args.push(managed_buffer!(TOKEN_IDS[1]));
+                remove_liquidity_args.push(managed_buffer!(TOKEN_IDS[0]));
+                remove_liquidity_args
+                    .push(managed_biguint!(expected_second_token_balance).to_bytes_be_buffer());
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::AddLiquidity, add_liquidity_args).into());
+                tasks.push((TaskType::RemoveLiquidity, remove_liquidity_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[1]),
+                    0,
+                    managed_biguint!(expected_second_token_balance),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_second_token_balance),
+    );
+}
+
+#[test]
+fn swap_multiple_input_tokens_into_same_slot_add_liquidity_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+
+    let user_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_token_balance),
+    );
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(user_token_balance),
+    );
+
+    let input_tokens = [
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[0].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_token_balance),
+        },
+        TxTokenTransfer {
+            token_identifier: TOKEN_IDS[1].to_vec(),
+            nonce: 0,
+            value: rust_biguint!(user_token_balance),
+        },
+    ];
+
+    // 90_909_090 + 272_727_272 WEGLD are added as liquidity, with 3 WEGLD leftover
+    let expected_lp_balance = 167_748_413u64;
+    let expected_wegld_leftover = 3u64;
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_multi_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            &input_tokens,
+            |sc| {
+                let mut first_swap_args = ManagedVec::new();
+                first_swap_args.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+                first_swap_args.push(managed_buffer!(WEGLD_TOKEN_ID));
+                first_swap_args.push(managed_buffer!(b"1"));
+
+                let second_swap_args = first_swap_args.clone();
+
+                let mut add_liquidity_args = ManagedVec::new();
+                add_liquidity_args.push(managed_buffer!(TOKEN_IDS[3]));
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::Swap, 0, 0, first_swap_args).into());
+                tasks.push((TaskType::Swap, 1, 0, second_swap_args).into());
+                tasks.push((TaskType::AddLiquidity, 0, 0, add_liquidity_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(LP_TOKEN_IDS[3]),
+                    0,
+                    managed_biguint!(expected_lp_balance),
+                );
+
+                sc.compose_tasks_with_slots(expected_token_out, None, tasks);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        LP_TOKEN_IDS[3],
+        &rust_biguint!(expected_lp_balance),
+    );
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(expected_wegld_leftover),
+    );
+    b_mock
+        .borrow_mut()
+        .check_esdt_balance(&first_user_addr, TOKEN_IDS[0], &rust_biguint!(0));
+    b_mock
+        .borrow_mut()
+        .check_esdt_balance(&first_user_addr, TOKEN_IDS[1], &rust_biguint!(0));
+}
+
+#[test]
+fn swap_send_and_continue_split_payout_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+    let merchant_addr = b_mock.borrow_mut().create_user_account(&rust_biguint!(0));
+    let fee_wallet_addr = b_mock.borrow_mut().create_user_account(&rust_biguint!(0));
+
+    let user_first_token_balance = 200_000_000u64;
+
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // 166_666_666 tokens out of the swap
+    let expected_merchant_balance = 158_333_332u64;
+    let fee_amount = 1_000_000u64;
+    let expected_user_balance = 7_333_334u64;
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut swap_args = ManagedVec::new();
+                swap_args.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+                swap_args.push(managed_buffer!(TOKEN_IDS[0]));
+                swap_args.push(managed_buffer!(b"1"));
+
+                let mut merchant_send_args = ManagedVec::new();
+                merchant_send_args.push(managed_buffer!(merchant_addr.as_bytes()));
+                merchant_send_args.push(managed_buffer!(b"percentage"));
+                merchant_send_args.push(managed_biguint!(9_500).to_bytes_be_buffer());
+
+                let mut fee_send_args = ManagedVec::new();
+                fee_send_args.push(managed_buffer!(fee_wallet_addr.as_bytes()));
+                fee_send_args.push(managed_buffer!(b"fixedAmount"));
+                fee_send_args.push(managed_biguint!(fee_amount).to_bytes_be_buffer());
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::Swap, swap_args).into());
+                tasks.push((TaskType::SendAndContinue, merchant_send_args).into());
+                tasks.push((TaskType::SendAndContinue, fee_send_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(expected_user_balance),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().check_esdt_balance(
+        &merchant_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_merchant_balance),
+    );
+    b_mock
+        .borrow_mut()
+        .check_esdt_balance(&fee_wallet_addr, TOKEN_IDS[0], &rust_biguint!(fee_amount));
+
+    // The remainder is returned to the caller
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_user_balance),
+    );
+}
+
+#[test]
+fn typed_swap_single_task_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+
+    let user_first_token_balance = 200_000_000u64;
+
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    let expected_balance = 166_666_666u64;
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push(TaskWithSlots {
+                    input_slot: 0,
+                    output_slot: 0,
+                    task: Task::Swap(SwapArgs {
+                        function: SwapFunction::FixedInput,
+                        token_out: managed_token_id!(TOKEN_IDS[0]),
+                        min_out: managed_biguint!(1),
+                    }),
+                });
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(expected_balance),
+                );
+
+                sc.compose_typed_tasks(
+                    ManagedVec::from_single_item(expected_token_out),
+                    None,
+                    tasks,
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_balance),
+    );
+}
+
+#[test]
+fn invalid_task_argument_error_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+
+    let user_first_token_balance = 200_000_000u64;
+    b_mock
+        .borrow_mut()
+        .set_egld_balance(&first_user_addr, &rust_biguint!(user_first_token_balance));
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut swap_args = ManagedVec::new();
+                swap_args.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+                swap_args.push(managed_buffer!(b"not a token"));
+                swap_args.push(managed_buffer!(b"1"));
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::WrapEGLD, ManagedVec::new()).into());
+                tasks.push((TaskType::Swap, swap_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(1),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_error(4, "Task 1: invalid token_out");
+}
+
+#[test]
+fn typed_invalid_task_argument_error_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+    let second_user_addr = composable_tasks_setup.second_user;
+
+    let user_first_token_balance = 200_000_000u64;
+    b_mock
+        .borrow_mut()
+        .set_egld_balance(&first_user_addr, &rust_biguint!(user_first_token_balance));
+
+    let min_expected_tokens_out = || {
+        ManagedVec::from_single_item(EgldOrEsdtTokenPayment::new(
+            EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+            0,
+            managed_biguint!(1),
+        ))
+    };
+
+    // same error as in invalid_task_argument_error_test
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push(TaskWithSlots {
+                    input_slot: 0,
+                    output_slot: 0,
+                    task: Task::WrapEGLD,
+                });
+                tasks.push(TaskWithSlots {
+                    input_slot: 0,
+                    output_slot: 0,
+                    task: Task::Swap(SwapArgs {
+                        function: SwapFunction::FixedInput,
+                        token_out: managed_token_id!(b"not a token"),
+                        min_out: managed_biguint!(1),
+                    }),
+                });
+
+                sc.compose_typed_tasks(min_expected_tokens_out(), None, tasks);
+            },
+        )
+        .assert_error(4, "Task 1: invalid token_out");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push(TaskWithSlots {
+                    input_slot: 0,
+                    output_slot: 0,
+                    task: Task::SendAndContinue(SendAndContinueArgs {
+                        destination: managed_address!(&second_user_addr),
+                        amount: SendAmount::Fixed(managed_biguint!(0)),
+                        opt_call: None,
+                    }),
+                });
+
+                sc.compose_typed_tasks(min_expected_tokens_out(), None, tasks);
+            },
+        )
+        .assert_error(4, "Task 0: invalid amount");
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&composable_tasks_setup.ct_wrapper, |sc| {
+            let mut tasks = MultiValueEncoded::new();
+            tasks.push(TaskWithSlots {
+                input_slot: 0,
+                output_slot: 0,
+                task: Task::AssertMinBalance(AssertMinBalanceArgs {
+                    token_id: EgldOrEsdtTokenIdentifier::egld(),
+                    min_amount: managed_biguint!(0),
+                }),
+            });
+
+            let payments = ManagedVec::from_single_item(EgldOrEsdtTokenPayment::new(
+                EgldOrEsdtTokenIdentifier::egld(),
+                0,
+                managed_biguint!(user_first_token_balance),
+            ));
+            let _ = sc.simulate_compose_typed_tasks(payments, tasks);
+        })
+        .assert_error(4, "Task 0: invalid min_amount");
+}
+
+#[test]
+fn legacy_zero_min_out_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let pair_addr = composable_tasks_setup.pair_setups[0]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&composable_tasks_setup.ct_wrapper, |sc| {
+            let mut swap_args = ManagedVec::new();
+            swap_args.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+            swap_args.push(managed_buffer!(TOKEN_IDS[0]));
+            swap_args.push(managed_buffer!(b""));
+
+            match sc.decode_legacy_task(0, TaskType::Swap, swap_args) {
+                Task::Swap(args) => assert_eq!(args.min_out, managed_biguint!(0)),
+                _ => panic!("Invalid task"),
+            }
+
+            let mut router_swap_args = ManagedVec::new();
+            router_swap_args.push(managed_buffer!(pair_addr.as_bytes()));
+            router_swap_args.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+            router_swap_args.push(managed_buffer!(TOKEN_IDS[0]));
+            router_swap_args.push(managed_buffer!(b""));
+
+            match sc.decode_legacy_task(0, TaskType::RouterSwap, router_swap_args) {
+                Task::RouterSwap(args) => {
+                    assert_eq!(args.swap_operations.get(0).min_out, managed_biguint!(0))
+                }
+                _ => panic!("Invalid task"),
+            }
+        })
+        .assert_ok();
+}
+
+#[test]
+fn assert_min_balance_task_fail_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+
+    let user_first_token_balance = 200_000_000u64;
+
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // the swap outputs 166_666_666 tokens
+    let min_balance = 170_000_000u64;
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut swap_args = ManagedVec::new();
+                swap_args.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+                swap_args.push(managed_buffer!(TOKEN_IDS[0]));
+                swap_args.push(managed_buffer!(b"1"));
+
+                let mut assert_args = ManagedVec::new();
+                assert_args.push(managed_buffer!(TOKEN_IDS[0]));
+                assert_args.push(managed_biguint!(min_balance).to_bytes_be_buffer());
+
+                let mut swap_back_args = ManagedVec::new();
+                swap_back_args.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+                swap_back_args.push(managed_buffer!(WEGLD_TOKEN_ID));
+                swap_back_args.push(managed_buffer!(b"1"));
+
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push((TaskType::Swap, swap_args).into());
+                tasks.push((TaskType::AssertMinBalance, assert_args).into());
+                tasks.push((TaskType::Swap, swap_back_args).into());
+
+                let expected_token_out = EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(WEGLD_TOKEN_ID),
+                    0,
+                    managed_biguint!(1),
+                );
+
+                sc.compose_tasks(expected_token_out, tasks);
+            },
+        )
+        .assert_error(4, "Task 1: balance less than the min amount");
+}
+
+#[test]
+fn typed_send_must_be_last_task_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+    let second_user_addr = composable_tasks_setup.second_user;
+
+    let user_first_token_balance = 200_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    let expected_balance = 166_666_666u64;
+    let swap_task = || TaskWithSlots {
+        input_slot: 0,
+        output_slot: 0,
+        task: Task::Swap(SwapArgs {
+            function: SwapFunction::FixedInput,
+            token_out: managed_token_id!(TOKEN_IDS[0]),
+            min_out: managed_biguint!(1),
+        }),
+    };
+    let send_task = || TaskWithSlots {
+        input_slot: 0,
+        output_slot: 0,
+        task: Task::SendEgldOrEsdt(SendArgs {
+            destination: managed_address!(&second_user_addr),
+        }),
+    };
+    let min_expected_tokens_out = || {
+        ManagedVec::from_single_item(EgldOrEsdtTokenPayment::new(
+            EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+            0,
+            managed_biguint!(expected_balance),
+        ))
+    };
+
+    // composeTasks would skip the swap, so the call fails instead
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push(send_task());
+                tasks.push(swap_task());
+
+                sc.compose_typed_tasks(min_expected_tokens_out(), None, tasks);
+            },
+        )
+        .assert_error(4, "Task 1: no task can follow SendEgldOrEsdt");
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut tasks = MultiValueEncoded::new();
+                tasks.push(swap_task());
+                tasks.push(send_task());
+
+                sc.compose_typed_tasks(min_expected_tokens_out(), None, tasks);
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().check_esdt_balance(
+        &second_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_balance),
+    );
+    b_mock
+        .borrow_mut()
+        .check_esdt_balance(&first_user_addr, TOKEN_IDS[0], &rust_biguint!(0));
+}
+
+#[test]
+fn typed_swap_fixed_output_check_leftover_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let first_user_addr = composable_tasks_setup.first_user;
+
+    let user_first_token_balance = 200_000_000u64;
+
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // 111_111_112 WEGLD are swapped for the exact output amount
+    let expected_balance = 100_000_000u64;
+    let expected_leftover = 88_888_888u64;
+
+    let typed_tasks = || {
+        let mut tasks = MultiValueEncoded::new();
+        tasks.push(TaskWithSlots {
+            input_slot: 0,
+            output_slot: 0,
+            task: Task::Swap(SwapArgs {
+                function: SwapFunction::FixedOutput,
+                token_out: managed_token_id!(TOKEN_IDS[0]),
+                min_out: managed_biguint!(expected_balance),
+            }),
+        });
+
+        tasks
+    };
+    let min_expected_tokens_out = |leftover_amount: u64| {
+        let mut min_expected_tokens_out = ManagedVec::new();
+        min_expected_tokens_out.push(EgldOrEsdtTokenPayment::new(
+            EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+            0,
+            managed_biguint!(expected_balance),
+        ));
+        min_expected_tokens_out.push(EgldOrEsdtTokenPayment::new(
+            EgldOrEsdtTokenIdentifier::esdt(WEGLD_TOKEN_ID),
+            0,
+            managed_biguint!(leftover_amount),
+        ));
+
+        min_expected_tokens_out
+    };
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                sc.compose_typed_tasks(ManagedVec::new(), None, typed_tasks());
+            },
+        )
+        .assert_error(4, "No expected outputs given");
+
+    // the WEGLD leftover is sent back as well, so it needs a min amount
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let mut expected_tokens_out = min_expected_tokens_out(expected_leftover);
+                expected_tokens_out.remove(1);
+
+                sc.compose_typed_tasks(expected_tokens_out, None, typed_tasks());
+            },
+        )
+        .assert_error(4, "Output 1: no min amount given");
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                sc.compose_typed_tasks(
+                    min_expected_tokens_out(expected_leftover + 1),
+                    None,
+                    typed_tasks(),
+                );
+            },
+        )
+        .assert_error(4, "Expected output 1: less than the min amount");
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                sc.compose_typed_tasks(
+                    min_expected_tokens_out(expected_leftover),
+                    None,
+                    typed_tasks(),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_balance),
+    );
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(expected_leftover),
+    );
+}
+
+#[test]
+fn simulate_wrap_swap_add_liquidity_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+
+    let egld_amount = 200_000_000u64;
+    let expected_swap_output = 166_666_666u64;
+    // 80_123_449 tokens are swapped before adding liquidity
+    let expected_lp_amount = 80_123_448u64;
+    let expected_leftover = 2u64;
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&composable_tasks_setup.ct_wrapper, |sc| {
+            let mut swap_args = ManagedVec::new();
+            swap_args.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+            swap_args.push(managed_buffer!(TOKEN_IDS[0]));
+            swap_args.push(managed_buffer!(b"1"));
+
+            let mut add_liquidity_args = ManagedVec::new();
+            add_liquidity_args.push(managed_buffer!(TOKEN_IDS[1]));
+
+            let mut tasks = MultiValueEncoded::new();
+            tasks.push((TaskType::WrapEGLD, ManagedVec::new()).into());
+            tasks.push((TaskType::Swap, swap_args).into());
+            tasks.push((TaskType::AddLiquidity, add_liquidity_args).into());
+
+            let payment = EgldOrEsdtTokenPayment::new(
+                EgldOrEsdtTokenIdentifier::egld(),
+                0,
+                managed_biguint!(egld_amount),
+            );
+            let (step_outputs, final_outputs) =
+                sc.simulate_compose_tasks(payment, tasks).into_tuple();
+
+            assert_eq!(step_outputs.len(), 3);
+            assert_eq!(
+                step_outputs.get(0),
+                EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(WEGLD_TOKEN_ID),
+                    0,
+                    managed_biguint!(egld_amount),
+                )
+            );
+            assert_eq!(
+                step_outputs.get(1),
+                EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(expected_swap_output),
+                )
+            );
+
+            let expected_lp_payment = EgldOrEsdtTokenPayment::new(
+                EgldOrEsdtTokenIdentifier::esdt(LP_TOKEN_IDS[0]),
+                0,
+                managed_biguint!(expected_lp_amount),
+            );
+            assert_eq!(step_outputs.get(2), expected_lp_payment);
+
+            assert_eq!(final_outputs.len(), 2);
+            assert_eq!(final_outputs.get(0), expected_lp_payment);
+            assert_eq!(
+                final_outputs.get(1),
+                EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(expected_leftover),
+                )
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn simulate_typed_swap_into_same_slot_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+
+    let wegld_amount = 200_000_000u64;
+    let expected_swap_output = 166_666_666u64;
+    let second_payment_amount = 1_000u64;
+
+    b_mock
+        .borrow_mut()
+        .execute_query(&composable_tasks_setup.ct_wrapper, |sc| {
+            let mut payments = ManagedVec::new();
+            payments.push(EgldOrEsdtTokenPayment::new(
+                EgldOrEsdtTokenIdentifier::esdt(WEGLD_TOKEN_ID),
+                0,
+                managed_biguint!(wegld_amount),
+            ));
+            payments.push(EgldOrEsdtTokenPayment::new(
+                EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+                0,
+                managed_biguint!(second_payment_amount),
+            ));
+
+            // the second swap has the same input and output token, so the payment is kept
+            let mut tasks = MultiValueEncoded::new();
+            for input_slot in 0..2 {
+                tasks.push(TaskWithSlots {
+                    input_slot,
+                    output_slot: 0,
+                    task: Task::Swap(SwapArgs {
+                        function: SwapFunction::FixedInput,
+                        token_out: managed_token_id!(TOKEN_IDS[0]),
+                        min_out: managed_biguint!(1),
+                    }),
+                });
+            }
+
+            let (step_outputs, final_outputs) =
+                sc.simulate_compose_typed_tasks(payments, tasks).into_tuple();
+
+            assert_eq!(step_outputs.len(), 2);
+            assert_eq!(
+                step_outputs.get(0),
+                EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(expected_swap_output),
+                )
+            );
+            assert_eq!(
+                step_outputs.get(1),
+                EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(second_payment_amount),
+                )
+            );
+
+            assert_eq!(final_outputs.len(), 1);
+            assert_eq!(
+                final_outputs.get(0),
+                EgldOrEsdtTokenPayment::new(
+                    EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+                    0,
+                    managed_biguint!(expected_swap_output + second_payment_amount),
+                )
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn call_whitelisted_contract_task_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let owner = composable_tasks_setup.owner;
+    let first_user_addr = composable_tasks_setup.first_user;
+    let pair_addr = composable_tasks_setup.pair_setups[0]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    let call_contract_tasks = || {
+        let mut call_args = ManagedVec::new();
+        call_args.push(managed_buffer!(pair_addr.as_bytes()));
+        call_args.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+        call_args.push(managed_buffer!(TOKEN_IDS[1]));
+        call_args.push(managed_buffer!(TOKEN_IDS[1]));
+        call_args.push(managed_biguint!(1).to_bytes_be_buffer());
+
+        let mut tasks = MultiValueEncoded::new();
+        tasks.push((TaskType::CallContract, call_args).into());
+
+        tasks
+    };
+
+    let expected_balance = 181_818_181u64;
+    let expected_token_out = || {
+        EgldOrEsdtTokenPayment::new(
+            EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[1]),
+            0,
+            managed_biguint!(expected_balance),
+        )
+    };
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                sc.compose_tasks(expected_token_out(), call_contract_tasks());
+            },
+        )
+        .assert_error(4, "Contract call not whitelisted");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &composable_tasks_setup.ct_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut endpoint_names = MultiValueEncoded::new();
+                endpoint_names.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+
+                sc.add_whitelisted_contract_calls(managed_address!(&pair_addr), endpoint_names);
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                sc.compose_tasks(expected_token_out(), call_contract_tasks());
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_balance),
+    );
+    b_mock
+        .borrow_mut()
+        .check_esdt_balance(&first_user_addr, TOKEN_IDS[0], &rust_biguint!(0));
+}
+
+#[test]
+fn send_and_continue_with_whitelisted_call_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let owner = composable_tasks_setup.owner;
+    let first_user_addr = composable_tasks_setup.first_user;
+    let pair_addr = composable_tasks_setup.pair_setups[0]
+        .pair_wrapper
+        .address_ref()
+        .clone();
+
+    let user_first_token_balance = 100_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    // half of the payment is swapped through the call, the other half is passed on
+    let send_and_continue_tasks = || {
+        let mut send_args = ManagedVec::new();
+        send_args.push(managed_buffer!(pair_addr.as_bytes()));
+        send_args.push(managed_buffer!(b"percentage"));
+        send_args.push(managed_biguint!(5_000).to_bytes_be_buffer());
+        send_args.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+        send_args.push(managed_buffer!(TOKEN_IDS[1]));
+        send_args.push(managed_biguint!(1).to_bytes_be_buffer());
+
+        let mut tasks = MultiValueEncoded::new();
+        tasks.push((TaskType::SendAndContinue, send_args).into());
+
+        tasks
+    };
+
+    let remaining_amount = 50_000_000u64;
+    let expected_token_out = || {
+        EgldOrEsdtTokenPayment::new(
+            EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+            0,
+            managed_biguint!(remaining_amount),
+        )
+    };
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                sc.compose_tasks(expected_token_out(), send_and_continue_tasks());
+            },
+        )
+        .assert_error(4, "Contract call not whitelisted");
+
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &composable_tasks_setup.ct_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut endpoint_names = MultiValueEncoded::new();
+                endpoint_names.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+
+                sc.add_whitelisted_contract_calls(managed_address!(&pair_addr), endpoint_names);
+            },
+        )
+        .assert_ok();
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            TOKEN_IDS[0],
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                sc.compose_tasks(expected_token_out(), send_and_continue_tasks());
+            },
+        )
+        .assert_ok();
+
+    // The swap output of the call is returned to the caller
+    let expected_swap_output = 95_238_095u64;
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[1],
+        &rust_biguint!(expected_swap_output),
+    );
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(remaining_amount),
+    );
+    b_mock.borrow_mut().check_esdt_balance(
+        composable_tasks_setup.ct_wrapper.address_ref(),
+        TOKEN_IDS[1],
+        &rust_biguint!(0),
+    );
+}
+
+#[test]
+fn swap_with_integrator_fee_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let owner = composable_tasks_setup.owner;
+    let first_user_addr = composable_tasks_setup.first_user;
+    let integrator_addr = composable_tasks_setup.second_user;
+
+    // max 1% fee
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &composable_tasks_setup.ct_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_max_integrator_fee_bps(100);
+            },
+        )
+        .assert_ok();
+
+    let user_first_token_balance = 200_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    let swap_tasks = || {
+        let mut swap_args = ManagedVec::new();
+        swap_args.push(managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME));
+        swap_args.push(managed_buffer!(TOKEN_IDS[0]));
+        swap_args.push(managed_buffer!(b"1"));
+
+        let mut tasks = MultiValueEncoded::new();
+        tasks.push((TaskType::Swap, swap_args).into());
+
+        tasks
+    };
+
+    // 166_666_666 tokens out, of which 1% goes to the integrator
+    let expected_fee = 1_666_666u64;
+    let expected_balance = 165_000_000u64;
+    let expected_token_out = || {
+        EgldOrEsdtTokenPayment::new(
+            EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+            0,
+            managed_biguint!(expected_balance),
+        )
+    };
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                sc.compose_tasks_with_integrator_fee(
+                    managed_address!(&integrator_addr),
+                    200,
+                    expected_token_out(),
+                    swap_tasks(),
+                );
+            },
+        )
+        .assert_error(4, "Integrator fee too high");
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                sc.compose_tasks_with_integrator_fee(
+                    managed_address!(&integrator_addr),
+                    100,
+                    expected_token_out(),
+                    swap_tasks(),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_balance),
+    );
+    b_mock.borrow_mut().check_esdt_balance(
+        &integrator_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_fee),
+    );
+}
+
+#[test]
+fn typed_swap_with_integrator_fee_test() {
+    let composable_tasks_setup = ComposableTasksSetup::new(
+        pair::contract_obj,
+        router::contract_obj,
+        multiversx_wegld_swap_sc::contract_obj,
+        composable_tasks::contract_obj,
+    );
+
+    let b_mock = composable_tasks_setup.b_mock;
+    let owner = composable_tasks_setup.owner;
+    let first_user_addr = composable_tasks_setup.first_user;
+    let integrator_addr = composable_tasks_setup.second_user;
+
+    // max 1% fee
+    b_mock
+        .borrow_mut()
+        .execute_tx(
+            &owner,
+            &composable_tasks_setup.ct_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_max_integrator_fee_bps(100);
+            },
+        )
+        .assert_ok();
+
+    let user_first_token_balance = 200_000_000u64;
+    b_mock.borrow_mut().set_esdt_balance(
+        &first_user_addr,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(user_first_token_balance),
+    );
+
+    let typed_tasks = || {
+        let mut tasks = MultiValueEncoded::new();
+        tasks.push(TaskWithSlots {
+            input_slot: 0,
+            output_slot: 0,
+            task: Task::Swap(SwapArgs {
+                function: SwapFunction::FixedInput,
+                token_out: managed_token_id!(TOKEN_IDS[0]),
+                min_out: managed_biguint!(1),
+            }),
+        });
+
+        tasks
+    };
+
+    // 166_666_666 tokens out, of which 1% goes to the integrator
+    let expected_fee = 1_666_666u64;
+    let expected_balance = 165_000_000u64;
+    let min_expected_tokens_out = |amount: u64| {
+        ManagedVec::from_single_item(EgldOrEsdtTokenPayment::new(
+            EgldOrEsdtTokenIdentifier::esdt(TOKEN_IDS[0]),
+            0,
+            managed_biguint!(amount),
+        ))
+    };
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let integrator_fee = IntegratorFee {
+                    integrator: managed_address!(&integrator_addr),
+                    fee_bps: 100,
+                };
+                sc.compose_typed_tasks(
+                    min_expected_tokens_out(expected_balance + 1),
+                    Some(integrator_fee),
+                    typed_tasks(),
+                );
+            },
+        )
+        .assert_error(4, "Expected output 0: less than the min amount");
+
+    b_mock
+        .borrow_mut()
+        .execute_esdt_transfer(
+            &first_user_addr,
+            &composable_tasks_setup.ct_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(user_first_token_balance),
+            |sc| {
+                let integrator_fee = IntegratorFee {
+                    integrator: managed_address!(&integrator_addr),
+                    fee_bps: 100,
+                };
+                sc.compose_typed_tasks(
+                    min_expected_tokens_out(expected_balance),
+                    Some(integrator_fee),
+                    typed_tasks(),
+                );
+            },
+        )
+        .assert_ok();
+
+    b_mock.borrow_mut().check_esdt_balance(
+        &first_user_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_balance),
+    );
+    b_mock.borrow_mut().check_esdt_balance(
+        &integrator_addr,
+        TOKEN_IDS[0],
+        &rust_biguint!(expected_fee),
+    );
+}
@@ -6,9 +6,9 @@
 
 // Init:                                 1
 // Upgrade:                              1
-// Endpoints:                            4
+// Endpoints:                           16
 // Async Callback (empty):               1
-// Total number of exported functions:   7
+// Total number of exported functions:  19
 
 #![no_std]
 
@@ -21,9 +21,21 @@ multiversx_sc_wasm_adapter::endpoints! {
         init => init
         upgrade => upgrade
         composeTasks => compose_tasks
+        composeTasksWithIntegratorFee => compose_tasks_with_integrator_fee
+        composeTasksWithSlots => compose_tasks_with_slots
+        composeTypedTasks => compose_typed_tasks
         setWrapEgldAddr => set_wrap_egld_address
         setRouterAddr => set_router_address
+        setEnergyFactoryAddr => set_energy_factory_address
+        addWhitelistedContractCalls => add_whitelisted_contract_calls
+        removeWhitelistedContractCalls => remove_whitelisted_contract_calls
+        getWhitelistedContractCalls => whitelisted_contract_calls
         getPair => get_pair
+        setMaxIntegratorFeeBps => set_max_integrator_fee_bps
+        getMaxIntegratorFeeBps => max_integrator_fee_bps
+        simulateComposeTasks => simulate_compose_tasks
+        simulateComposeTasksWithSlots => simulate_compose_tasks_with_slots
+        simulateComposeTypedTasks => simulate_compose_typed_tasks
     )
 }
 
@@ -40,8 +40,12 @@ pub trait CreateFarmPosModule:
         let farm_address = self.farm_address().get();
 
         let mut first_token_payment = self.process_payment(first_payment, swap_operations);
-        let second_token_payment =
-            self.swap_half_input_payment_if_needed(&mut first_token_payment, pair_address.clone());
+        let max_price_deviation_bps = self.get_max_price_deviation(&pair_address, None);
+        let second_token_payment = self.swap_optimal_input_if_needed(
+            &mut first_token_payment,
+            pair_address.clone(),
+            max_price_deviation_bps,
+        );
 
         let (other_tokens, locked_tokens) = self.prepare_locked_payments(
             lock_epochs,
@@ -16,6 +16,7 @@ use common_structs::Epoch;
 #[multiversx_sc::contract]
 pub trait LockedTokenPosCreatorContract:
     configs::pairs_config::PairsConfigModule
+    + configs::price_deviation_config::PriceDeviationConfigModule
     + utils::UtilsModule
     + read_external_storage::ReadExternalStorageModule
     + energy_query::EnergyQueryModule
@@ -6,9 +6,9 @@
 
 // Init:                                 1
 // Upgrade:                              1
-// Endpoints:                            7
+// Endpoints:                           12
 // Async Callback (empty):               1
-// Total number of exported functions:  10
+// Total number of exported functions:  15
 
 #![no_std]
 
@@ -20,6 +20,11 @@ multiversx_sc_wasm_adapter::endpoints! {
     (
         init => init
         upgrade => upgrade
+        setMaxPriceDeviation => set_max_price_deviation
+        setPriceOracle => set_price_oracle
+        removePriceOracle => remove_price_oracle
+        getMaxPriceDeviation => max_price_deviation
+        getPriceOracle => price_oracle
         createEnergyPosition => create_energy_position
         setEnergyFactoryAddress => set_energy_factory_address
         getEnergyFactoryAddress => energy_factory_address